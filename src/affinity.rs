@@ -0,0 +1,131 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/affinity.rs
+
+//! CPU affinity pinning for the search workers.
+//!
+//! Workers are spread round-robin over the NUMA nodes, so the first threads
+//! land on different sockets before any node is filled up.
+
+use std::{fs, thread};
+
+#[cfg(target_os = "linux")]
+const CPU_SET_WORDS: usize = 16;
+
+#[cfg(target_os = "linux")]
+unsafe extern "C" {
+    fn sched_setaffinity(pid: i32, size: usize, mask: *const u64) -> i32;
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" {
+    fn GetCurrentThread() -> isize;
+    fn SetThreadAffinityMask(thread: isize, mask: usize) -> usize;
+}
+
+/// Parses a kernel cpu list such as `0-3,8,10-11`.
+fn parse_cpulist(list: &str) -> Vec<usize> {
+    let mut cpus: Vec<usize> = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        let bounds: Option<(usize, usize)> = match range.split_once('-') {
+            Some((start, end)) => start.parse().ok().zip(end.parse().ok()),
+            None => range.parse().ok().map(|cpu: usize| (cpu, cpu)),
+        };
+        if let Some((start, end)) = bounds {
+            cpus.extend(start..=end);
+        }
+    }
+    cpus
+}
+
+/// Interleaves the cpus of every node: first cpu of each node, then the second...
+fn interleave(nodes: &[Vec<usize>]) -> Vec<usize> {
+    let longest: usize = nodes.iter().map(Vec::len).max().unwrap_or(0);
+    (0..longest)
+        .flat_map(|index| {
+            nodes
+                .iter()
+                .filter_map(move |node| node.get(index).copied())
+        })
+        .collect()
+}
+
+/// Order in which the workers are assigned to the logical cpus.
+pub fn cpu_order() -> Vec<usize> {
+    let mut nodes: Vec<Vec<usize>> = Vec::new();
+    for node in 0.. {
+        match fs::read_to_string(format!("/sys/devices/system/node/node{node}/cpulist")) {
+            Ok(list) => nodes.push(parse_cpulist(&list)),
+            Err(_) => break,
+        }
+    }
+
+    let order: Vec<usize> = interleave(&nodes);
+    if order.is_empty() {
+        let cpus: usize = thread::available_parallelism().map_or(1, |cpus| cpus.get());
+        (0..cpus).collect()
+    } else {
+        order
+    }
+}
+
+/// Pins the calling thread to a logical cpu, returning whether it succeeded.
+#[cfg(target_os = "linux")]
+pub fn pin(cpu: usize) -> bool {
+    if cpu >= CPU_SET_WORDS * 64 {
+        return false;
+    }
+    let mut mask: [u64; CPU_SET_WORDS] = [0; CPU_SET_WORDS];
+    mask[cpu / 64] |= 1 << (cpu % 64);
+    unsafe { sched_setaffinity(0, size_of_val(&mask), mask.as_ptr()) == 0 }
+}
+
+/// Pins the calling thread to a logical cpu, returning whether it succeeded.
+#[cfg(target_os = "windows")]
+pub fn pin(cpu: usize) -> bool {
+    if cpu >= usize::BITS as usize {
+        return false;
+    }
+    unsafe { SetThreadAffinityMask(GetCurrentThread(), 1 << cpu) != 0 }
+}
+
+/// Pinning is not supported on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn pin(_cpu: usize) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cpu_order, interleave, parse_cpulist};
+
+    #[test]
+    fn test_parse_cpulist() {
+        assert_eq!(parse_cpulist("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpulist(""), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_interleave_nodes() {
+        let nodes: Vec<Vec<usize>> = vec![vec![0, 1, 2], vec![4, 5]];
+        assert_eq!(interleave(&nodes), vec![0, 4, 1, 5, 2]);
+        assert!(!cpu_order().is_empty());
+    }
+}
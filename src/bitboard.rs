@@ -0,0 +1,314 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/bitboard.rs
+
+//! Set-wise direction shifts, fills and attacks over BitBoards.
+
+use laura_core::{BitBoard, Color};
+
+const WHITE: usize = Color::White as usize;
+
+pub const NORTH: i32 = 8;
+pub const SOUTH: i32 = -8;
+pub const EAST: i32 = 1;
+pub const WEST: i32 = -1;
+pub const NORTH_EAST: i32 = 9;
+pub const NORTH_WEST: i32 = 7;
+pub const SOUTH_EAST: i32 = -7;
+pub const SOUTH_WEST: i32 = -9;
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+const FILE_B: u64 = FILE_A << 1;
+const FILE_G: u64 = FILE_H >> 1;
+const FILE_H: u64 = 0x8080_8080_8080_8080;
+
+/// Direction shifts and fills, implemented for [`BitBoard`].
+///
+/// Absolute directions are named after the compass, with north towards the
+/// eighth rank and east towards the h-file. Relative directions take the side
+/// as `const COLOR: usize` (`WHITE` or `BLACK`) and are seen from its point of
+/// view: forward is towards the opponent, and left is towards the a-file for
+/// White and the h-file for Black. They carry a `shift_` prefix because
+/// laura_core's inherent `BitBoard::forward`, `left` and `right` would shadow them.
+pub trait Shift: Sized {
+    /// Shifts every square one step towards `DIR`, dropping squares that leave the board.
+    fn shift<const DIR: i32>(self) -> Self;
+
+    /// Fills every square in front of the set, from the point of view of `COLOR`.
+    fn front_fill<const COLOR: usize>(self) -> Self;
+
+    /// Fills the whole file of every square of the set.
+    fn file_fill(self) -> Self;
+
+    #[inline(always)]
+    fn north(self) -> Self {
+        self.shift::<NORTH>()
+    }
+
+    #[inline(always)]
+    fn south(self) -> Self {
+        self.shift::<SOUTH>()
+    }
+
+    #[inline(always)]
+    fn east(self) -> Self {
+        self.shift::<EAST>()
+    }
+
+    #[inline(always)]
+    fn west(self) -> Self {
+        self.shift::<WEST>()
+    }
+
+    #[inline(always)]
+    fn north_east(self) -> Self {
+        self.shift::<NORTH_EAST>()
+    }
+
+    #[inline(always)]
+    fn north_west(self) -> Self {
+        self.shift::<NORTH_WEST>()
+    }
+
+    #[inline(always)]
+    fn south_east(self) -> Self {
+        self.shift::<SOUTH_EAST>()
+    }
+
+    #[inline(always)]
+    fn south_west(self) -> Self {
+        self.shift::<SOUTH_WEST>()
+    }
+
+    /// Shifts one rank forward from the point of view of `COLOR`.
+    #[inline(always)]
+    fn shift_forward<const COLOR: usize>(self) -> Self {
+        if COLOR == WHITE {
+            self.north()
+        } else {
+            self.south()
+        }
+    }
+
+    /// Shifts one rank backward from the point of view of `COLOR`.
+    #[inline(always)]
+    fn shift_backward<const COLOR: usize>(self) -> Self {
+        if COLOR == WHITE {
+            self.south()
+        } else {
+            self.north()
+        }
+    }
+
+    /// Shifts one file to the left from the point of view of `COLOR`.
+    #[inline(always)]
+    fn shift_left<const COLOR: usize>(self) -> Self {
+        if COLOR == WHITE {
+            self.west()
+        } else {
+            self.east()
+        }
+    }
+
+    /// Shifts one file to the right from the point of view of `COLOR`.
+    #[inline(always)]
+    fn shift_right<const COLOR: usize>(self) -> Self {
+        if COLOR == WHITE {
+            self.east()
+        } else {
+            self.west()
+        }
+    }
+
+    /// Shifts diagonally forward and to the left from the point of view of `COLOR`.
+    #[inline(always)]
+    fn shift_forward_left<const COLOR: usize>(self) -> Self {
+        if COLOR == WHITE {
+            self.north_west()
+        } else {
+            self.south_east()
+        }
+    }
+
+    /// Shifts diagonally forward and to the right from the point of view of `COLOR`.
+    #[inline(always)]
+    fn shift_forward_right<const COLOR: usize>(self) -> Self {
+        if COLOR == WHITE {
+            self.north_east()
+        } else {
+            self.south_west()
+        }
+    }
+
+    /// Squares attacked by a set of pawns of `COLOR`.
+    fn pawn_attacks<const COLOR: usize>(self) -> Self;
+
+    /// Squares attacked by a set of pawns of the opponent of `COLOR`, which
+    /// capture backward from `COLOR`'s point of view.
+    fn enemy_pawn_attacks<const COLOR: usize>(self) -> Self;
+
+    /// Squares attacked by a set of knights.
+    fn knight_attacks(self) -> Self;
+
+    /// Squares attacked by a set of kings.
+    fn king_attacks(self) -> Self;
+}
+
+impl Shift for BitBoard {
+    #[inline(always)]
+    fn shift<const DIR: i32>(self) -> Self {
+        let shifted: u64 = if DIR > 0 {
+            self.0.wrapping_shl(DIR as u32)
+        } else {
+            self.0.wrapping_shr(DIR.unsigned_abs())
+        };
+
+        // Horizontal components wrap around the board edges
+        let mask: u64 = match DIR.rem_euclid(8) {
+            1 => !FILE_A,
+            7 => !FILE_H,
+            _ => !0,
+        };
+
+        BitBoard(shifted & mask)
+    }
+
+    #[inline(always)]
+    fn front_fill<const COLOR: usize>(self) -> Self {
+        let mut bb: u64 = self.0;
+        if COLOR == WHITE {
+            bb |= bb << 8;
+            bb |= bb << 16;
+            bb |= bb << 32;
+            BitBoard(bb << 8)
+        } else {
+            bb |= bb >> 8;
+            bb |= bb >> 16;
+            bb |= bb >> 32;
+            BitBoard(bb >> 8)
+        }
+    }
+
+    #[inline(always)]
+    fn pawn_attacks<const COLOR: usize>(self) -> Self {
+        BitBoard(self.shift_forward_left::<COLOR>().0 | self.shift_forward_right::<COLOR>().0)
+    }
+
+    #[inline(always)]
+    fn enemy_pawn_attacks<const COLOR: usize>(self) -> Self {
+        let behind: BitBoard = self.shift_backward::<COLOR>();
+        BitBoard(behind.east().0 | behind.west().0)
+    }
+
+    #[inline(always)]
+    fn knight_attacks(self) -> Self {
+        let bb: u64 = self.0;
+        let one: u64 = ((bb << 1) & !FILE_A) | ((bb >> 1) & !FILE_H);
+        let two: u64 = ((bb << 2) & !(FILE_A | FILE_B)) | ((bb >> 2) & !(FILE_G | FILE_H));
+        BitBoard((one << 16) | (one >> 16) | (two << 8) | (two >> 8))
+    }
+
+    #[inline(always)]
+    fn king_attacks(self) -> Self {
+        let sides: u64 = self.east().0 | self.west().0;
+        let row: u64 = sides | self.0;
+        BitBoard(sides | (row << 8) | (row >> 8))
+    }
+
+    #[inline(always)]
+    fn file_fill(self) -> Self {
+        let mut bb: u64 = self.0;
+        bb |= bb << 8;
+        bb |= bb << 16;
+        bb |= bb << 32;
+        bb |= bb >> 8;
+        bb |= bb >> 16;
+        bb |= bb >> 32;
+        BitBoard(bb)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Shift;
+    use laura_core::{BitBoard, Square, get_king_attacks, get_knight_attacks};
+
+    #[test]
+    fn test_shifts() {
+        // a1 and h1
+        let corners: BitBoard = BitBoard(0x81);
+        assert_eq!(corners.north().0, 0x8100);
+        assert_eq!(corners.east().0, 0x02);
+        assert_eq!(corners.west().0, 0x40);
+        assert_eq!(corners.north_east().0, 0x0200);
+        assert_eq!(corners.north_west().0, 0x4000);
+        assert_eq!(corners.south().0, 0);
+    }
+
+    #[test]
+    fn test_fills() {
+        // e4
+        let square: BitBoard = BitBoard(1 << 28);
+        assert_eq!(square.front_fill::<0>().0, 0x1010_1010_0000_0000);
+        assert_eq!(square.front_fill::<1>().0, 0x0000_0000_0010_1010);
+        assert_eq!(square.file_fill().0, 0x1010_1010_1010_1010);
+    }
+
+    #[test]
+    fn test_batch_attacks() {
+        // Knights and kings on every square, alone and combined
+        let mut all_knights: BitBoard = BitBoard(0);
+        let mut all_kings: BitBoard = BitBoard(0);
+        for index in 0..64 {
+            let bb: BitBoard = BitBoard(1 << index);
+            let square: Square = bb.to_square().unwrap();
+            assert_eq!(bb.knight_attacks(), get_knight_attacks(square));
+            assert_eq!(bb.king_attacks(), get_king_attacks(square));
+            if index % 3 == 0 {
+                all_knights = BitBoard(all_knights.0 | get_knight_attacks(square).0);
+                all_kings = BitBoard(all_kings.0 | get_king_attacks(square).0);
+            }
+        }
+        let set: BitBoard = BitBoard((0..64).filter(|i| i % 3 == 0).map(|i| 1u64 << i).sum());
+        assert_eq!(set.knight_attacks(), all_knights);
+        assert_eq!(set.king_attacks(), all_kings);
+
+        // b2 and h2 pawns
+        let pawns: BitBoard = BitBoard(0x8200);
+        assert_eq!(pawns.pawn_attacks::<0>().0, 0x45_0000);
+        assert_eq!(pawns.pawn_attacks::<1>().0, 0x45);
+        assert_eq!(pawns.enemy_pawn_attacks::<0>().0, 0x45);
+        assert_eq!(pawns.enemy_pawn_attacks::<1>().0, 0x45_0000);
+    }
+
+    #[test]
+    fn test_relative_shifts() {
+        // e4
+        let square: BitBoard = BitBoard(1 << 28);
+        assert_eq!(square.shift_forward::<0>(), square.shift_backward::<1>());
+        assert_eq!(square.shift_forward::<1>(), square.shift_backward::<0>());
+        assert_eq!(square.shift_left::<0>(), square.shift_right::<1>());
+        assert_eq!(square.shift_left::<0>().0, 1 << 27);
+        assert_eq!(square.shift_forward_left::<0>().0, 1 << 35);
+        assert_eq!(square.shift_forward_right::<0>().0, 1 << 37);
+        assert_eq!(square.shift_forward_left::<1>().0, 1 << 21);
+        assert_eq!(square.shift_forward_right::<1>().0, 1 << 19);
+    }
+}
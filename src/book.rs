@@ -0,0 +1,97 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/book.rs
+
+//! Tiny embedded opening book, only consulted in time scrambles.
+//!
+//! Each line is the sequence of moves from the standard starting position
+//! followed by the reply to play. Lines are replayed on demand, so the book
+//! needs no hashing of its own and stays readable.
+
+use laura_core::{Board, Move};
+
+const BOOK: [(&str, &str); 20] = [
+    ("", "e2e4"),
+    ("e2e4", "e7e5"),
+    ("d2d4", "d7d5"),
+    ("c2c4", "e7e5"),
+    ("g1f3", "d7d5"),
+    ("e2e4 e7e5", "g1f3"),
+    ("e2e4 c7c5", "g1f3"),
+    ("e2e4 e7e6", "d2d4"),
+    ("e2e4 c7c6", "d2d4"),
+    ("e2e4 d7d5", "e4d5"),
+    ("d2d4 d7d5", "c2c4"),
+    ("d2d4 g8f6", "c2c4"),
+    ("c2c4 e7e5", "b1c3"),
+    ("g1f3 d7d5", "d2d4"),
+    ("e2e4 e7e5 g1f3", "b8c6"),
+    ("e2e4 c7c5 g1f3", "d7d6"),
+    ("d2d4 d7d5 c2c4", "e7e6"),
+    ("d2d4 g8f6 c2c4", "e7e6"),
+    ("e2e4 e7e5 g1f3 b8c6", "f1b5"),
+    ("e2e4 e7e6 d2d4", "d7d5"),
+];
+
+/// Replays the moves of a book line from the starting position.
+fn replay(line: &str) -> Option<Board> {
+    line.split_whitespace()
+        .try_fold(Board::default(), |board, uci_move| {
+            board.find_move(uci_move).map(|mv| board.make_move(mv))
+        })
+}
+
+/// Book reply for `board`, if it is one of the covered opening positions.
+pub fn probe(board: &Board) -> Option<Move> {
+    BOOK.iter()
+        .find(|(line, _)| replay(line).is_some_and(|book| book.zobrist.0 == board.zobrist.0))
+        .and_then(|(_, reply)| board.find_move(reply))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BOOK, probe, replay};
+    use laura_core::{Board, Move};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_book_lines() {
+        // Every line and reply is legal
+        for (line, reply) in BOOK {
+            let board: Board = replay(line).unwrap_or_else(|| panic!("bad line '{line}'"));
+            assert!(board.find_move(reply).is_some(), "bad reply '{reply}'");
+        }
+    }
+
+    #[test]
+    fn test_probe() {
+        let start: Board = Board::default();
+        let e4: Move = start.find_move("e2e4").unwrap();
+        assert_eq!(probe(&start), Some(e4));
+
+        let after: Board = start.make_move(e4);
+        assert_eq!(probe(&after), after.find_move("e7e5"));
+
+        let middlegame: Board =
+            Board::from_str("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+                .unwrap();
+        assert_eq!(probe(&middlegame), None);
+    }
+}
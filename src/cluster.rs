@@ -0,0 +1,398 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/cluster.rs
+
+//! Experimental cluster search over TCP.
+//!
+//! A master engine accepts worker engines and, on every `go`, forwards them
+//! the root position followed by `go infinite`. When its own search is over
+//! it sends `stop`, and each worker answers with a single line:
+//!
+//! `result depth <depth> score <cp> pv <moves>`
+//!
+//! The master stores the reported lines as hash hints and plays the deepest
+//! one if it went further than its own search. Workers run lazy SMP on their
+//! own machines, so the whole cluster shares nothing but the root.
+//!
+//! Workers are not authenticated, so the master listens on localhost unless
+//! `cluster listen <port> <host>` names another interface.
+
+use crate::{
+    config::{CLUSTER_TIMEOUT, INFINITY},
+    options::EngineOptions,
+    position::Position,
+    thread::ThreadPool,
+    transposition::TranspositionTable,
+    uci::UCICommand,
+};
+use laura_core::{Board, Move};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+    time::Duration,
+};
+
+/// Search result reported by a worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerResult {
+    pub depth: usize,
+    pub score: i32,
+    pub pv: Vec<String>,
+}
+
+impl WorkerResult {
+    pub fn new(depth: usize, score: i32, pv: &[Move]) -> Self {
+        Self {
+            depth,
+            score,
+            pv: pv.iter().map(|mv| mv.to_string()).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for WorkerResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "result depth {} score {} pv {}",
+            self.depth,
+            self.score,
+            self.pv.join(" ")
+        )
+    }
+}
+
+impl FromStr for WorkerResult {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["result", "depth", depth, "score", score, "pv", pv @ ..] => Ok(Self {
+                depth: depth.parse().map_err(|_| ())?,
+                score: score.parse().map_err(|_| ())?,
+                pv: pv.iter().map(|mv| mv.to_string()).collect(),
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+struct Peer {
+    addr: SocketAddr,
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl Peer {
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.stream, "{line}")
+    }
+
+    fn receive(&mut self) -> io::Result<WorkerResult> {
+        self.stream
+            .set_read_timeout(Some(Duration::from_millis(CLUSTER_TIMEOUT)))?;
+        let mut line: String = String::new();
+        self.reader.read_line(&mut line)?;
+        WorkerResult::from_str(&line)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid worker result"))
+    }
+}
+
+/// Master side: the workers connected so far.
+pub struct Cluster {
+    peers: Arc<Mutex<Vec<Peer>>>,
+    port: u16,
+}
+
+impl std::fmt::Debug for Cluster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cluster(port {}, {} workers)", self.port, self.workers())
+    }
+}
+
+impl Cluster {
+    /// Accepts workers on `host:port` in the background.
+    pub fn listen(host: &str, port: u16) -> io::Result<Self> {
+        let listener: TcpListener = TcpListener::bind((host, port))?;
+        let port: u16 = listener.local_addr()?.port();
+        let peers: Arc<Mutex<Vec<Peer>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let accepted: Arc<Mutex<Vec<Peer>>> = Arc::clone(&peers);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let Ok(addr) = stream.peer_addr() else {
+                    continue;
+                };
+                let Ok(reader) = stream.try_clone() else {
+                    continue;
+                };
+                let _ = stream.set_nodelay(true);
+                println!("info string cluster worker {} connected", addr);
+                accepted.lock().unwrap().push(Peer {
+                    addr,
+                    stream,
+                    reader: BufReader::new(reader),
+                });
+            }
+        });
+
+        Ok(Self { peers, port })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn workers(&self) -> usize {
+        self.peers.lock().unwrap().len()
+    }
+
+    /// Sends the root to every worker, dropping those that disconnected.
+    pub fn start(&self, position: &str) {
+        self.peers.lock().unwrap().retain_mut(|peer| {
+            let sent: io::Result<()> = peer.send(position).and_then(|()| peer.send("go infinite"));
+            report(peer, sent.is_ok())
+        });
+    }
+
+    /// Stops the workers and collects their results.
+    pub fn collect(&self) -> Vec<WorkerResult> {
+        let mut results: Vec<WorkerResult> = Vec::new();
+        self.peers.lock().unwrap().retain_mut(|peer| {
+            match peer.send("stop").and_then(|()| peer.receive()) {
+                Ok(result) => {
+                    println!(
+                        "info string cluster worker {} depth {} score cp {} pv {}",
+                        peer.addr,
+                        result.depth,
+                        result.score,
+                        result.pv.join(" ")
+                    );
+                    results.push(result);
+                    true
+                }
+                Err(_) => report(peer, false),
+            }
+        });
+        results
+    }
+}
+
+impl Drop for Cluster {
+    fn drop(&mut self) {
+        // Closing the sockets lets every worker return to its own UCI loop
+        for peer in self.peers.lock().unwrap().drain(..) {
+            let _ = peer.stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn report(peer: &Peer, alive: bool) -> bool {
+    if !alive {
+        eprintln!(
+            "info string [warning] cluster worker {} disconnected.",
+            peer.addr
+        );
+    }
+    alive
+}
+
+/// Stores the worker lines as hash hints and returns the deepest root move
+/// reported beyond `depth`, the depth completed by the master itself.
+pub fn merge(
+    results: &[WorkerResult],
+    position: &Position,
+    ttable: &TranspositionTable,
+    depth: usize,
+) -> Option<Move> {
+    use crate::transposition::BoundType;
+
+    let board: Board = position.board();
+    let mut best: Option<(usize, Move)> = None;
+    for result in results {
+        let Some(mv) = result.pv.first().and_then(|mv| board.find_move(mv)) else {
+            continue;
+        };
+        // In check the search stores no static evaluation either
+        let evaluation: i32 = if position.in_check() {
+            -INFINITY
+        } else {
+            position.evaluate()
+        };
+        ttable.insert(
+            position.key(),
+            mv,
+            result.score,
+            evaluation,
+            result.depth,
+            BoundType::Exact,
+            true,
+            0,
+        );
+
+        if result.depth > best.map_or(depth, |(depth, _)| depth) {
+            best = Some((result.depth, mv));
+        }
+    }
+    best.map(|(_, mv)| mv)
+}
+
+/// Worker side: serves the master at `addr` until it disconnects.
+///
+/// The connection is read on its own thread, so a `stop` reaches the running
+/// search through the shared stop flag, just like on the UCI input.
+pub fn serve(
+    addr: &str,
+    stop: &Arc<AtomicBool>,
+    position: &mut Position,
+    threadpool: &mut ThreadPool,
+    ttable: &TranspositionTable,
+    options: &Arc<EngineOptions>,
+) -> io::Result<()> {
+    let stream: TcpStream = TcpStream::connect(addr)?;
+    stream.set_nodelay(true)?;
+    let mut writer: TcpStream = stream.try_clone()?;
+    println!("info string cluster connected to {}", addr);
+
+    let (sender, receiver): (Sender<String>, Receiver<String>) = mpsc::channel();
+    let reader_stop: Arc<AtomicBool> = Arc::clone(stop);
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match line.split_whitespace().next() {
+                Some("stop") => reader_stop.store(true, Ordering::SeqCst),
+                Some("go") => {
+                    reader_stop.store(false, Ordering::SeqCst);
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+                _ => {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        reader_stop.store(true, Ordering::SeqCst);
+    });
+
+    while let Ok(line) = receiver.recv() {
+        match UCICommand::from_str(&line) {
+            Ok(UCICommand::Position(board, history, _)) => {
+                position.set_board(board);
+                position.set_game(history);
+            }
+            Ok(UCICommand::Go(time_control, _)) => {
+                threadpool.set_excluded(Vec::new());
+                ttable.age();
+                threadpool.start_search(position, ttable, time_control, options);
+                let (depth, score, pv) = threadpool.principal_variation();
+                writeln!(writer, "{}", WorkerResult::new(depth, score, &pv))?;
+            }
+            _ => eprintln!(
+                "info string [warning] unexpected cluster command '{}'.",
+                line
+            ),
+        }
+    }
+
+    println!("info string cluster master {} disconnected", addr);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cluster, WorkerResult, serve};
+    use crate::{
+        Position, ThreadPool, config::CLUSTER_HOST, options::EngineOptions,
+        transposition::TranspositionTable,
+    };
+    use laura_core::Board;
+    use std::{
+        str::FromStr,
+        sync::{Arc, atomic::AtomicBool},
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_result_format() {
+        let result: WorkerResult = WorkerResult {
+            depth: 12,
+            score: -35,
+            pv: vec!["e2e4".to_string(), "e7e5".to_string()],
+        };
+        let line: String = result.to_string();
+        assert_eq!(line, "result depth 12 score -35 pv e2e4 e7e5");
+        assert_eq!(WorkerResult::from_str(&line), Ok(result));
+        assert!(WorkerResult::from_str("result depth x score 0 pv").is_err());
+        assert!(WorkerResult::from_str("bestmove e2e4").is_err());
+    }
+
+    #[test]
+    fn test_master_worker() {
+        let cluster: Cluster = Cluster::listen(CLUSTER_HOST, 0).unwrap();
+        let addr: String = format!("{}:{}", CLUSTER_HOST, cluster.port());
+
+        let worker = thread::spawn(move || {
+            let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+            let mut threadpool: ThreadPool = ThreadPool::new(Arc::clone(&stop));
+            let mut position: Position = Position::default();
+            let mut ttable: TranspositionTable = TranspositionTable::default();
+            ttable.resize(1);
+            let options: Arc<EngineOptions> = Arc::default();
+            serve(
+                &addr,
+                &stop,
+                &mut position,
+                &mut threadpool,
+                &ttable,
+                &options,
+            )
+            .unwrap();
+        });
+
+        while cluster.workers() == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        cluster.start("position startpos moves e2e4");
+        thread::sleep(Duration::from_millis(200));
+        let results: Vec<WorkerResult> = cluster.collect();
+        assert_eq!(results.len(), 1);
+
+        // The reported move is legal in the forwarded position
+        let board: Board = Board::default().make_move(Board::default().find_move("e2e4").unwrap());
+        assert!(board.find_move(&results[0].pv[0]).is_some());
+
+        drop(cluster);
+        worker.join().unwrap();
+    }
+}
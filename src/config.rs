@@ -29,15 +29,59 @@ pub const MINIMUM_TIME: u64 = 30;
 pub const OPTIMAL_TIME_BASE: u64 = 65;
 pub const INCREMENT_TIME_BASE: u64 = 85;
 pub const DEFAULT_MOVESTOGO: u64 = 20;
+// Clock with at least this many increments left: spend at least one increment
+pub const HEALTHY_INCREMENTS: u64 = 10;
+// Behind when our clock is under this percentage of the opponent's
+pub const BEHIND_CLOCK_RATIO: u64 = 70;
+pub const BEHIND_TIME_SCALE: u64 = 75;
+pub const PANIC_TIME: u64 = 100;
+pub const PANIC_DIVISOR: u64 = 4;
+pub const PANIC_DEPTH: usize = 2;
+pub const NODE_CHUNK: u64 = 1_024;
+pub const NODE_CHUNK_DIVISOR: u64 = 64;
+pub const NPS_SMOOTHING: f64 = 0.3;
+pub const ENDGAME_TIME_SCALE: u64 = 80;
+pub const MIDDLEGAME_TIME_SCALE: u64 = 115;
+pub const CHECK_TIME_SCALE: u64 = 115;
+pub const INSTABILITY_MARGIN: i32 = 40;
+pub const INSTABILITY_TIME_SCALE: u64 = 130;
+pub const FEW_MOVES: usize = 3;
+pub const FEW_MOVES_TIME_SCALE: u64 = 70;
 
 // Search parameters
 pub const INFINITY: i32 = 32_001;
 pub const MATE: i32 = 32_000;
 pub const MAX_MATE: i32 = MATE - MAX_PLY as i32;
-pub const MAX_PLY: usize = 128;
+pub const MAX_PLY: usize = 256;
 pub const ASPIRATION_MARGIN: i32 = 25;
 pub const ASPIRATION_DEPTH_THRESHOLD: usize = 5;
+pub const ASPIRATION_SCORE_DIVISOR: i32 = 64;
+pub const ASPIRATION_DEPTH_BONUS: i32 = 64;
+pub const ASPIRATION_MAX_REDUCTION: usize = 3;
 pub const MAX_DELTA: i32 = 1_025;
+pub const KEY_HISTORY_SIZE: usize = 512;
+pub const CURRLINE_DELAY: u64 = 1_000;
+pub const CURRLINE_LENGTH: usize = 8;
+
+// Razoring values
+pub const RAZOR_DEPTH: usize = 3;
+pub const RAZOR_BASE_MARGIN: i32 = 150;
+pub const RAZOR_DEPTH_MARGIN: i32 = 100;
+
+// Quiescence values
+// Stand-pat discount while one of our rooks or queens is en prise
+pub const QS_THREAT_MARGIN: i32 = 120;
+
+// Quiet move pruning values
+pub const LMP_DEPTH: usize = 3;
+pub const HISTORY_PRUNING_DEPTH: usize = 5;
+pub const HISTORY_PRUNING_MARGIN: i32 = 2_000;
+pub const QUIET_SEE_DEPTH: usize = 8;
+pub const QUIET_SEE_MARGIN: i32 = 60;
+
+// Proof-number search values
+pub const PN_INFINITY: u32 = u32::MAX / 2;
+pub const PNS_MAX_NODES: usize = 4_000_000;
 
 // LMR values
 pub const LMR_THRESHOLD: i32 = 3;
@@ -54,8 +98,13 @@ pub static LMR_TABLE: LazyLock<[[i32; 64]; 64]> = LazyLock::new(|| {
 });
 
 // Tables parameters
-pub const KILLER_SLOTS: usize = 2;
+pub const KILLER_SLOTS: usize = if cfg!(feature = "killers-3") { 3 } else { 2 };
 pub const HIST_CLAMP: i32 = 16384;
+// Share of every history score (1 / HIST_DECAY) dropped before each search
+pub const HIST_DECAY: i32 = 4;
+
+// Evaluation parameters
+pub const PAWN_CACHE_SIZE: usize = 1 << 14;
 
 // Transposition table parameters
 pub const TTMATE: i32 = 30_000;
@@ -68,6 +117,17 @@ pub const ENTRIES_PER_CELL: usize = 3;
 pub const MAX_AGE: u8 = 1 << 5;
 pub const AGE_MASK: u8 = MAX_AGE - 1;
 pub const DEFAULT_SIZE: usize = 16;
+pub const MIN_HASH_PER_THREAD: usize = 4;
+pub const HASHFULL_SAMPLE: usize = 1_000;
+pub const CLEAR_CHUNK_SIZE: usize = 64 * MEGABYTE;
 pub const DATA_MASK: u128 = 0xFFFF_FFFF_FFFF_FFFF;
 pub const KEY_MASK: u128 = 0xFFFF;
 pub const KEY_WRAPPER_MASK: u64 = 0xFFFF;
+
+// Cluster parameters
+pub const CLUSTER_TIMEOUT: u64 = 2_000;
+pub const CLUSTER_HOST: &str = "127.0.0.1";
+
+// Benchmark parameters
+pub const BENCH_OCCUPANCIES: usize = 1_024;
+pub const BENCH_ROUNDS: usize = 16;
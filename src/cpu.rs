@@ -0,0 +1,224 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/cpu.rs
+
+//! Runtime detection of the cpu the engine is running on.
+//!
+//! The attack getters of laura_core pick their backend (PEXT or magic
+//! bitboards) at compile time through the `bmi2` feature, so the choice
+//! cannot be switched at startup from here. What can be checked is whether
+//! the running cpu matches the build and, if not, which build suits it.
+
+use crate::{
+    config::{BENCH_OCCUPANCIES, BENCH_ROUNDS},
+    geometry,
+};
+use laura_core::{BitBoard, Square, get_bishop_attacks, get_rook_attacks};
+use std::time::{Duration, Instant};
+
+/// Whether the running cpu supports BMI2 at all.
+pub fn has_bmi2() -> bool {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        std::is_x86_feature_detected!("bmi2")
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        false
+    }
+}
+
+/// Whether the running cpu executes PEXT in hardware. AMD processors before
+/// Zen 3 (family 0x17 and older) implement it in microcode, which is far
+/// slower than a magic bitboard lookup.
+pub fn fast_pext() -> bool {
+    has_bmi2() && !slow_pext_family()
+}
+
+#[cfg(target_arch = "x86_64")]
+#[allow(unused_unsafe)]
+fn slow_pext_family() -> bool {
+    use std::arch::x86_64::{__cpuid, CpuidResult};
+
+    // SAFETY: cpuid is available on every x86_64 processor
+    let vendor: CpuidResult = unsafe { __cpuid(0) };
+    let amd: bool = vendor.ebx == u32::from_le_bytes(*b"Auth")
+        && vendor.edx == u32::from_le_bytes(*b"enti")
+        && vendor.ecx == u32::from_le_bytes(*b"cAMD");
+    if !amd {
+        return false;
+    }
+
+    // SAFETY: leaf 1 is always supported
+    let signature: u32 = unsafe { __cpuid(1) }.eax;
+    let base: u32 = (signature >> 8) & 0xF;
+    let family: u32 = if base == 0xF {
+        base + ((signature >> 20) & 0xFF)
+    } else {
+        base
+    };
+    family <= 0x17
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn slow_pext_family() -> bool {
+    false
+}
+
+/// Describes a mismatch between the build and the running cpu, if any.
+pub fn backend_advice() -> Option<&'static str> {
+    let pext_build: bool = cfg!(feature = "bmi2");
+    match (pext_build, has_bmi2(), fast_pext()) {
+        (true, false, _) => Some("this build uses PEXT but the cpu has no BMI2 support"),
+        (true, true, false) => {
+            Some("PEXT is microcoded on this cpu, the build without bmi2 is faster")
+        }
+        (false, _, true) => Some("this cpu has fast PEXT, the bmi2 build is faster"),
+        _ => None,
+    }
+}
+
+/// Timings of the `bench attacks` micro-benchmark.
+#[derive(Debug, Clone, Copy)]
+pub struct AttackBench {
+    /// Rook plus bishop lookups performed per measurement
+    pub lookups: u64,
+    /// Time spent by the attack getters of the compiled backend
+    pub backend: Duration,
+    /// Time spent computing the same indices with the PEXT instruction
+    pub pext: Option<Duration>,
+}
+
+impl AttackBench {
+    /// Million lookups per second of a measurement.
+    pub fn rate(&self, elapsed: Duration) -> f64 {
+        self.lookups as f64 / elapsed.as_secs_f64().max(f64::EPSILON) / 1_000_000.0
+    }
+}
+
+/// Measures rook and bishop attack generation on random occupancies, with
+/// the compiled backend and, when available, with a raw PEXT index.
+pub fn bench_attacks() -> AttackBench {
+    let squares: Vec<Square> = geometry::squares().collect();
+    let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut next = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    // Sparse occupancies, as on a real board
+    let occupancies: Vec<u64> = (0..BENCH_OCCUPANCIES).map(|_| next() & next()).collect();
+    let lookups: u64 = (BENCH_ROUNDS * BENCH_OCCUPANCIES * squares.len() * 2) as u64;
+
+    let start: Instant = Instant::now();
+    let mut checksum: u64 = 0;
+    for _ in 0..BENCH_ROUNDS {
+        for &occupancy in occupancies.iter() {
+            for &square in squares.iter() {
+                checksum ^= get_rook_attacks(square, BitBoard(occupancy)).0
+                    ^ get_bishop_attacks(square, BitBoard(occupancy)).0;
+            }
+        }
+    }
+    std::hint::black_box(checksum);
+    let backend: Duration = start.elapsed();
+
+    AttackBench {
+        lookups,
+        backend,
+        pext: bench_pext(&occupancies),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn bench_pext(occupancies: &[u64]) -> Option<Duration> {
+    #[target_feature(enable = "bmi2")]
+    unsafe fn run(occupancies: &[u64], masks: &[(u64, u64)]) -> u64 {
+        use std::arch::x86_64::_pext_u64;
+
+        let mut checksum: u64 = 0;
+        for _ in 0..BENCH_ROUNDS {
+            for &occupancy in occupancies.iter() {
+                for &(rook, bishop) in masks.iter() {
+                    checksum ^= _pext_u64(occupancy, rook) ^ _pext_u64(occupancy, bishop);
+                }
+            }
+        }
+        checksum
+    }
+
+    if !has_bmi2() {
+        return None;
+    }
+
+    // Relevant occupancy masks, without the edges of each ray
+    let masks: Vec<(u64, u64)> = (0..64)
+        .map(|square: i32| {
+            let (file, rank) = (square % 8, square / 8);
+            let (mut rook, mut bishop) = (0u64, 0u64);
+            for target in (0..64).filter(|&target| target != square) {
+                let (f, r) = (target % 8, target / 8);
+                if (f == file && (1..7).contains(&r)) || (r == rank && (1..7).contains(&f)) {
+                    rook |= 1 << target;
+                }
+                if (f - file).abs() == (r - rank).abs()
+                    && (1..7).contains(&f)
+                    && (1..7).contains(&r)
+                {
+                    bishop |= 1 << target;
+                }
+            }
+            (rook, bishop)
+        })
+        .collect();
+
+    let start: Instant = Instant::now();
+    // SAFETY: BMI2 support was checked above
+    std::hint::black_box(unsafe { run(occupancies, &masks) });
+    Some(start.elapsed())
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn bench_pext(_: &[u64]) -> Option<Duration> {
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cpu::{AttackBench, backend_advice, bench_attacks, fast_pext, has_bmi2};
+
+    #[test]
+    fn test_detection() {
+        // Fast PEXT implies BMI2, and a build matching the cpu gets no advice
+        assert!(!fast_pext() || has_bmi2());
+        if cfg!(feature = "bmi2") == fast_pext() {
+            assert_eq!(backend_advice(), None);
+        }
+    }
+
+    #[test]
+    fn test_bench_attacks() {
+        let bench: AttackBench = bench_attacks();
+        assert!(bench.lookups > 0);
+        assert!(bench.rate(bench.backend) > 0.0);
+        assert_eq!(bench.pext.is_some(), has_bmi2());
+    }
+}
@@ -0,0 +1,191 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/display.rs
+
+//! Board pretty printing for terminal users.
+
+use laura_core::{BitBoard, Board, Color, PieceType, Square};
+use std::fmt;
+
+const WHITE: usize = Color::White as usize;
+
+const ASCII: [[char; 6]; 2] = [
+    ['P', 'N', 'B', 'R', 'Q', 'K'],
+    ['p', 'n', 'b', 'r', 'q', 'k'],
+];
+const UNICODE: [[char; 6]; 2] = [
+    ['♙', '♘', '♗', '♖', '♕', '♔'],
+    ['♟', '♞', '♝', '♜', '♛', '♚'],
+];
+
+const LIGHT_BACKGROUND: &str = "\x1b[48;5;180m";
+const DARK_BACKGROUND: &str = "\x1b[48;5;94m";
+const RESET: &str = "\x1b[0m";
+
+/// Formatter wrapper selecting how a [`Board`] is printed.
+#[derive(Debug, Clone, Copy)]
+pub struct BoardView<'a> {
+    board: &'a Board,
+    unicode: bool,
+    flipped: bool,
+    colored: bool,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn new(board: &'a Board) -> Self {
+        Self {
+            board,
+            unicode: false,
+            flipped: false,
+            colored: false,
+        }
+    }
+
+    /// Uses Unicode chess glyphs instead of letters.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    /// Prints the board from Black's point of view.
+    pub fn flipped(mut self, flipped: bool) -> Self {
+        self.flipped = flipped;
+        self
+    }
+
+    /// Colors the squares with ANSI escape codes.
+    pub fn colored(mut self, colored: bool) -> Self {
+        self.colored = colored;
+        self
+    }
+
+    fn glyph(&self, square: Square) -> char {
+        match self.board.piece_on(square) {
+            Some(piece) => {
+                let color: usize =
+                    if self.board.sides_bitboard[WHITE].0 & (1 << square.to_index()) != 0 {
+                        0
+                    } else {
+                        1
+                    };
+                let index: usize = match piece.piece_type() {
+                    PieceType::Pawn => 0,
+                    PieceType::Knight => 1,
+                    PieceType::Bishop => 2,
+                    PieceType::Rook => 3,
+                    PieceType::Queen => 4,
+                    PieceType::King => 5,
+                };
+                if self.unicode {
+                    UNICODE[color][index]
+                } else {
+                    ASCII[color][index]
+                }
+            }
+            None => {
+                if self.colored {
+                    ' '
+                } else {
+                    '.'
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for BoardView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranks: [usize; 8] = if self.flipped {
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        } else {
+            [7, 6, 5, 4, 3, 2, 1, 0]
+        };
+        let files: [usize; 8] = if self.flipped {
+            [7, 6, 5, 4, 3, 2, 1, 0]
+        } else {
+            [0, 1, 2, 3, 4, 5, 6, 7]
+        };
+
+        for rank in ranks {
+            write!(f, " {} ", rank + 1)?;
+            for file in files {
+                let square: Square = BitBoard(1 << (rank * 8 + file)).to_square().unwrap();
+                let glyph: char = self.glyph(square);
+                if self.colored {
+                    let background: &str = if (rank + file) % 2 == 1 {
+                        LIGHT_BACKGROUND
+                    } else {
+                        DARK_BACKGROUND
+                    };
+                    write!(f, "{background} {glyph} {RESET}")?;
+                } else {
+                    write!(f, " {glyph}")?;
+                }
+            }
+            writeln!(f)?;
+        }
+
+        write!(f, "   ")?;
+        for file in files {
+            let letter: char = (b'a' + file as u8) as char;
+            if self.colored {
+                write!(f, " {letter} ")?;
+            } else {
+                write!(f, " {letter}")?;
+            }
+        }
+        writeln!(f)?;
+
+        let side: &str = if self.board.side() == Color::White {
+            "White"
+        } else {
+            "Black"
+        };
+        write!(f, "\n Side to move: {side}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BoardView;
+    use laura_core::Board;
+
+    #[test]
+    fn test_board_view() {
+        let board: Board = Board::default();
+
+        let plain: String = BoardView::new(&board).to_string();
+        let lines: Vec<&str> = plain.lines().collect();
+        assert_eq!(lines[0], " 8  r n b q k b n r");
+        assert_eq!(lines[7], " 1  R N B Q K B N R");
+        assert_eq!(lines[8], "    a b c d e f g h");
+        assert!(plain.ends_with("Side to move: White"));
+
+        let flipped: String = BoardView::new(&board)
+            .flipped(true)
+            .unicode(true)
+            .to_string();
+        let lines: Vec<&str> = flipped.lines().collect();
+        assert_eq!(lines[0], " 1  ♖ ♘ ♗ ♔ ♕ ♗ ♘ ♖");
+        assert_eq!(lines[8], "    h g f e d c b a");
+
+        println!("{}", BoardView::new(&board).unicode(true).colored(true));
+    }
+}
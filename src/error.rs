@@ -0,0 +1,145 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/error.rs
+
+//! Crate-level error type.
+
+use std::{error::Error, fmt};
+
+use crate::{
+    storage::SharedMemoryError, timer::TimeParserError, transposition::AllocationError,
+    uci::UCIError,
+};
+
+/// Every error the engine API reports, so callers can handle them in one place
+/// instead of parsing `info string` lines.
+#[derive(Debug)]
+pub enum EngineError {
+    Uci(UCIError),
+    Time(TimeParserError),
+    /// The table could not be resized; it keeps `kept` megabytes
+    Allocation {
+        error: AllocationError,
+        kept: usize,
+    },
+    SharedMemory(SharedMemoryError),
+    /// An option value outside its type or range
+    InvalidOption {
+        name: &'static str,
+        value: String,
+        expected: String,
+    },
+    UnknownOption(String),
+}
+
+impl EngineError {
+    pub fn invalid_option(name: &'static str, value: &str, expected: impl Into<String>) -> Self {
+        Self::InvalidOption {
+            name,
+            value: value.to_string(),
+            expected: expected.into(),
+        }
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uci(error) => write!(f, "{error}"),
+            Self::Time(error) => write!(f, "[error] {error}."),
+            Self::Allocation { error, kept } => {
+                write!(f, "[error] {error}, keeping {kept} MB.")
+            }
+            Self::SharedMemory(error) => {
+                write!(f, "[error] {error}, keeping the current table.")
+            }
+            Self::InvalidOption {
+                name,
+                value,
+                expected,
+            } => write!(
+                f,
+                "[error] Invalid value for {name}: '{value}'. Must be {expected}."
+            ),
+            Self::UnknownOption(name) => write!(f, "[error] unrecognized option '{name}'"),
+        }
+    }
+}
+
+impl Error for EngineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Uci(error) => Some(error),
+            Self::Time(error) => Some(error),
+            Self::Allocation { error, .. } => Some(error),
+            Self::SharedMemory(error) => Some(error),
+            Self::InvalidOption { .. } | Self::UnknownOption(_) => None,
+        }
+    }
+}
+
+impl From<UCIError> for EngineError {
+    fn from(error: UCIError) -> Self {
+        Self::Uci(error)
+    }
+}
+
+impl From<TimeParserError> for EngineError {
+    fn from(error: TimeParserError) -> Self {
+        Self::Time(error)
+    }
+}
+
+impl From<SharedMemoryError> for EngineError {
+    fn from(error: SharedMemoryError) -> Self {
+        Self::SharedMemory(error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use super::EngineError;
+    use crate::{timer::TimeParserError, transposition::AllocationError, uci::UCIError};
+
+    #[test]
+    fn test_engine_error() {
+        let invalid: EngineError = EngineError::invalid_option("Threads", "0", "between 1 and 512");
+        assert_eq!(
+            invalid.to_string(),
+            "[error] Invalid value for Threads: '0'. Must be between 1 and 512."
+        );
+        assert!(invalid.source().is_none());
+
+        let allocation: EngineError = EngineError::Allocation {
+            error: AllocationError { megabytes: 64 },
+            kept: 16,
+        };
+        assert!(allocation.to_string().ends_with("keeping 16 MB."));
+        assert!(allocation.source().is_some());
+
+        let uci: EngineError = UCIError::NoOptionValue.into();
+        assert_eq!(uci.to_string(), "[error] no option value provided.");
+
+        let time: EngineError = TimeParserError::UnknownParameter("foo".to_string()).into();
+        assert_eq!(time.to_string(), "[error] unknown go parameter 'foo'.");
+    }
+}
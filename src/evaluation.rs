@@ -21,18 +21,34 @@
 
 //! Static board evaluation.
 
+use crate::{
+    bitboard::Shift,
+    config::PAWN_CACHE_SIZE,
+    geometry::{distance, file_distance, rank_distance},
+};
 use laura_core::{
-    BitBoard, Board, Color, Piece, PieceType, Square, get_bishop_attacks, get_knight_attacks,
+    BitBoard, Board, Color, Move, PieceType, Square, get_bishop_attacks, get_knight_attacks,
     get_rook_attacks,
 };
-use std::ops::{AddAssign, Mul, Sub};
+use std::{
+    cell::RefCell,
+    ops::{Add, AddAssign, Mul, Sub, SubAssign},
+};
 
-const WHITE: usize = Color::White as usize;
-const BLACK: usize = Color::Black as usize;
+pub const WHITE: usize = Color::White as usize;
+pub const BLACK: usize = Color::Black as usize;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Value(i32, i32);
 
+impl Add for Value {
+    type Output = Value;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Value(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
 impl AddAssign for Value {
     fn add_assign(&mut self, rhs: Self) {
         self.0 += rhs.0;
@@ -40,6 +56,13 @@ impl AddAssign for Value {
     }
 }
 
+impl SubAssign for Value {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0;
+        self.1 -= rhs.1;
+    }
+}
+
 impl Sub for Value {
     type Output = Value;
 
@@ -153,7 +176,28 @@ const PASSED_PAWN_BONUS: [Value; 8] = [
     Value(100, 180), // Rank 7
     Value(0, 0),     // Rank 8
 ];
+const CANDIDATE_PASSER_BONUS: [Value; 8] = [
+    Value(0, 0),   // Rank 1
+    Value(2, 5),   // Rank 2
+    Value(5, 10),  // Rank 3
+    Value(10, 20), // Rank 4
+    Value(20, 35), // Rank 5
+    Value(35, 60), // Rank 6
+    Value(0, 0),   // Rank 7
+    Value(0, 0),   // Rank 8
+];
 const ISOLATED_PAWN: Value = Value(-3, -15);
+const BACKWARD_PAWN: [Value; 2] = [Value(-6, -10), Value(-15, -15)];
+const PAWN_LEVER_BONUS: [Value; 8] = [
+    Value(0, 0),  // Rank 1
+    Value(0, 0),  // Rank 2
+    Value(0, 0),  // Rank 3
+    Value(2, 0),  // Rank 4
+    Value(10, 5), // Rank 5
+    Value(15, 5), // Rank 6
+    Value(0, 0),  // Rank 7
+    Value(0, 0),  // Rank 8
+];
 const DOUBLED_PAWN_PENALTY: [Value; 8] = [
     Value(-15, -30), // File A
     Value(-10, -25), // File B
@@ -185,6 +229,17 @@ const OUTPOST_KNIGHT_BONUS: [Value; 8] = [
     Value(40, 40), // Rank Seven
     Value(0, 0),   // Rank Eight
 ];
+const REACHABLE_OUTPOST_KNIGHT: Value = Value(12, 6);
+// Knight in the enemy corner whose escape square is covered by a pawn, e.g. Na8 with a7/c7
+const TRAPPED_KNIGHT: [(usize, usize); 2] = [(56, 41), (63, 46)];
+const TRAPPED_KNIGHT_PENALTY: Value = Value(-60, -50);
+// Bishop cut off by a pawn, e.g. Ba7 after ...b6
+const TRAPPED_BISHOP: [(usize, usize, Value); 4] = [
+    (48, 41, Value(-90, -90)),
+    (55, 46, Value(-90, -90)),
+    (40, 33, Value(-45, -45)),
+    (47, 38, Value(-45, -45)),
+];
 const KNIGHT_MOBILITY_BONUS: [Value; 9] = [
     Value(-40, -35), // 0
     Value(-20, -25), // 1
@@ -197,6 +252,14 @@ const KNIGHT_MOBILITY_BONUS: [Value; 9] = [
     Value(40, 20),   // 8
 ];
 const BISHOP_PAIR: Value = Value(30, 60);
+const LONG_DIAGONAL_BISHOP: Value = Value(10, 4);
+const FIANCHETTO_BISHOP: Value = Value(15, 0);
+// Bishop square, king squares and shielding pawn for White, mirrored for Black
+const FIANCHETTO: [(usize, u64, usize); 2] = [(14, 0xE0, 22), (9, 0x07, 17)];
+const BAD_BISHOP: Value = Value(-2, -6);
+// Extra penalty for own pawns on the bishop's complex that cannot advance
+const FIXED_PAWN_BISHOP: Value = Value(-3, -7);
+const OPPOSITE_COMPLEX_BISHOP: Value = Value(6, 12);
 const OUTPOST_BISHOP_BONUS: [Value; 8] = [
     Value(0, 0),   // Rank One
     Value(0, 0),   // Rank Two
@@ -224,6 +287,10 @@ const BISHOP_MOBILITY_BONUS: [Value; 14] = [
     Value(40, 35),   // 13
 ];
 const OPEN_FILE_ROOK: [Value; 2] = [Value(10, 0), Value(15, 10)];
+const TRAPPED_ROOK: Value = Value(-45, -10);
+const TRAPPED_ROOK_MOBILITY: usize = 3;
+const CONNECTED_ROOKS: Value = Value(8, 4);
+const DOUBLED_ROOKS_OPEN: Value = Value(15, 5);
 const ROOK_MOBILITY_BONUS: [Value; 15] = [
     Value(-30, -60), // 0
     Value(-20, -40), // 1
@@ -281,6 +348,13 @@ const QUEEN_MOBILITY_BONUS: [Value; 28] = [
     Value(80, 75),
     Value(85, 80), // 28
 ];
+// Per minor piece still at home when the queen has already left
+const EARLY_QUEEN: Value = Value(-10, 0);
+const EARLY_QUEEN_PHASE: i32 = 112;
+const KNIGHT_HOMES: u64 = 0x42;
+const BISHOP_HOMES: u64 = 0x24;
+const QUEEN_HOME: usize = 3;
+const QUEEN_INFILTRATION: Value = Value(20, 10);
 const OPEN_FILE_KING: [Value; 2] = [Value(-15, -5), Value(-20, -0)];
 const SHIELD_PENALTY: [Value; 4] = [Value(-20, 0), Value(-15, 0), Value(-8, 0), Value(5, 0)];
 const PAWN_STORM: [Value; 8] = [
@@ -293,16 +367,47 @@ const PAWN_STORM: [Value; 8] = [
     Value(0, 0),
     Value(0, 0),
 ];
+const STORM_RACE: Value = Value(2, 0);
+const KING_TROPISM: [Value; 6] = [
+    Value(0, 0), // Pawns
+    Value(2, 1), // Knights
+    Value(1, 1), // Bishops
+    Value(1, 1), // Rooks
+    Value(3, 2), // Queens
+    Value(0, 0), // Kings
+];
+const SAFE_CHECK: [Value; 6] = [
+    Value(0, 0),     // Pawns
+    Value(-35, -5),  // Knights
+    Value(-25, -5),  // Bishops
+    Value(-45, -10), // Rooks
+    Value(-30, -10), // Queens
+    Value(0, 0),     // Kings
+];
+const QUEEN_CONTACT_CHECK: Value = Value(-50, -15);
 const TEMPO: i32 = 20;
+// Endgame scale factors, out of SCALE_NORMAL
+const SCALE_NORMAL: i32 = 64;
+const OCB_SCALE: i32 = 24;
+const OCB_SCALE_PER_PIECE: i32 = 10;
 
 pub fn evaluate(board: &Board) -> i32 {
-    let eval: Value = evaluate_pieces(board);
+    evaluate_with(board, psqt(board))
+}
+
+/// Evaluation using an already known material and piece-square score,
+/// usually kept up to date incrementally by the Position.
+pub fn evaluate_with(board: &Board, psqt: Value) -> i32 {
+    let mut eval: Value = evaluate_pieces(board);
+    eval += psqt;
 
     // Game phase calculation from Stockfish
     let phase: i32 = phase(board);
 
-    // Interpolated evaluation from White perspective
-    let mut eval: i32 = (eval.0 * phase + (eval.1 * (128 - phase))) / 128;
+    // Interpolated evaluation from White perspective, with the endgame part
+    // scaled down in drawish material configurations
+    let endgame: i32 = eval.1 * scale_factor(board) / SCALE_NORMAL;
+    let mut eval: i32 = (eval.0 * phase + (endgame * (128 - phase))) / 128;
     eval += TEMPO;
 
     if board.side == Color::Black {
@@ -314,46 +419,161 @@ pub fn evaluate(board: &Board) -> i32 {
 
 fn evaluate_pieces(board: &Board) -> Value {
     let mut eval: Value = Value(0, 0);
-    eval += evaluate_pawns::<WHITE>(board) - evaluate_pawns::<BLACK>(board);
+    let pawn_entry: PawnEntry = evaluate_pawn_structure(board);
+    eval += pawn_entry.eval;
+    eval += evaluate_pawns::<WHITE>(board, pawn_entry.passed[WHITE])
+        - evaluate_pawns::<BLACK>(board, pawn_entry.passed[BLACK]);
     eval += evaluate_king_pawns::<WHITE>(board) - evaluate_king_pawns::<BLACK>(board);
     eval += evaluate_knights::<WHITE>(board) - evaluate_knights::<BLACK>(board);
     eval += evaluate_bishops::<WHITE>(board) - evaluate_bishops::<BLACK>(board);
     eval += evaluate_rooks::<WHITE>(board) - evaluate_rooks::<BLACK>(board);
     eval += evaluate_queens::<WHITE>(board) - evaluate_queens::<BLACK>(board);
-    eval += evaluate_kings::<WHITE>(board) - evaluate_kings::<BLACK>(board);
+    eval += evaluate_trapped::<WHITE>(board) - evaluate_trapped::<BLACK>(board);
+    eval += evaluate_tropism::<WHITE>(board) - evaluate_tropism::<BLACK>(board);
+
+    let attacks: [[BitBoard; 6]; 2] = [attack_map::<WHITE>(board), attack_map::<BLACK>(board)];
+    eval += evaluate_safe_checks::<WHITE>(board, &attacks)
+        - evaluate_safe_checks::<BLACK>(board, &attacks);
 
     eval
 }
 
-fn evaluate_pawns<const COLOR: usize>(board: &Board) -> Value {
+/// Cached pawn structure evaluation, depending only on the pawn placement.
+#[derive(Debug, Clone, Copy)]
+struct PawnEntry {
+    pawns: [u64; 2],
+    eval: Value,
+    passed: [BitBoard; 2],
+}
+
+impl PawnEntry {
+    // A position without pawns has no pawn structure terms
+    const EMPTY: Self = Self {
+        pawns: [0, 0],
+        eval: Value(0, 0),
+        passed: [BitBoard::EMPTY, BitBoard::EMPTY],
+    };
+}
+
+thread_local! {
+    static PAWN_CACHE: RefCell<Vec<PawnEntry>> =
+        RefCell::new(vec![PawnEntry::EMPTY; PAWN_CACHE_SIZE]);
+}
+
+#[inline(always)]
+fn pawn_cache_index(pawns: [u64; 2]) -> usize {
+    let hash: u64 = pawns[WHITE].wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ pawns[BLACK].wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+
+    (hash >> (64 - PAWN_CACHE_SIZE.trailing_zeros())) as usize
+}
+
+fn evaluate_pawn_structure(board: &Board) -> PawnEntry {
+    let pawns: [u64; 2] = [
+        (board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[WHITE]).0,
+        (board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[BLACK]).0,
+    ];
+
+    PAWN_CACHE.with_borrow_mut(|cache| {
+        let entry: &mut PawnEntry = &mut cache[pawn_cache_index(pawns)];
+
+        if entry.pawns != pawns {
+            let (white_eval, white_passed) = pawn_structure::<WHITE>(board);
+            let (black_eval, black_passed) = pawn_structure::<BLACK>(board);
+
+            *entry = PawnEntry {
+                pawns,
+                eval: white_eval - black_eval,
+                passed: [white_passed, black_passed],
+            };
+        }
+
+        *entry
+    })
+}
+
+fn evaluate_pawns<const COLOR: usize>(board: &Board, passed: BitBoard) -> Value {
     let mut eval: Value = Value(0, 0);
     let pawns: BitBoard = board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR];
-    let enemy_pawns: BitBoard =
-        board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR ^ 1];
-    let connected: BitBoard = connected_pawns::<COLOR>(pawns);
 
     // Detect endgame: no queens, and at most one minor piece or one rook per side
     let queens: u32 = board.pieces_bitboard[PieceType::QUEEN].count_bits();
     let is_endgame = queens == 0;
 
     for square in pawns {
-        eval += PIECE_VALUE[PieceType::PAWN];
-        eval += PAWN_TABLE[square.to_index() ^ (56 * COLOR)];
+        let rank_index = square.rank().to_index() ^ (7 * COLOR);
+
+        // Endgame: general advancement bonus for all pawns (rank-scaled)
+        // Encourages flank pawns (a,b,c,f,g,h) to push forward in endgame
+        if is_endgame && rank_index >= 2 {
+            let advance_bonus = (rank_index as i32 - 1) * 6;
+            eval += Value(0, advance_bonus);
+        }
+    }
+
+    // Extra endgame incentive for passed pawns when no queen
+    if is_endgame {
+        for square in passed {
+            let bonus: Value = PASSED_PAWN_BONUS[square.rank().to_index() ^ (7 * COLOR)];
+            eval += Value(0, bonus.1 / 2);
+        }
+    }
+
+    eval
+}
 
+/// Pawn structure terms of one side, and the set of its passed pawns.
+fn pawn_structure<const COLOR: usize>(board: &Board) -> (Value, BitBoard) {
+    let mut eval: Value = Value(0, 0);
+    let mut passed: BitBoard = BitBoard::EMPTY;
+    let pawns: BitBoard = board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR];
+    let enemy_pawns: BitBoard =
+        board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR ^ 1];
+    let connected: BitBoard = connected_pawns::<COLOR>(pawns);
+
+    for square in pawns {
         let rank_index = square.rank().to_index() ^ (7 * COLOR);
+        let passed_mask: u64 = PASSED_PAWN_MASKS[COLOR][square.to_index()];
 
         // Passed pawn bonus
-        if enemy_pawns.0 & PASSED_PAWN_MASKS[COLOR][square.to_index()] == 0 {
-            let mut bonus = PASSED_PAWN_BONUS[rank_index];
-            // Extra endgame incentive for passed pawns when no queen
-            if is_endgame {
-                bonus += Value(0, bonus.1 / 2);
+        if enemy_pawns.0 & passed_mask == 0 {
+            passed = passed.set_square(square);
+            eval += PASSED_PAWN_BONUS[rank_index];
+        } else {
+            // Candidate passer: no enemy pawn in front on the same file, and at
+            // least as many helpers on the adjacent files as sentries ahead.
+            let file: u64 = square.file().to_bitboard().0;
+            let adjacent: u64 = ISOLATED_PAWN_MASKS[square.file().to_index()];
+            let sentries: u32 = (enemy_pawns.0 & passed_mask & adjacent).count_ones();
+            let helpers: u32 = (pawns.0 & adjacent & !passed_mask).count_ones();
+
+            if enemy_pawns.0 & passed_mask & file == 0 && helpers >= sentries {
+                eval += CANDIDATE_PASSER_BONUS[rank_index];
             }
-            eval += bonus;
         }
+
         // Isolated pawn penalties
         if pawns.0 & ISOLATED_PAWN_MASKS[square.file().to_index()] == 0 {
             eval += ISOLATED_PAWN;
+        } else if pawns.0 & ISOLATED_PAWN_MASKS[square.file().to_index()] & !passed_mask == 0 {
+            // Backward pawn: no friendly pawn can support it and its stop
+            // square is controlled by an enemy pawn.
+            let stop: usize = if COLOR == WHITE {
+                square.to_index() + 8
+            } else {
+                square.to_index() - 8
+            };
+
+            if enemy_pawns.0 & DOUBLE_SUPPORTED_PAWN_MASKS[COLOR ^ 1][stop] != 0 {
+                let semi_open: bool =
+                    enemy_pawns.0 & passed_mask & square.file().to_bitboard().0 == 0;
+                eval += BACKWARD_PAWN[semi_open as usize];
+            }
+        }
+
+        // Pawn lever: the pawn attacks an enemy pawn, creating tension
+        if enemy_pawns.0 & DOUBLE_SUPPORTED_PAWN_MASKS[COLOR ^ 1][square.to_index()] != 0 {
+            eval += PAWN_LEVER_BONUS[rank_index];
         }
         // Penalty for doubled pawns
         if pawns.0 & DOUBLED_PAWN_MASK[square.to_index()] != 0 {
@@ -369,13 +589,6 @@ fn evaluate_pawns<const COLOR: usize>(board: &Board) -> Value {
         if CENTER_MASK & (1u64 << square.to_index()) != 0 {
             eval += CENTRAL_PAWN_BONUS;
         }
-
-        // Endgame: general advancement bonus for all pawns (rank-scaled)
-        // Encourages flank pawns (a,b,c,f,g,h) to push forward in endgame
-        if is_endgame && rank_index >= 2 {
-            let advance_bonus = (rank_index as i32 - 1) * 6;
-            eval += Value(0, advance_bonus);
-        }
     }
 
     // Connected pawn bonus
@@ -383,12 +596,12 @@ fn evaluate_pawns<const COLOR: usize>(board: &Board) -> Value {
         eval += CONNECTED_PAWN_BONUS[square.rank().to_index() ^ (7 * COLOR)]
     }
 
-    eval
+    (eval, passed)
 }
 
 fn connected_pawns<const COLOR: usize>(pawns: BitBoard) -> BitBoard {
-    let phalanx: BitBoard = pawns.left_for::<COLOR>() | pawns.right_for::<COLOR>();
-    let supported: BitBoard = pawns.up_left_for::<COLOR>() | pawns.up_right_for::<COLOR>();
+    let phalanx: BitBoard = pawns.shift_left::<COLOR>() | pawns.shift_right::<COLOR>();
+    let supported: BitBoard = pawns.pawn_attacks::<COLOR>();
     pawns & (phalanx | supported)
 }
 
@@ -400,6 +613,14 @@ fn evaluate_king_pawns<const COLOR: usize>(board: &Board) -> Value {
     let king: Square = (board.pieces_bitboard[PieceType::KING] & board.sides_bitboard[COLOR])
         .to_square()
         .unwrap();
+    let enemy_king: Square = (board.pieces_bitboard[PieceType::KING]
+        & board.sides_bitboard[COLOR ^ 1])
+        .to_square()
+        .unwrap();
+
+    // Kings castled on opposite wings race their pawns against each other
+    let (file, enemy_file): (usize, usize) = (king.to_index() % 8, enemy_king.to_index() % 8);
+    let opposite_wings: bool = (file <= 2 && enemy_file >= 5) || (file >= 5 && enemy_file <= 2);
 
     // King safety (shelter, open files, pawn storm) only applies in middlegame.
     // In endgame (no queens) the king should be active, so we skip these penalties.
@@ -418,16 +639,34 @@ fn evaluate_king_pawns<const COLOR: usize>(board: &Board) -> Value {
         let shield_count: usize = (pawns & shield_mask).count_bits() as usize;
         eval += SHIELD_PENALTY[shield_count];
 
-        // Pawn Storm
+        // Pawn Storm: full weight against opposite-side castling, halved when
+        // the enemy pawns would also be opening their own king
         for pawn in shield_mask {
             let enemy_pawn: BitBoard = pawn.file().to_bitboard() & enemy_pawns;
             let distance: usize = if let Some(square) = enemy_pawn.to_square_nearest::<COLOR>() {
-                king.rank().to_index().abs_diff(square.rank().to_index())
+                rank_distance(king.to_index(), square.to_index())
             } else {
                 7
             };
 
-            eval += PAWN_STORM[distance];
+            let storm: Value = PAWN_STORM[distance];
+            eval += if opposite_wings {
+                storm
+            } else {
+                Value(storm.0 / 2, storm.1 / 2)
+            };
+        }
+
+        // Storm Race: our own pawns advancing on the enemy king, scaled by
+        // the file distance between both kings
+        if opposite_wings {
+            let kings_apart: i32 = file_distance(king.to_index(), enemy_king.to_index()) as i32;
+            for pawn in pawns {
+                if file_distance(pawn.to_index(), enemy_king.to_index()) <= 1 {
+                    let advance: i32 = (pawn.rank().to_index() ^ (7 * COLOR)) as i32 - 1;
+                    eval += STORM_RACE * Value(advance * kings_apart, advance * kings_apart);
+                }
+            }
         }
     }
 
@@ -440,15 +679,20 @@ fn evaluate_knights<const COLOR: usize>(board: &Board) -> Value {
         board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR ^ 1];
     let pawns: BitBoard = board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR];
     let outpost: BitBoard = knights & OUTPOST_MASK[COLOR];
+    let safe_outposts: BitBoard = safe_outposts::<COLOR>(board);
 
     for square in knights {
-        eval += PIECE_VALUE[PieceType::KNIGHT];
-        eval += KNIGHT_TABLE[square.to_index() ^ (56 * COLOR)];
-
         // Knight mobility bonus/penalty
         let mobility_count: usize =
             (get_knight_attacks(square) & !board.sides_bitboard[COLOR]).count_bits() as usize;
         eval += KNIGHT_MOBILITY_BONUS[mobility_count];
+
+        // Smaller bonus for a knight one move away from an outpost
+        if outpost.0 & (1 << square.to_index()) == 0
+            && !(get_knight_attacks(square) & safe_outposts).is_empty()
+        {
+            eval += REACHABLE_OUTPOST_KNIGHT;
+        }
     }
 
     // Knight Outpost Bonus
@@ -466,6 +710,50 @@ fn evaluate_knights<const COLOR: usize>(board: &Board) -> Value {
     eval
 }
 
+/// Empty outpost squares of `COLOR`, supported by a pawn and out of reach of the enemy pawns.
+fn safe_outposts<const COLOR: usize>(board: &Board) -> BitBoard {
+    let pawns: BitBoard = board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR];
+    let enemy_pawns: BitBoard =
+        board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR ^ 1];
+    let mut outposts: BitBoard = BitBoard::EMPTY;
+
+    for square in OUTPOST_MASK[COLOR] & !board.sides_bitboard[COLOR] {
+        if enemy_pawns.0 & DOUBLE_SUPPORTED_PAWN_MASKS[COLOR ^ 1][square.to_index()] == 0
+            && pawns.0 & DOUBLE_SUPPORTED_PAWN_MASKS[COLOR][square.to_index()] != 0
+        {
+            outposts = outposts.set_square(square);
+        }
+    }
+
+    outposts
+}
+
+/// Knights and bishops trapped on the rim by enemy pawns.
+fn evaluate_trapped<const COLOR: usize>(board: &Board) -> Value {
+    let mut eval: Value = Value(0, 0);
+    let knights: u64 = (board.pieces_bitboard[PieceType::KNIGHT] & board.sides_bitboard[COLOR]).0;
+    let bishops: u64 = (board.pieces_bitboard[PieceType::BISHOP] & board.sides_bitboard[COLOR]).0;
+    let enemy_pawns: BitBoard =
+        board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR ^ 1];
+    let enemy_pawn_attacks: u64 = enemy_pawns.enemy_pawn_attacks::<COLOR>().0;
+
+    for (square, escape) in TRAPPED_KNIGHT {
+        let (square, escape) = (square ^ (56 * COLOR), escape ^ (56 * COLOR));
+        if knights & (1 << square) != 0 && enemy_pawn_attacks & (1 << escape) != 0 {
+            eval += TRAPPED_KNIGHT_PENALTY;
+        }
+    }
+
+    for (square, pawn, penalty) in TRAPPED_BISHOP {
+        let (square, pawn) = (square ^ (56 * COLOR), pawn ^ (56 * COLOR));
+        if bishops & (1 << square) != 0 && enemy_pawns.0 & (1 << pawn) != 0 {
+            eval += penalty;
+        }
+    }
+
+    eval
+}
+
 fn evaluate_bishops<const COLOR: usize>(board: &Board) -> Value {
     let mut eval: Value = Value(0, 0);
     let bishops: BitBoard = board.pieces_bitboard[PieceType::BISHOP] & board.sides_bitboard[COLOR];
@@ -483,13 +771,55 @@ fn evaluate_bishops<const COLOR: usize>(board: &Board) -> Value {
 
     let blockers: BitBoard = board.sides_bitboard[COLOR] | board.sides_bitboard[COLOR ^ 1];
 
-    for square in bishops {
-        eval += PIECE_VALUE[PieceType::BISHOP];
-        eval += BISHOP_TABLE[square.to_index() ^ (56 * COLOR)];
+    let king: usize = (board.pieces_bitboard[PieceType::KING] & board.sides_bitboard[COLOR])
+        .to_square()
+        .unwrap()
+        .to_index();
 
+    // Own pawns whose push square is occupied
+    let stops: BitBoard = pawns.shift_forward::<COLOR>() & blockers;
+    let fixed: BitBoard = stops.shift_backward::<COLOR>();
+
+    for square in bishops {
         // Bishop mobility bonus/penalty
-        let mobility_count: usize = get_bishop_attacks(square, blockers).count_bits() as usize;
+        let attacks: BitBoard = get_bishop_attacks(square, blockers);
+        let mobility_count: usize = attacks.count_bits() as usize;
         eval += BISHOP_MOBILITY_BONUS[mobility_count];
+
+        // Long diagonal bonus, per central square the bishop sees
+        if LONG_DIAGONALS & (1 << square.to_index()) != 0 {
+            let center: i32 = (attacks.0 & CENTER_MASK).count_ones() as i32;
+            eval += LONG_DIAGONAL_BISHOP * Value(center, center);
+        }
+
+        // Fianchetto bonus, with its pawn in front and the king castled behind it
+        for (bishop, kings, pawn) in FIANCHETTO {
+            if square.to_index() == bishop ^ (56 * COLOR)
+                && kings << (56 * COLOR) & (1 << king) != 0
+                && pawns.0 & (1 << (pawn ^ (56 * COLOR))) != 0
+            {
+                eval += FIANCHETTO_BISHOP;
+            }
+        }
+
+        // Bad bishop penalty, per own pawn on the bishop's color complex
+        let complex: BitBoard = if BitBoard::LIGHT_SQUARES.0 & (1 << square.to_index()) != 0 {
+            BitBoard::LIGHT_SQUARES
+        } else {
+            BitBoard::DARK_SQUARES
+        };
+        let same: i32 = (pawns & complex).count_bits() as i32;
+        eval += BAD_BISHOP * Value(same, same);
+
+        // Fixed pawns on the same complex weigh more, as they will stay there
+        let blocked: i32 = (fixed & complex).count_bits() as i32;
+        eval += FIXED_PAWN_BISHOP * Value(blocked, blocked);
+
+        // Pawn structure mostly on the other complex
+        let opposite: i32 = pawns.count_bits() as i32 - same;
+        if opposite > same {
+            eval += OPPOSITE_COMPLEX_BISHOP;
+        }
     }
 
     // Bishop Outpost Bonus
@@ -518,13 +848,18 @@ fn evaluate_rooks<const COLOR: usize>(board: &Board) -> Value {
         .to_square()
         .unwrap();
     let outpost: BitBoard = rooks & ROOK_OUTPOST_MASK[COLOR];
+    let king: Square = (board.pieces_bitboard[PieceType::KING] & board.sides_bitboard[COLOR])
+        .to_square()
+        .unwrap();
 
     let blockers: BitBoard = board.sides_bitboard[COLOR] | board.sides_bitboard[COLOR ^ 1];
 
-    for square in rooks {
-        eval += PIECE_VALUE[PieceType::ROOK];
-        eval += ROOK_TABLE[square.to_index() ^ (56 * COLOR)];
+    // A king that left its original square has lost its castling rights
+    let home_rank: usize = 7 * COLOR;
+    let king_file: usize = king.to_index() % 8;
+    let uncastled: bool = king.rank().to_index() == home_rank && king_file != 4;
 
+    for square in rooks {
         // Open/Semi-open file bonus
         if pawns & square.file().to_bitboard() == BitBoard::EMPTY {
             let open: usize =
@@ -540,8 +875,34 @@ fn evaluate_rooks<const COLOR: usize>(board: &Board) -> Value {
         }
 
         // Rook mobility bonus/penalty
-        let mobility_count: usize = get_rook_attacks(square, blockers).count_bits() as usize;
+        let attacks: BitBoard = get_rook_attacks(square, blockers);
+        let mobility_count: usize = attacks.count_bits() as usize;
         eval += ROOK_MOBILITY_BONUS[mobility_count];
+
+        // Trapped rook: shut in the corner by a king that can no longer castle
+        let file: usize = square.to_index() % 8;
+        if uncastled
+            && mobility_count <= TRAPPED_ROOK_MOBILITY
+            && square.rank().to_index() == home_rank
+            && ((king_file >= 5 && file > king_file) || (king_file <= 3 && file < king_file))
+        {
+            eval += TRAPPED_ROOK;
+        }
+
+        // Connected rooks on the back rank and rooks doubled on a pawnless file,
+        // each pair counted once
+        for other in attacks & rooks {
+            if other.to_index() < square.to_index() {
+                continue;
+            }
+            if other.to_index() / 8 == home_rank && square.to_index() / 8 == home_rank {
+                eval += CONNECTED_ROOKS;
+            } else if other.to_index() % 8 == file
+                && pawns & square.file().to_bitboard() == BitBoard::EMPTY
+            {
+                eval += DOUBLED_ROOKS_OPEN;
+            }
+        }
     }
 
     // Rook Outpost Bonus
@@ -565,56 +926,350 @@ fn evaluate_queens<const COLOR: usize>(board: &Board) -> Value {
 
     let blockers: BitBoard = board.sides_bitboard[COLOR] | board.sides_bitboard[COLOR ^ 1];
 
-    for square in queens {
-        eval += PIECE_VALUE[PieceType::QUEEN];
-        eval += QUEEN_TABLE[square.to_index() ^ (56 * COLOR)];
+    // Minor pieces left at home, counted only while the game is in its opening
+    let undeveloped: i32 = if phase(board) >= EARLY_QUEEN_PHASE {
+        let knights: u64 =
+            (board.pieces_bitboard[PieceType::KNIGHT] & board.sides_bitboard[COLOR]).0;
+        let bishops: u64 =
+            (board.pieces_bitboard[PieceType::BISHOP] & board.sides_bitboard[COLOR]).0;
+        ((knights & KNIGHT_HOMES << (56 * COLOR)).count_ones()
+            + (bishops & BISHOP_HOMES << (56 * COLOR)).count_ones()) as i32
+    } else {
+        0
+    };
 
+    // Weak squares around the enemy king, out of reach of its pawns
+    let enemy_king: Square = (board.pieces_bitboard[PieceType::KING]
+        & board.sides_bitboard[COLOR ^ 1])
+        .to_square()
+        .unwrap();
+    let enemy_pawns: BitBoard =
+        board.pieces_bitboard[PieceType::PAWN] & board.sides_bitboard[COLOR ^ 1];
+    let enemy_pawn_attacks: u64 = enemy_pawns.enemy_pawn_attacks::<COLOR>().0;
+    let weak: u64 = KING_ZONE[COLOR ^ 1][enemy_king.to_index()] & !enemy_pawn_attacks;
+
+    for square in queens {
         // Queen mobility bonus/penalty
         let mobility_count: usize = (get_rook_attacks(square, blockers)
             | get_bishop_attacks(square, blockers))
         .count_bits() as usize;
         eval += QUEEN_MOBILITY_BONUS[mobility_count];
+
+        // Early development penalty
+        if square.to_index() != QUEEN_HOME ^ (56 * COLOR) {
+            eval += EARLY_QUEEN * Value(undeveloped, undeveloped);
+        }
+
+        // Queen infiltration bonus
+        if weak & (1 << square.to_index()) != 0 {
+            eval += QUEEN_INFILTRATION;
+        }
+    }
+
+    eval
+}
+
+const PIECE_TABLES: [[Value; 64]; 6] = [
+    PAWN_TABLE,
+    KNIGHT_TABLE,
+    BISHOP_TABLE,
+    ROOK_TABLE,
+    QUEEN_TABLE,
+    KING_TABLE,
+];
+
+/// Material and piece-square value of a piece, from the point of view of its color.
+#[inline(always)]
+fn piece_square(piece: usize, color: usize, square: Square) -> Value {
+    let mut value: Value = PIECE_VALUE[piece];
+    value += PIECE_TABLES[piece][square.to_index() ^ (56 * color)];
+    value
+}
+
+/// Piece-square gain of a quiet move for the side to move, destination minus
+/// source, interpolated by `phase` like the evaluation.
+pub fn pst_gain(board: &Board, mv: Move, phase: i32) -> i32 {
+    let Some(piece) = board.piece_on(mv.get_src()) else {
+        return 0;
+    };
+    let (piece, color): (usize, usize) = (piece.piece_type() as usize, board.side() as usize);
+    let gain: Value = PIECE_TABLES[piece][mv.get_dest().to_index() ^ (56 * color)]
+        - PIECE_TABLES[piece][mv.get_src().to_index() ^ (56 * color)];
+    (gain.0 * phase + gain.1 * (128 - phase)) / 128
+}
+
+/// Material and piece-square score from White's point of view, computed from scratch.
+pub fn psqt(board: &Board) -> Value {
+    let mut eval: Value = Value(0, 0);
+    for piece in 0..6 {
+        for square in board.pieces_bitboard[piece] & board.sides_bitboard[WHITE] {
+            eval += piece_square(piece, WHITE, square);
+        }
+        for square in board.pieces_bitboard[piece] & board.sides_bitboard[BLACK] {
+            eval -= piece_square(piece, BLACK, square);
+        }
+    }
+
+    eval
+}
+
+/// Change of the material and piece-square score between two consecutive boards.
+/// Only the pieces that were removed or added are visited, which covers captures,
+/// promotions, castling and en passant alike.
+pub fn psqt_delta(old: &Board, new: &Board) -> Value {
+    let mut delta: Value = Value(0, 0);
+    for piece in 0..6 {
+        for color in [WHITE, BLACK] {
+            let before: BitBoard = old.pieces_bitboard[piece] & old.sides_bitboard[color];
+            let after: BitBoard = new.pieces_bitboard[piece] & new.sides_bitboard[color];
+            let changed: BitBoard = before ^ after;
+            if changed.is_empty() {
+                continue;
+            }
+
+            let mut value: Value = Value(0, 0);
+            for square in after & changed {
+                value += piece_square(piece, color, square);
+            }
+            for square in before & changed {
+                value -= piece_square(piece, color, square);
+            }
+
+            if color == WHITE {
+                delta += value;
+            } else {
+                delta -= value;
+            }
+        }
+    }
+
+    delta
+}
+
+/// King tropism: pieces closer to the enemy king get a bonus scaled by piece type.
+fn evaluate_tropism<const COLOR: usize>(board: &Board) -> Value {
+    let mut eval: Value = Value(0, 0);
+    let enemy_king: usize = (board.pieces_bitboard[PieceType::KING]
+        & board.sides_bitboard[COLOR ^ 1])
+        .to_square()
+        .unwrap()
+        .to_index();
+
+    for piece in [
+        PieceType::KNIGHT,
+        PieceType::BISHOP,
+        PieceType::ROOK,
+        PieceType::QUEEN,
+    ] {
+        for square in board.pieces_bitboard[piece] & board.sides_bitboard[COLOR] {
+            let closeness: i32 = 7 - distance(square.to_index(), enemy_king) as i32;
+            eval += KING_TROPISM[piece] * Value(closeness, closeness);
+        }
     }
 
     eval
 }
 
-fn evaluate_kings<const COLOR: usize>(board: &Board) -> Value {
+/// Squares attacked by each piece type of one side.
+fn attack_map<const COLOR: usize>(board: &Board) -> [BitBoard; 6] {
+    let side: BitBoard = board.sides_bitboard[COLOR];
+    let occupied: BitBoard = board.combined_bitboard();
+    let mut attacks: [BitBoard; 6] = [BitBoard::EMPTY; 6];
+
+    attacks[PieceType::PAWN] =
+        (board.pieces_bitboard[PieceType::PAWN] & side).pawn_attacks::<COLOR>();
+    attacks[PieceType::KNIGHT] = (board.pieces_bitboard[PieceType::KNIGHT] & side).knight_attacks();
+    for square in board.pieces_bitboard[PieceType::BISHOP] & side {
+        attacks[PieceType::BISHOP] |= get_bishop_attacks(square, occupied);
+    }
+    for square in board.pieces_bitboard[PieceType::ROOK] & side {
+        attacks[PieceType::ROOK] |= get_rook_attacks(square, occupied);
+    }
+    for square in board.pieces_bitboard[PieceType::QUEEN] & side {
+        attacks[PieceType::QUEEN] |=
+            get_bishop_attacks(square, occupied) | get_rook_attacks(square, occupied);
+    }
+    attacks[PieceType::KING] = (board.pieces_bitboard[PieceType::KING] & side).king_attacks();
+
+    attacks
+}
+
+/// Safe checks against the king of `COLOR`: squares from which an enemy piece could
+/// give check without landing on a square we defend.
+fn evaluate_safe_checks<const COLOR: usize>(board: &Board, attacks: &[[BitBoard; 6]; 2]) -> Value {
     let mut eval: Value = Value(0, 0);
+    let enemy: &[BitBoard; 6] = &attacks[COLOR ^ 1];
+    let occupied: BitBoard = board.combined_bitboard();
     let king: Square = (board.pieces_bitboard[PieceType::KING] & board.sides_bitboard[COLOR])
         .to_square()
         .unwrap();
 
-    eval += KING_TABLE[king.to_index() ^ (56 * COLOR)];
+    let defended: BitBoard = attacks[COLOR]
+        .iter()
+        .fold(BitBoard::EMPTY, |all, attack| all | *attack);
+    let safe: BitBoard = !defended & !board.sides_bitboard[COLOR ^ 1];
+
+    let bishop_rays: BitBoard = get_bishop_attacks(king, occupied);
+    let rook_rays: BitBoard = get_rook_attacks(king, occupied);
+    let checks: [BitBoard; 4] = [
+        get_knight_attacks(king) & enemy[PieceType::KNIGHT],
+        bishop_rays & enemy[PieceType::BISHOP],
+        rook_rays & enemy[PieceType::ROOK],
+        (bishop_rays | rook_rays) & enemy[PieceType::QUEEN],
+    ];
+    for (piece, squares) in [
+        PieceType::KNIGHT,
+        PieceType::BISHOP,
+        PieceType::ROOK,
+        PieceType::QUEEN,
+    ]
+    .into_iter()
+    .zip(checks)
+    {
+        if !(squares & safe).is_empty() {
+            eval += SAFE_CHECK[piece];
+        }
+    }
+
+    // Queen contact checks: next to the king, supported by another enemy piece
+    // and defended by nothing but the king itself
+    let defended_by_pieces: BitBoard = attacks[COLOR][..PieceType::KING]
+        .iter()
+        .fold(BitBoard::EMPTY, |all, attack| all | *attack);
+    let support: BitBoard = enemy
+        .iter()
+        .enumerate()
+        .filter(|(piece, _)| *piece != PieceType::QUEEN)
+        .fold(BitBoard::EMPTY, |all, (_, attack)| all | *attack);
+    let contact: BitBoard = BitBoard(KING_RING[king.to_index()])
+        & enemy[PieceType::QUEEN]
+        & support
+        & !defended_by_pieces
+        & !board.sides_bitboard[COLOR ^ 1];
+    if !contact.is_empty() {
+        eval += QUEEN_CONTACT_CHECK;
+    }
 
     eval
 }
 
-fn non_pawn_material(piece: Piece) -> i32 {
-    match piece.piece_type() {
-        PieceType::Pawn => 0,
-        PieceType::Knight => 781,
-        PieceType::Bishop => 825,
-        PieceType::Rook => 1276,
-        PieceType::Queen => 2538,
-        PieceType::King => 0,
+/// Non-pawn material of one side, using the Stockfish middlegame values.
+pub fn non_pawn_material<const COLOR: usize>(board: &Board) -> i32 {
+    let side: BitBoard = board.sides_bitboard[COLOR];
+
+    (board.pieces_bitboard[PieceType::KNIGHT] & side).count_bits() as i32 * 781
+        + (board.pieces_bitboard[PieceType::BISHOP] & side).count_bits() as i32 * 825
+        + (board.pieces_bitboard[PieceType::ROOK] & side).count_bits() as i32 * 1276
+        + (board.pieces_bitboard[PieceType::QUEEN] & side).count_bits() as i32 * 2538
+}
+
+/// Whether a rook or queen of the side to move is en prise: attacked by a cheaper
+/// piece, a queen attacked by a rook, or any major attacked and left undefended.
+pub fn hanging_major(board: &Board) -> bool {
+    let us: usize = board.side() as usize;
+    let side: BitBoard = board.sides_bitboard[us];
+    let rooks: BitBoard = board.pieces_bitboard[PieceType::ROOK] & side;
+    let queens: BitBoard = board.pieces_bitboard[PieceType::QUEEN] & side;
+    let majors: BitBoard = rooks | queens;
+    if majors.is_empty() {
+        return false;
+    }
+
+    // Most nodes have no major under attack, so the attack maps are skipped
+    let occupied: BitBoard = board.combined_bitboard();
+    if majors
+        .into_iter()
+        .all(|square| !board.attacked_square(square, occupied))
+    {
+        return false;
+    }
+
+    let attacks: [[BitBoard; 6]; 2] = [attack_map::<WHITE>(board), attack_map::<BLACK>(board)];
+    let (ours, enemy): (&[BitBoard; 6], &[BitBoard; 6]) = (&attacks[us], &attacks[us ^ 1]);
+    let minors: BitBoard =
+        enemy[PieceType::PAWN] | enemy[PieceType::KNIGHT] | enemy[PieceType::BISHOP];
+    let attacked: BitBoard = enemy.iter().fold(BitBoard::EMPTY, |acc, &bb| acc | bb);
+    let defended: BitBoard = ours.iter().fold(BitBoard::EMPTY, |acc, &bb| acc | bb);
+
+    !(majors & minors).is_empty()
+        || !(queens & enemy[PieceType::ROOK]).is_empty()
+        || !(majors & attacked & !defended).is_empty()
+}
+
+/// Endgame scale factor out of SCALE_NORMAL. Opposite-colored bishops are
+/// drawish, the more so the fewer other pieces remain to support a break.
+fn scale_factor(board: &Board) -> i32 {
+    let bishops: BitBoard = board.pieces_bitboard[PieceType::BISHOP];
+    let white: BitBoard = bishops & board.sides_bitboard[WHITE];
+    let black: BitBoard = bishops & board.sides_bitboard[BLACK];
+    let opposite: bool = white.count_bits() == 1
+        && black.count_bits() == 1
+        && (bishops & BitBoard::LIGHT_SQUARES).count_bits() == 1;
+    if !opposite {
+        return SCALE_NORMAL;
     }
+
+    let others: i32 = (board.pieces_bitboard[PieceType::KNIGHT]
+        | board.pieces_bitboard[PieceType::ROOK]
+        | board.pieces_bitboard[PieceType::QUEEN])
+        .count_bits() as i32;
+    SCALE_NORMAL.min(OCB_SCALE + OCB_SCALE_PER_PIECE * others)
 }
 
-fn phase(board: &Board) -> i32 {
+/// Game phase in the range 0 (endgame) to 128 (middlegame).
+pub fn phase(board: &Board) -> i32 {
     const MG_LIMIT: i32 = 15258;
     const EG_LIMIT: i32 = 3915;
 
-    let mut npm: i32 = board.pieces_bitboard[PieceType::KNIGHT].count_bits() as i32 * 781
-        + board.pieces_bitboard[PieceType::BISHOP].count_bits() as i32 * 825
-        + board.pieces_bitboard[PieceType::ROOK].count_bits() as i32 * 1276
-        + board.pieces_bitboard[PieceType::QUEEN].count_bits() as i32 * 2538;
+    let mut npm: i32 = non_pawn_material::<WHITE>(board) + non_pawn_material::<BLACK>(board);
 
     npm = EG_LIMIT.max(MG_LIMIT.min(npm));
 
     ((npm - EG_LIMIT) * 128) / (MG_LIMIT - EG_LIMIT)
 }
 
+/// Squares adjacent to the king.
+pub const KING_RING: [u64; 64] = {
+    let mut table: [u64; 64] = [0; 64];
+    let mut king: usize = 0;
+    while king < 64 {
+        let mut square: usize = 0;
+        while square < 64 {
+            if distance(king, square) == 1 {
+                table[king] |= 1 << square;
+            }
+            square += 1;
+        }
+        king += 1;
+    }
+    table
+};
+
+/// King square, its ring and the three squares two ranks in front of it.
+pub const KING_ZONE: [[u64; 64]; 2] = {
+    let mut table: [[u64; 64]; 2] = [[0; 64]; 2];
+    let mut king: usize = 0;
+    while king < 64 {
+        let mut square: usize = 0;
+        while square < 64 {
+            let file: usize = file_distance(king, square);
+            let (king_rank, rank) = (king / 8, square / 8);
+
+            if distance(king, square) <= 1 {
+                table[WHITE][king] |= 1 << square;
+                table[BLACK][king] |= 1 << square;
+            } else if file <= 1 && rank == king_rank + 2 {
+                table[WHITE][king] |= 1 << square;
+            } else if file <= 1 && rank + 2 == king_rank {
+                table[BLACK][king] |= 1 << square;
+            }
+            square += 1;
+        }
+        king += 1;
+    }
+    table
+};
+
 #[rustfmt::skip]
 pub const PASSED_PAWN_MASKS: [[u64; 64]; 2] = [
     [
@@ -680,6 +1335,7 @@ pub const DOUBLED_PAWN_MASK: [u64; 64] = [
     282578800148737, 565157600297474, 1130315200594948, 2260630401189896, 4521260802379792, 9042521604759584, 18085043209519168, 36170086419038336,
 ];
 const CENTER_MASK: u64 = 103481868288;
+const LONG_DIAGONALS: u64 = 0x8040_2010_0804_0201 | 0x0102_0408_1020_4080;
 #[rustfmt::skip]
 pub const KING_SHELTER_MASK: [[u64; 64]; 2] = [
     [
@@ -708,11 +1364,19 @@ pub const ROOK_OUTPOST_MASK: [BitBoard; 2] = [BitBoard(55102866016174080), BitBo
 
 #[cfg(test)]
 mod test {
+    use crate::bitboard::Shift;
     use crate::evaluation::{
-        CONNECTED_PAWN_BONUS, DOUBLE_SUPPORTED_PAWN_MASKS, OUTPOST_MASK, Value, WHITE,
-        connected_pawns, evaluate,
+        BAD_BISHOP, BLACK, CANDIDATE_PASSER_BONUS, CONNECTED_PAWN_BONUS, CONNECTED_ROOKS,
+        DOUBLE_SUPPORTED_PAWN_MASKS, EARLY_QUEEN, FIANCHETTO_BISHOP, FIXED_PAWN_BISHOP, KING_RING,
+        KING_ZONE, OCB_SCALE, OCB_SCALE_PER_PIECE, OPPOSITE_COMPLEX_BISHOP, OUTPOST_MASK,
+        QUEEN_CONTACT_CHECK, QUEEN_INFILTRATION, QUEEN_MOBILITY_BONUS, ROOK_MOBILITY_BONUS,
+        SAFE_CHECK, SCALE_NORMAL, STORM_RACE, TRAPPED_KNIGHT_PENALTY, TRAPPED_ROOK, Value, WHITE,
+        attack_map, connected_pawns, evaluate, evaluate_bishops, evaluate_king_pawns,
+        evaluate_queens, evaluate_rooks, evaluate_safe_checks, evaluate_trapped, hanging_major,
+        pawn_structure, psqt, psqt_delta, safe_outposts, scale_factor,
     };
-    use laura_core::{BitBoard, Board};
+    use laura_core::PieceType;
+    use laura_core::{BitBoard, Board, Move, Square, get_bishop_attacks, get_rook_attacks};
     use std::str::FromStr;
 
     #[test]
@@ -721,6 +1385,218 @@ mod test {
         assert_eq!(evaluate(&board), 20);
     }
 
+    #[test]
+    fn incremental_psqt() {
+        // Castling, en passant, captures and promotions
+        let games: [(&str, &[&str]); 3] = [
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                &["e1g1", "e8c8", "e5f7", "h3g2", "a2a3", "g2f1q"],
+            ),
+            (
+                "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+                &["e5d6", "e8d7", "e1e2", "d7d6"],
+            ),
+            (
+                "4k3/1P6/8/8/8/8/6p1/4K2R w K - 0 1",
+                &["b7b8n", "g2h1r", "e1f2"],
+            ),
+        ];
+        for (fen, moves) in games {
+            let mut board: Board = Board::from_str(fen).unwrap();
+            let mut score: Value = psqt(&board);
+            for uci in moves {
+                let mv: Move = board.find_move(uci).unwrap();
+                let next: Board = board.make_move(mv);
+                score += psqt_delta(&board, &next);
+                assert_eq!(score, psqt(&next), "{fen} {uci}");
+                board = next;
+            }
+        }
+    }
+
+    #[test]
+    fn queen_terms() {
+        // Queen out on h5 with all four minors still at home
+        let early: Board =
+            Board::from_str("rnbqkbnr/pppp1ppp/8/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR b KQkq - 1 2")
+                .unwrap();
+        assert_eq!(
+            evaluate_queens::<WHITE>(&early),
+            QUEEN_MOBILITY_BONUS[14] + EARLY_QUEEN * Value(4, 4)
+        );
+
+        // Queen on f6, a hole in front of the castled king
+        let infiltrated: Board = Board::from_str("6k1/5p1p/5QpB/8/8/8/8/6K1 b - - 0 1").unwrap();
+        let blockers: BitBoard =
+            infiltrated.sides_bitboard[WHITE] | infiltrated.sides_bitboard[BLACK];
+        let mobility: usize = (get_rook_attacks(Square::F6, blockers)
+            | get_bishop_attacks(Square::F6, blockers))
+        .count_bits() as usize;
+        assert_eq!(
+            evaluate_queens::<WHITE>(&infiltrated),
+            QUEEN_MOBILITY_BONUS[mobility] + QUEEN_INFILTRATION
+        );
+
+        // Same square covered by a pawn is not weak
+        let covered: Board = Board::from_str("6k1/4pp1p/5QpB/8/8/8/8/6K1 b - - 0 1").unwrap();
+        let blockers: BitBoard = covered.sides_bitboard[WHITE] | covered.sides_bitboard[BLACK];
+        let mobility: usize = (get_rook_attacks(Square::F6, blockers)
+            | get_bishop_attacks(Square::F6, blockers))
+        .count_bits() as usize;
+        assert_eq!(
+            evaluate_queens::<WHITE>(&covered),
+            QUEEN_MOBILITY_BONUS[mobility]
+        );
+    }
+
+    #[test]
+    fn rook_terms() {
+        // The king walked to f1, so the h1 rook can no longer castle its way out
+        let trapped: Board = Board::from_str("4k3/8/8/8/8/8/5PPP/5K1R w - - 0 1").unwrap();
+        let castled: Board = Board::from_str("4k3/8/8/8/8/8/5PPP/4K2R w K - 0 1").unwrap();
+        assert_eq!(
+            evaluate_rooks::<WHITE>(&trapped),
+            ROOK_MOBILITY_BONUS[3] + TRAPPED_ROOK
+        );
+        assert_eq!(evaluate_rooks::<WHITE>(&castled), ROOK_MOBILITY_BONUS[4]);
+
+        // Connected on the back rank, then doubled on a file without own pawns
+        let connected: Board = Board::from_str("4k3/8/8/8/8/8/PP4PP/R4RK1 w - - 0 1").unwrap();
+        let apart: Board = Board::from_str("4k3/8/8/8/8/8/PP4PP/R1N2RK1 w - - 0 1").unwrap();
+        let diff: Value = evaluate_rooks::<WHITE>(&connected) - evaluate_rooks::<WHITE>(&apart);
+        assert!(diff.0 >= CONNECTED_ROOKS.0);
+        let doubled: Board = Board::from_str("4k3/8/8/8/8/3R4/PP4PP/3R2K1 w - - 0 1").unwrap();
+        let single: Board = Board::from_str("4k3/8/8/8/8/3R4/PP4PP/3N2K1 w - - 0 1").unwrap();
+        assert!(evaluate_rooks::<WHITE>(&doubled).0 > evaluate_rooks::<WHITE>(&single).0);
+    }
+
+    #[test]
+    fn bishop_terms() {
+        // A fianchettoed bishop in front of the castled king
+        let fianchetto: Board = Board::from_str("4k3/8/8/8/8/6P1/5PBP/6K1 w - - 0 1").unwrap();
+        let uncastled: Board = Board::from_str("4k3/8/8/8/8/6P1/5PBP/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            evaluate_bishops::<WHITE>(&fianchetto) - evaluate_bishops::<WHITE>(&uncastled),
+            FIANCHETTO_BISHOP
+        );
+
+        // Mirrored for Black
+        let black: Board = Board::from_str("6k1/5pbp/6p1/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(
+            evaluate_bishops::<BLACK>(&black),
+            evaluate_bishops::<WHITE>(&fianchetto)
+        );
+
+        // Own pawns on the bishop's color make it worse
+        let good: Board = Board::from_str("4k3/8/8/8/8/8/1B2P3/4K3 w - - 0 1").unwrap();
+        let bad: Board = Board::from_str("4k3/8/8/8/8/8/1B1P4/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            evaluate_bishops::<WHITE>(&bad) - evaluate_bishops::<WHITE>(&good),
+            BAD_BISHOP - OPPOSITE_COMPLEX_BISHOP
+        );
+
+        // A fixed pawn on the bishop's complex is penalised on top of it
+        let free: Board = Board::from_str("4k3/8/8/8/3P4/8/1B6/4K3 w - - 0 1").unwrap();
+        let fixed: Board = Board::from_str("4k3/8/8/3p4/3P4/8/1B6/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            evaluate_bishops::<WHITE>(&fixed) - evaluate_bishops::<WHITE>(&free),
+            FIXED_PAWN_BISHOP
+        );
+    }
+
+    #[test]
+    fn hanging_majors() {
+        let hanging = |fen: &str| hanging_major(&Board::from_str(fen).unwrap());
+
+        // Rook attacked by a knight, defended or not
+        assert!(hanging("4k3/8/4n3/8/3R4/8/8/3QK3 w - - 0 1"));
+        // Rook traded for a rook only when nothing defends it
+        assert!(!hanging("4k3/8/8/8/r2R4/8/8/3QK3 w - - 0 1"));
+        assert!(hanging("4k3/8/8/8/r2R4/8/8/4K3 w - - 0 1"));
+        // A queen attacked by a rook is lost even when defended
+        assert!(hanging("4k3/8/8/8/r2Q4/8/8/3RK3 w - - 0 1"));
+        // Only the side to move is considered
+        assert!(!hanging("4k3/8/4n3/8/3R4/8/8/4K3 b - - 0 1"));
+    }
+
+    #[test]
+    fn opposite_bishops_scaling() {
+        let same: Board = Board::from_str("4k3/5b2/8/3p4/3P4/2P5/4B3/4K3 w - - 0 1").unwrap();
+        let pure: Board = Board::from_str("4k3/4b3/8/3p4/3P4/2P5/4B3/4K3 w - - 0 1").unwrap();
+        let rooks: Board = Board::from_str("r3k3/4b3/8/3p4/3P4/2P5/4B3/R3K3 w - - 0 1").unwrap();
+        let heavy: Board =
+            Board::from_str("rr1qk3/4b3/8/3p4/3P4/2P5/4B3/RR1QK3 w - - 0 1").unwrap();
+
+        assert_eq!(scale_factor(&same), SCALE_NORMAL);
+        assert_eq!(scale_factor(&pure), OCB_SCALE);
+        assert_eq!(scale_factor(&rooks), OCB_SCALE + 2 * OCB_SCALE_PER_PIECE);
+        assert_eq!(scale_factor(&heavy), SCALE_NORMAL);
+
+        // The extra pawn is worth much less with only the bishops left
+        assert!(evaluate(&pure).abs() < evaluate(&same).abs());
+    }
+
+    #[test]
+    fn storm_opposite_castling() {
+        // The same g5/h5 storm hurts more when the kings are on opposite wings
+        let same: Board = Board::from_str("6k1/q4p2/8/6pp/8/8/5PPP/6K1 w - - 0 1").unwrap();
+        let opposite: Board = Board::from_str("2k5/q4p2/8/6pp/8/8/5PPP/6K1 w - - 0 1").unwrap();
+        assert!(evaluate_king_pawns::<WHITE>(&opposite).0 < evaluate_king_pawns::<WHITE>(&same).0);
+
+        // Racing pawns are worth more the further they have advanced
+        let home: Board = Board::from_str("2k5/q7/8/8/8/8/1P3PPP/6K1 w - - 0 1").unwrap();
+        let race: Board = Board::from_str("2k5/q7/8/1P6/8/8/5PPP/6K1 w - - 0 1").unwrap();
+        let bonus: Value =
+            evaluate_king_pawns::<WHITE>(&race) - evaluate_king_pawns::<WHITE>(&home);
+        assert_eq!(bonus, STORM_RACE * Value(3 * 4, 3 * 4));
+    }
+
+    #[test]
+    fn reachable_outposts() {
+        // d5 is supported by e4 and no black pawn can challenge it; Nc3 can reach it
+        let board: Board = Board::from_str("4k3/pp4pp/8/8/4P3/2N5/PP4PP/4K3 w - - 0 1").unwrap();
+        let outposts: BitBoard = safe_outposts::<WHITE>(&board);
+        assert_ne!(outposts.0 & (1 << Square::D5.to_index()), 0);
+        assert_eq!(outposts.0 & (1 << Square::C5.to_index()), 0);
+    }
+
+    #[test]
+    fn trapped_pieces() {
+        // Ba7 shut in by ...b6
+        let trapped: Board = Board::from_str("4k3/B7/1p6/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let free: Board = Board::from_str("4k3/B7/2p5/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(evaluate_trapped::<WHITE>(&trapped), Value(-90, -90));
+        assert_eq!(evaluate_trapped::<WHITE>(&free), Value(0, 0));
+
+        // Black knight on h1 with g3 covered by the h2 pawn
+        let board: Board = Board::from_str("4k3/8/8/8/8/8/7P/4K2n b - - 0 1").unwrap();
+        assert_eq!(evaluate_trapped::<BLACK>(&board), TRAPPED_KNIGHT_PENALTY);
+    }
+
+    #[test]
+    fn safe_checks() {
+        let checks = |fen: &str| -> Value {
+            let board: Board = Board::from_str(fen).unwrap();
+            let attacks: [[BitBoard; 6]; 2] =
+                [attack_map::<WHITE>(&board), attack_map::<BLACK>(&board)];
+            evaluate_safe_checks::<WHITE>(&board, &attacks)
+        };
+
+        // Nf3+ is covered by the g2 pawn...
+        assert_eq!(checks("6k1/8/8/4n3/8/8/5PPP/6K1 w - - 0 1"), Value(0, 0));
+        // ...but not once it is gone
+        assert_eq!(
+            checks("6k1/8/8/4n3/8/8/5P1P/6K1 w - - 0 1"),
+            SAFE_CHECK[PieceType::KNIGHT]
+        );
+        // Qg2 contact check supported by the h3 pawn
+        assert_eq!(
+            checks("6k1/8/8/8/8/6qp/5P1P/6K1 w - - 0 1"),
+            QUEEN_CONTACT_CHECK
+        );
+    }
+
     #[test]
     fn test_evaluation() {
         let board: Board =
@@ -735,6 +1611,54 @@ mod test {
         println!("Evaluation: {}", evaluate(&board));
     }
 
+    #[test]
+    fn candidate_passer() {
+        // The b4 pawn faces a single sentry on a5 and is helped by the c2 pawn,
+        // itself held back by the d4 sentry. A second sentry on a6 outnumbers
+        // the helper and is the only difference between both positions.
+        let board: Board = Board::from_str("8/6k1/8/p7/1P1p4/8/2P5/1K6 w - - 0 1").unwrap();
+        let (candidate, passed) = pawn_structure::<WHITE>(&board);
+        assert!(passed.is_empty());
+
+        let board: Board = Board::from_str("8/6k1/p7/p7/1P1p4/8/2P5/1K6 w - - 0 1").unwrap();
+        let (outnumbered, passed) = pawn_structure::<WHITE>(&board);
+        assert!(passed.is_empty());
+        assert_eq!(candidate - outnumbered, CANDIDATE_PASSER_BONUS[3]);
+    }
+
+    #[test]
+    fn backward_pawn() {
+        // The d3 pawn has no support from the c/e files and its stop square is
+        // controlled by the c5 pawn; with the pawn on c6 it is not backward.
+        let backward: Board = Board::from_str("4k3/8/8/2p5/4P3/3P4/8/4K3 w - - 0 1").unwrap();
+        let free: Board = Board::from_str("4k3/8/2p5/8/4P3/3P4/8/4K3 w - - 0 1").unwrap();
+        let (backward_eval, _) = pawn_structure::<WHITE>(&backward);
+        let (free_eval, _) = pawn_structure::<WHITE>(&free);
+        assert!(backward_eval.0 < free_eval.0);
+    }
+
+    #[test]
+    fn king_masks() {
+        // g1: f1, h1, f2, g2, h2
+        assert_eq!(KING_RING[Square::G1.to_index()], 0xE0A0);
+        assert_eq!(KING_RING[Square::E4.to_index()].count_ones(), 8);
+
+        // g1 zone adds f3, g3, h3 for White only
+        assert_eq!(KING_ZONE[WHITE][Square::G1.to_index()], 0xE0_E0E0);
+        assert_eq!(KING_ZONE[BLACK][Square::G1.to_index()], 0xE0E0);
+        assert_eq!(KING_ZONE[BLACK][Square::G8.to_index()].count_ones(), 9);
+    }
+
+    #[test]
+    fn pawn_cache() {
+        let board: Board =
+            Board::from_str("r2r2k1/ppp2p1p/5qp1/3pnb2/1b1NpQ2/1PN1P3/P1PP1PPP/R3KB1R b KQ - 0 1")
+                .unwrap();
+        let first: i32 = evaluate(&board);
+        let second: i32 = evaluate(&board);
+        assert_eq!(first, second);
+    }
+
     #[test]
     fn doubled_pawn() {
         let board: Board = Board::from_str("8/6k1/8/1P4p1/1P6/6P1/1K6/8 w - - 0 1").unwrap();
@@ -746,7 +1670,7 @@ mod test {
         let board: Board =
             Board::from_str("rnbqkbnr/pppppppp/8/7P/8/8/PPPPPPP1/RNBQKBNR w KQkq - 0 1").unwrap();
         let pawns: BitBoard = board.allied_pawns();
-        let phalanx: BitBoard = pawns.left_for::<WHITE>() | pawns.right_for::<WHITE>();
+        let phalanx: BitBoard = pawns.shift_left::<WHITE>() | pawns.shift_right::<WHITE>();
         println!("Phalanx: {}", phalanx);
         println!("{}", phalanx & pawns);
     }
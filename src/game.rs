@@ -0,0 +1,73 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/game.rs
+
+//! Game results and rule-based game states.
+
+use laura_core::Color;
+use std::fmt;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    #[default]
+    Ongoing,
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameResult::WhiteWins => write!(f, "1-0"),
+            GameResult::BlackWins => write!(f, "0-1"),
+            GameResult::Draw => write!(f, "1/2-1/2"),
+            GameResult::Ongoing => write!(f, "*"),
+        }
+    }
+}
+
+/// Rule-based state of a position, as decided by `Position::game_state`.
+/// Repetitions need the game history and are left to `Position::is_draw`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    Ongoing,
+    Checkmate,
+    Stalemate,
+    DrawByFifty,
+    DrawByInsufficientMaterial,
+}
+
+impl GameState {
+    #[inline(always)]
+    pub fn is_over(self) -> bool {
+        self != GameState::Ongoing
+    }
+
+    /// Game result when `side` is the side to move.
+    pub fn result(self, side: Color) -> GameResult {
+        match self {
+            GameState::Ongoing => GameResult::Ongoing,
+            GameState::Checkmate if side == Color::White => GameResult::BlackWins,
+            GameState::Checkmate => GameResult::WhiteWins,
+            _ => GameResult::Draw,
+        }
+    }
+}
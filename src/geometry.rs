@@ -0,0 +1,128 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/geometry.rs
+
+//! Distances between square indices and coordinate parsing.
+
+use laura_core::{BitBoard, Color, Square};
+
+#[inline(always)]
+pub const fn file_distance(a: usize, b: usize) -> usize {
+    (a % 8).abs_diff(b % 8)
+}
+
+#[inline(always)]
+pub const fn rank_distance(a: usize, b: usize) -> usize {
+    (a / 8).abs_diff(b / 8)
+}
+
+/// Chebyshev distance: the number of king moves between two squares.
+#[inline(always)]
+pub const fn distance(a: usize, b: usize) -> usize {
+    let file: usize = file_distance(a, b);
+    let rank: usize = rank_distance(a, b);
+    if file > rank { file } else { rank }
+}
+
+/// File index of a file letter, `a` to `h` in either case.
+#[inline(always)]
+pub const fn file_from_char(c: char) -> Option<usize> {
+    match c.to_ascii_lowercase() {
+        'a'..='h' => Some((c.to_ascii_lowercase() as u8 - b'a') as usize),
+        _ => None,
+    }
+}
+
+/// Rank index of a rank digit, `1` to `8`.
+#[inline(always)]
+pub const fn rank_from_char(c: char) -> Option<usize> {
+    match c {
+        '1'..='8' => Some((c as u8 - b'1') as usize),
+        _ => None,
+    }
+}
+
+/// Square index of a coordinate such as `e4`.
+pub fn square_from_str(s: &str) -> Option<usize> {
+    let mut chars = s.chars();
+    let file: usize = file_from_char(chars.next()?)?;
+    let rank: usize = rank_from_char(chars.next()?)?;
+    chars.next().is_none().then_some(rank * 8 + file)
+}
+
+/// Side to move of a FEN letter, `w` or `b`.
+#[inline(always)]
+pub const fn color_from_char(c: char) -> Option<Color> {
+    match c {
+        'w' => Some(Color::White),
+        'b' => Some(Color::Black),
+        _ => None,
+    }
+}
+
+/// Every square of the board, from a1 to h8.
+pub fn squares() -> impl Iterator<Item = Square> {
+    BitBoard(u64::MAX).into_iter()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        color_from_char, distance, file_distance, file_from_char, rank_distance, rank_from_char,
+        square_from_str, squares,
+    };
+    use laura_core::{Color, Square};
+
+    #[test]
+    fn test_distances() {
+        let (a1, h8, e4, f6) = (
+            Square::A1.to_index(),
+            Square::H8.to_index(),
+            Square::E4.to_index(),
+            Square::F6.to_index(),
+        );
+        assert_eq!(distance(a1, h8), 7);
+        assert_eq!(distance(e4, f6), 2);
+        assert_eq!(file_distance(e4, f6), 1);
+        assert_eq!(rank_distance(e4, f6), 2);
+        assert_eq!(distance(e4, e4), 0);
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(file_from_char('e'), Some(4));
+        assert_eq!(file_from_char('H'), Some(7));
+        assert_eq!(file_from_char('i'), None);
+        assert_eq!(rank_from_char('1'), Some(0));
+        assert_eq!(rank_from_char('9'), None);
+        assert_eq!(square_from_str("e4"), Some(Square::E4.to_index()));
+        assert_eq!(square_from_str("h8"), Some(Square::H8.to_index()));
+        assert_eq!(square_from_str("e"), None);
+        assert_eq!(square_from_str("e44"), None);
+        assert_eq!(square_from_str("é4"), None);
+        assert_eq!(color_from_char('w'), Some(Color::White));
+        assert_eq!(color_from_char('x'), None);
+
+        let all: Vec<Square> = squares().collect();
+        assert_eq!(all.len(), 64);
+        assert_eq!(all[0], Square::A1);
+        assert_eq!(all[63], Square::H8);
+    }
+}
@@ -1,16 +1,40 @@
 #![allow(dead_code)]
+mod affinity;
+mod bitboard;
+mod book;
+mod cluster;
 mod config;
+mod cpu;
+mod display;
+mod error;
 mod evaluation;
+mod game;
+mod geometry;
+mod movelist;
 mod movepicker;
+mod options;
+mod output;
+mod perft;
+mod pns;
 mod position;
+mod profile;
 mod search;
+mod settings;
+mod signal;
+mod snapshot;
 mod sse;
+mod stack;
+mod storage;
 mod tables;
+mod telemetry;
 mod thread;
 mod timer;
 mod transposition;
 mod uci;
+mod workers;
+mod zobrist;
 
+pub use error::EngineError;
 pub use position::Position;
 pub use thread::ThreadPool;
 pub use timer::TimeManager;
@@ -0,0 +1,127 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/movelist.rs
+
+//! Slice-style helpers for the move lists produced by the generator, plus
+//! closure-friendly wrappers over the callback enumerator.
+
+use laura_core::{AllMoves, Board, Move, MoveList, enumerate_legal_moves};
+use std::ops::ControlFlow;
+
+/// Calls `visit` on every legal move of `board`, without building a list.
+#[inline(always)]
+pub fn for_each_legal_move<F: FnMut(Move)>(board: &Board, mut visit: F) {
+    enumerate_legal_moves::<AllMoves, _>(board, |mv| -> bool {
+        visit(mv);
+        true
+    });
+}
+
+/// Visits legal moves until `visit` breaks, returning the break value.
+/// Moves generated after the break are ignored even if the generator keeps
+/// going, so callers can rely on the early exit.
+pub fn enumerate_until<T, F: FnMut(Move) -> ControlFlow<T>>(
+    board: &Board,
+    mut visit: F,
+) -> Option<T> {
+    let mut result: Option<T> = None;
+    enumerate_legal_moves::<AllMoves, _>(board, |mv| -> bool {
+        if result.is_some() {
+            return false;
+        }
+        match visit(mv) {
+            ControlFlow::Continue(()) => true,
+            ControlFlow::Break(value) => {
+                result = Some(value);
+                false
+            }
+        }
+    });
+    result
+}
+
+/// Extra operations on [`MoveList`] that keep callers free of index loops.
+pub trait MoveListExt {
+    /// The generated moves as a plain slice.
+    fn as_slice(&self) -> &[Move];
+
+    /// Keeps only the moves for which `keep` returns `true`, preserving order.
+    fn retain<F: FnMut(&Move) -> bool>(&mut self, keep: F);
+}
+
+impl MoveListExt for MoveList {
+    #[inline(always)]
+    fn as_slice(&self) -> &[Move] {
+        &self[..]
+    }
+
+    fn retain<F: FnMut(&Move) -> bool>(&mut self, mut keep: F) {
+        let moves: MoveList = std::mem::take(self);
+        for mv in moves.iter() {
+            if keep(mv) {
+                self.push(*mv);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::movelist::{MoveListExt, enumerate_until, for_each_legal_move};
+    use laura_core::{Board, Move, MoveList, legal_moves};
+    use std::ops::ControlFlow;
+
+    #[test]
+    fn test_retain() {
+        let board: Board = Board::default();
+        let mut moves: MoveList = legal_moves!(&board);
+        assert_eq!(moves.as_slice().len(), 20);
+
+        let knight: Move = board.find_move("g1f3").unwrap();
+        moves.retain(|mv| mv.is_capture() || *mv == knight);
+        assert_eq!(moves.as_slice(), &[knight]);
+
+        moves.retain(|_| false);
+        assert!(moves.as_slice().is_empty());
+    }
+
+    #[test]
+    fn test_enumerate_until() {
+        let board: Board = Board::default();
+        let mut count: usize = 0;
+        for_each_legal_move(&board, |_| count += 1);
+        assert_eq!(count, 20);
+
+        let mut visited: usize = 0;
+        let third: Option<Move> = enumerate_until(&board, |mv| {
+            visited += 1;
+            if visited == 3 {
+                ControlFlow::Break(mv)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(third.is_some());
+        assert_eq!(visited, 3);
+
+        let none: Option<()> = enumerate_until(&board, |_| ControlFlow::Continue(()));
+        assert!(none.is_none());
+    }
+}
@@ -21,16 +21,27 @@
 
 //! Move picker for search.
 
+use crate::config::KILLER_SLOTS;
+use crate::evaluation::{self, pst_gain};
+use crate::position::gives_check;
 use crate::sse::SEE;
 use crate::tables::HistoryTable;
-use laura_core::{Board, Color, Move, MoveList, quiet_moves, tactical_moves};
+use laura_core::{Board, Color, Move, MoveList, PieceType, quiet_moves, tactical_moves};
+
+const RANK_2: u64 = 0x0000_0000_0000_FF00;
+const RANK_7: u64 = 0x00FF_0000_0000_0000;
+
+/// Lifts queen promotions above any capture and sinks underpromotions below them.
+const PROMOTION_BONUS: i32 = 16 * 1000;
 
 #[derive(PartialEq, PartialOrd, Clone, Copy, Debug)]
 pub enum Stage {
     TTMove,
     GenCaptures,
     GoodCaptures,
+    GenQuiets,
     Killers,
+    Counter,
     Quiets,
     BadCaptures,
     Done,
@@ -38,7 +49,8 @@ pub enum Stage {
 
 pub struct MovePicker {
     tt_move: Option<Move>,
-    killer_move: [Option<Move>; 2],
+    killer_move: [Option<Move>; KILLER_SLOTS],
+    counter_move: Option<Move>,
     killer_index: usize,
     stage: Stage,
 
@@ -48,19 +60,27 @@ pub struct MovePicker {
 
     index: usize,
     pub skip_quiets: bool,
+    /// Orders quiet checks ahead of the other quiet moves
+    pub checks_first: bool,
 }
 
 impl MovePicker {
-    pub fn new(tt_move: Option<Move>, killer_move: [Option<Move>; 2]) -> Self {
+    pub fn new(
+        tt_move: Option<Move>,
+        killer_move: [Option<Move>; KILLER_SLOTS],
+        counter_move: Option<Move>,
+    ) -> Self {
         Self {
             tt_move,
             killer_move,
+            counter_move,
             stage: Stage::TTMove,
             good_captures: MoveList::default(),
             bad_captures: MoveList::default(),
             quiets: MoveList::default(),
             index: 0,
             skip_quiets: false,
+            checks_first: false,
             killer_index: 0,
         }
     }
@@ -87,25 +107,39 @@ impl MovePicker {
                     self.index = 0;
                 }
                 Stage::GoodCaptures => {
-                    if let Some(mv) = self.next_from_list(&self.good_captures.clone()) {
+                    let skip: [Option<Move>; KILLER_SLOTS + 2] = self.already_tried();
+                    if let Some(mv) = pick(&self.good_captures, &mut self.index, &skip) {
                         return Some(mv);
                     }
-                    self.stage = if self.skip_quiets {
-                        Stage::BadCaptures
-                    } else {
-                        Stage::Killers
-                    };
+                    self.stage = Stage::GenQuiets;
                     self.index = 0;
                 }
-                Stage::Killers => {
-                    if self.index == 0 {
-                        self.quiets = quiet_moves!(position);
-                        self.score_quiets(position, history);
+                Stage::GenQuiets => {
+                    if self.skip_quiets {
+                        self.stage = Stage::BadCaptures;
+                        continue;
                     }
 
-                    for i in self.killer_index..2 {
+                    // Quiet moves are generated once and reused to validate
+                    // the killer and counter moves. Queen promotions were
+                    // already tried along with the captures.
+                    let color: Color = position.side();
+                    self.quiets.clear();
+                    for mv in quiet_moves!(position).iter() {
+                        if !is_queen_promotion(*mv, color) {
+                            self.quiets.push(*mv);
+                        }
+                    }
+                    self.score_quiets(position, history);
+                    self.stage = Stage::Killers;
+                    self.index = 0;
+                }
+                Stage::Killers => {
+                    while !self.skip_quiets && self.killer_index < KILLER_SLOTS {
+                        let killer: Option<Move> = self.killer_move[self.killer_index];
                         self.killer_index += 1;
-                        match self.killer_move[i] {
+
+                        match killer {
                             Some(killer)
                                 if Some(killer) != self.tt_move
                                     && self.quiets.contains(&killer) =>
@@ -116,11 +150,26 @@ impl MovePicker {
                         }
                     }
 
+                    self.stage = Stage::Counter;
+                }
+                Stage::Counter => {
                     self.stage = Stage::Quiets;
+                    match self.counter_move {
+                        Some(counter)
+                            if !self.skip_quiets
+                                && Some(counter) != self.tt_move
+                                && !self.killer_move.contains(&Some(counter))
+                                && self.quiets.contains(&counter) =>
+                        {
+                            return Some(counter);
+                        }
+                        _ => continue,
+                    }
                 }
                 Stage::Quiets => {
                     if !self.skip_quiets {
-                        if let Some(mv) = self.next_from_list(&self.quiets.clone()) {
+                        let skip: [Option<Move>; KILLER_SLOTS + 2] = self.already_tried();
+                        if let Some(mv) = pick(&self.quiets, &mut self.index, &skip) {
                             return Some(mv);
                         }
                     }
@@ -128,7 +177,8 @@ impl MovePicker {
                     self.index = 0;
                 }
                 Stage::BadCaptures => {
-                    if let Some(mv) = self.next_from_list(&self.bad_captures.clone()) {
+                    let skip: [Option<Move>; KILLER_SLOTS + 2] = self.already_tried();
+                    if let Some(mv) = pick(&self.bad_captures, &mut self.index, &skip) {
                         return Some(mv);
                     }
                     self.stage = Stage::Done;
@@ -139,7 +189,23 @@ impl MovePicker {
     }
 
     fn generate_and_score_captures(&mut self, position: &Board) {
-        let all_captures: MoveList = tactical_moves!(position);
+        let mut all_captures: MoveList = tactical_moves!(position);
+        let color: Color = position.side();
+
+        // Queen promotions are always tried with the captures, so they are
+        // also searched when quiet moves are skipped (e.g. in quiescence)
+        let seventh: u64 = if color == Color::White {
+            RANK_7
+        } else {
+            RANK_2
+        };
+        if position.allied_pawns().0 & seventh != 0 {
+            for mv in quiet_moves!(position).iter() {
+                if is_queen_promotion(*mv, color) && !all_captures.contains(mv) {
+                    all_captures.push(*mv);
+                }
+            }
+        }
 
         self.good_captures.clear();
         self.bad_captures.clear();
@@ -149,7 +215,9 @@ impl MovePicker {
                 continue;
             }
 
-            let is_good: bool = SEE::see(position, *mv, 0);
+            // Underpromotions are only worth a look after everything else
+            let is_good: bool = (!mv.is_promotion() || is_queen_promotion(*mv, color))
+                && SEE::see(position, *mv, 0);
 
             if is_good {
                 self.good_captures.push(*mv);
@@ -157,45 +225,112 @@ impl MovePicker {
                 self.bad_captures.push(*mv);
             }
         }
+
+        // MVV-LVA ordering inside each capture bucket
+        self.good_captures
+            .sort_unstable_by_key(|mv| -tactical_score(position, *mv));
+        self.bad_captures
+            .sort_unstable_by_key(|mv| -tactical_score(position, *mv));
     }
 
-    fn next_from_list(&mut self, list: &MoveList) -> Option<Move> {
-        while self.index < list.len() {
-            let mv: Move = list[self.index];
-            self.index += 1;
+    /// Moves already returned by an earlier stage, which must not be repeated.
+    #[inline(always)]
+    fn already_tried(&self) -> [Option<Move>; KILLER_SLOTS + 2] {
+        let mut tried: [Option<Move>; KILLER_SLOTS + 2] = [None; KILLER_SLOTS + 2];
+        tried[0] = self.tt_move;
+        tried[1..=KILLER_SLOTS].copy_from_slice(&self.killer_move);
+        tried[KILLER_SLOTS + 1] = self.counter_move;
+        tried
+    }
 
-            if Some(mv) == self.tt_move
-                || Some(mv) == self.killer_move[0]
-                || Some(mv) == self.killer_move[1]
-            {
-                continue;
+    /// Orders quiets by history, with the piece-square gain breaking ties such
+    /// as the all-zero history at the start of a search.
+    fn score_quiets(&mut self, position: &Board, history: &HistoryTable) {
+        let color: Color = position.side();
+        let checks_first: bool = self.checks_first;
+        let phase: i32 = evaluation::phase(position);
+
+        // Each key is computed once per move rather than on every comparison
+        self.quiets.sort_by_cached_key(|mv| {
+            if mv.is_promotion() {
+                (i32::MAX, 0)
+            } else if checks_first && gives_check(position, *mv) {
+                (i32::MIN, -pst_gain(position, *mv, phase))
+            } else {
+                (
+                    -history.get_score(*mv, color),
+                    -pst_gain(position, *mv, phase),
+                )
             }
+        });
+    }
+}
 
-            return Some(mv);
+/// Returns the next move of `list` not contained in `skip`, advancing `index`.
+#[inline(always)]
+fn pick(list: &MoveList, index: &mut usize, skip: &[Option<Move>]) -> Option<Move> {
+    while *index < list.len() {
+        let mv: Move = list[*index];
+        *index += 1;
+
+        if skip.contains(&Some(mv)) {
+            continue;
         }
-        None
+
+        return Some(mv);
     }
+    None
+}
 
-    fn score_quiets(&mut self, position: &Board, history: &HistoryTable) {
-        let color: Color = position.side();
+/// Whether the move promotes a pawn to a queen.
+#[inline(always)]
+fn is_queen_promotion(mv: Move, color: Color) -> bool {
+    mv.is_promotion() && mv.get_prom(color).piece_type() == PieceType::Queen
+}
 
-        self.quiets
-            .sort_unstable_by_key(|mv| -history.get_score(*mv, color));
+/// MVV-LVA score of a tactical move, with promotions placed around the captures.
+#[inline(always)]
+fn tactical_score(position: &Board, mv: Move) -> i32 {
+    let score: i32 = mvv_lva(position, mv);
+    if !mv.is_promotion() {
+        score
+    } else if is_queen_promotion(mv, position.side()) {
+        score + PROMOTION_BONUS
+    } else {
+        score - PROMOTION_BONUS
     }
 }
 
+/// Most Valuable Victim - Least Valuable Attacker score of a tactical move.
+#[inline(always)]
+fn mvv_lva(position: &Board, mv: Move) -> i32 {
+    let victim: i32 = match position.piece_on(mv.get_dest()) {
+        Some(piece) => SEE::piece_value(piece.piece_type()),
+        None if mv.is_capture() => SEE::piece_value(PieceType::Pawn),
+        None => 0,
+    };
+    let attacker: i32 = match position.piece_on(mv.get_src()) {
+        Some(piece) => SEE::piece_value(piece.piece_type()),
+        None => 0,
+    };
+
+    victim * 8 - attacker / 100
+}
+
 #[cfg(test)]
 mod test {
-    use super::MovePicker;
+    use super::{MovePicker, Stage};
+    use crate::config::KILLER_SLOTS;
+    use crate::evaluation::{self, pst_gain};
     use crate::sse::SEE;
-    use crate::tables::HistoryTable;
-    use laura_core::Board;
+    use crate::tables::{HistoryTable, KillerMoves};
+    use laura_core::{Board, Color, Move, MoveList, PieceType, legal_moves};
     use std::str::FromStr;
 
     #[test]
     fn test_good_bad_captures() {
         let board: Board = Board::from_str("4k3/4p3/8/1p1p4/r3Q3/8/8/4K3 w - - 0 1").unwrap();
-        let mut picker: MovePicker = MovePicker::new(None, [None, None]);
+        let mut picker: MovePicker = MovePicker::new(None, [None; KILLER_SLOTS], None);
         let history: HistoryTable = HistoryTable::default();
 
         while let Some(mv) = picker.next(&board, &history) {
@@ -208,7 +343,7 @@ mod test {
         let board: Board =
             Board::from_str("rnbqkb1r/pp1p1pPp/8/2p1pP2/1P1P4/7P/P1P1P3/RNBQKBNR w KQkq e6 0 1")
                 .unwrap();
-        let mut picker: MovePicker = MovePicker::new(None, [None, None]);
+        let mut picker: MovePicker = MovePicker::new(None, [None; KILLER_SLOTS], None);
 
         picker.generate_and_score_captures(&board);
 
@@ -216,13 +351,134 @@ mod test {
         for mv in picker.good_captures.iter() {
             let see: bool = SEE::see(&board, *mv, 0);
             println!("Good: {} (SEE: {})", mv, see);
-            assert_eq!(see, true);
+            assert!(see);
         }
         println!("Bad captures: {}", picker.bad_captures.len());
         for mv in picker.bad_captures.iter() {
             let see: bool = SEE::see(&board, *mv, 0);
             println!("Bad: {} (SEE: {})", mv, see);
-            assert_eq!(see, false);
+            assert!(!see);
+        }
+    }
+
+    #[test]
+    fn test_promotion_ordering() {
+        let board: Board = Board::from_str("1n2k3/P7/8/7q/8/8/8/4K2R w K - 0 1").unwrap();
+        let mut picker: MovePicker = MovePicker::new(None, [None; KILLER_SLOTS], None);
+        let history: HistoryTable = HistoryTable::default();
+
+        let mut picked: Vec<Move> = Vec::new();
+        while let Some(mv) = picker.next(&board, &history) {
+            picked.push(mv);
+        }
+
+        // Queen promotions come first, ahead of the rook capturing the queen
+        let promotes_to = |mv: &Move, piece: PieceType| {
+            mv.is_promotion() && mv.get_prom(board.side()).piece_type() == piece
+        };
+        assert!(promotes_to(&picked[0], PieceType::Queen));
+        assert!(promotes_to(&picked[1], PieceType::Queen));
+        assert_eq!(picked[2], board.find_move("h1h5").unwrap());
+
+        // Underpromotions are tried last
+        let tail: &[Move] = &picked[picked.len() - 6..];
+        assert!(tail.iter().all(|mv| mv.is_promotion()));
+        assert!(!tail.iter().any(|mv| promotes_to(mv, PieceType::Queen)));
+    }
+
+    #[test]
+    fn test_qsearch_queen_promotion() {
+        let board: Board = Board::from_str("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let mut picker: MovePicker = MovePicker::new(None, [None; KILLER_SLOTS], None);
+        picker.skip_quiets = true;
+        let history: HistoryTable = HistoryTable::default();
+
+        let first: Move = picker.next(&board, &history).unwrap();
+        assert_eq!(first, board.find_move("a7a8q").unwrap());
+        while picker.next(&board, &history).is_some() {}
+        assert_eq!(picker.stage(), Stage::Done);
+    }
+
+    #[test]
+    fn test_checks_first() {
+        let board: Board = Board::from_str("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        let history: HistoryTable = HistoryTable::default();
+        let mut picker: MovePicker = MovePicker::new(None, [None; KILLER_SLOTS], None);
+        picker.checks_first = true;
+
+        let first: Move = picker.next(&board, &history).unwrap();
+        assert_eq!(first, board.find_move("a1a8").unwrap());
+    }
+
+    #[test]
+    fn test_all_moves_once() {
+        let board: Board =
+            Board::from_str("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        let legal: MoveList = legal_moves!(&board);
+        let killer: Move = board.find_move("a2a3").unwrap();
+        let counter: Move = board.find_move("e1g1").unwrap();
+        let mut killers: [Option<Move>; KILLER_SLOTS] = [None; KILLER_SLOTS];
+        killers[0] = Some(killer);
+        let mut picker: MovePicker = MovePicker::new(None, killers, Some(counter));
+        let history: HistoryTable = HistoryTable::default();
+
+        let mut picked: Vec<Move> = Vec::new();
+        while let Some(mv) = picker.next(&board, &history) {
+            assert!(!picked.contains(&mv), "{mv} picked twice");
+            picked.push(mv);
+        }
+
+        assert_eq!(picked.len(), legal.len());
+    }
+
+    #[test]
+    fn test_killer_order() {
+        let board: Board = Board::default();
+        let history: HistoryTable = HistoryTable::default();
+        let mut stored: KillerMoves = KillerMoves::default();
+        for mv in ["a2a3", "h2h3", "b1c3"].iter().rev() {
+            stored.store(board.find_move(mv).unwrap());
+        }
+        // A repeated store moves the killer up instead of duplicating it
+        stored.store(board.find_move("a2a3").unwrap());
+
+        let killers: [Option<Move>; KILLER_SLOTS] = stored.get();
+        let mut picker: MovePicker = MovePicker::new(None, killers, None);
+        let mut picked: Vec<Move> = Vec::new();
+        while let Some(mv) = picker.next(&board, &history) {
+            picked.push(mv);
+        }
+
+        // Killers come first, in slot order, and are never repeated later
+        let expected: Vec<Move> = killers.iter().flatten().copied().collect();
+        assert_eq!(&picked[..expected.len()], &expected[..]);
+        assert_eq!(expected[0], board.find_move("a2a3").unwrap());
+        assert_eq!(picked.len(), 20);
+    }
+
+    #[test]
+    fn test_pst_ordering() {
+        let board: Board = Board::default();
+        let history: HistoryTable = HistoryTable::default();
+        let mut picker: MovePicker = MovePicker::new(None, [None; KILLER_SLOTS], None);
+
+        // Without history, quiets come in order of piece-square gain
+        let phase: i32 = evaluation::phase(&board);
+        let mut picked: Vec<Move> = Vec::new();
+        while let Some(mv) = picker.next(&board, &history) {
+            picked.push(mv);
         }
+        let gains: Vec<i32> = picked
+            .iter()
+            .map(|mv| pst_gain(&board, *mv, phase))
+            .collect();
+        assert!(gains.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        // History still comes first
+        let rim: Move = board.find_move("h2h3").unwrap();
+        history.update_cutoff(rim, 5, Color::White);
+        let mut picker: MovePicker = MovePicker::new(None, [None; KILLER_SLOTS], None);
+        assert_eq!(picker.next(&board, &history), Some(rim));
     }
 }
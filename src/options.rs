@@ -0,0 +1,322 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/options.rs
+
+//! UCI options registry.
+
+use crate::config::{DEFAULT_SIZE, MAX_PLY};
+use crate::cpu;
+use crate::output::OutputFormat;
+use crate::profile::SearchProfile;
+use std::fmt;
+
+pub const THREADS_MIN: usize = 1;
+pub const THREADS_MAX: usize = 512;
+pub const HASH_MIN: usize = 1;
+pub const HASH_MAX: usize = 1048576;
+pub const EVAL_NOISE_MAX: i32 = 100;
+pub const ENGINE_ABOUT: &str = concat!(
+    "Laura ",
+    env!("CARGO_PKG_VERSION"),
+    " by HansTibberio, GPL-3.0, https://github.com/HansTibberio/Laura"
+);
+/// Commit the binary was built from, embedded by the build script.
+pub const GIT_HASH: &str = match option_env!("LAURA_GIT_HASH") {
+    Some(hash) => hash,
+    None => "unknown",
+};
+
+#[derive(Debug, Clone, Copy)]
+pub enum OptionType {
+    Spin {
+        default: i64,
+        min: i64,
+        max: i64,
+    },
+    Check {
+        default: bool,
+    },
+    Combo {
+        default: &'static str,
+        vars: &'static [&'static str],
+    },
+    String {
+        default: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UCIOption {
+    pub name: &'static str,
+    pub option_type: OptionType,
+}
+
+impl fmt::Display for UCIOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+        match self.option_type {
+            OptionType::Spin { default, min, max } => {
+                write!(f, "spin default {default} min {min} max {max}")
+            }
+            OptionType::Check { default } => write!(f, "check default {default}"),
+            OptionType::Combo { default, vars } => {
+                write!(f, "combo default {default}")?;
+                for var in vars {
+                    write!(f, " var {var}")?;
+                }
+                Ok(())
+            }
+            OptionType::String { default } => write!(f, "string default {default}"),
+        }
+    }
+}
+
+/// Every option announced on the `uci` handshake.
+pub const OPTIONS: &[UCIOption] = &[
+    UCIOption {
+        name: "UCI_EngineAbout",
+        option_type: OptionType::String {
+            default: ENGINE_ABOUT,
+        },
+    },
+    UCIOption {
+        name: "Hash",
+        option_type: OptionType::Spin {
+            default: DEFAULT_SIZE as i64,
+            min: HASH_MIN as i64,
+            max: HASH_MAX as i64,
+        },
+    },
+    UCIOption {
+        name: "SharedHash",
+        option_type: OptionType::String { default: "<empty>" },
+    },
+    UCIOption {
+        name: "Threads",
+        option_type: OptionType::Spin {
+            default: THREADS_MIN as i64,
+            min: THREADS_MIN as i64,
+            max: THREADS_MAX as i64,
+        },
+    },
+    UCIOption {
+        name: "MaxDepth",
+        option_type: OptionType::Spin {
+            default: MAX_PLY as i64,
+            min: 1,
+            max: MAX_PLY as i64,
+        },
+    },
+    UCIOption {
+        name: "MaxNodes",
+        option_type: OptionType::Spin {
+            default: 0,
+            min: 0,
+            max: i64::MAX,
+        },
+    },
+    UCIOption {
+        name: "TacticalMode",
+        option_type: OptionType::Check { default: false },
+    },
+    UCIOption {
+        name: "TieBreak",
+        option_type: OptionType::Check { default: false },
+    },
+    UCIOption {
+        name: "TieBreakSeed",
+        option_type: OptionType::Spin {
+            default: 0,
+            min: 0,
+            max: i64::MAX,
+        },
+    },
+    UCIOption {
+        name: "EvalNoise",
+        option_type: OptionType::Spin {
+            default: 0,
+            min: 0,
+            max: EVAL_NOISE_MAX as i64,
+        },
+    },
+    UCIOption {
+        name: "EvalNoisePlies",
+        option_type: OptionType::Spin {
+            default: 16,
+            min: 1,
+            max: MAX_PLY as i64,
+        },
+    },
+    UCIOption {
+        name: "ThreadAffinity",
+        option_type: OptionType::Check { default: false },
+    },
+    UCIOption {
+        name: "SharedHistory",
+        option_type: OptionType::Check { default: false },
+    },
+    UCIOption {
+        name: "Diagnostics",
+        option_type: OptionType::Check { default: false },
+    },
+    UCIOption {
+        name: "UCI_ShowRefutations",
+        option_type: OptionType::Check { default: false },
+    },
+    UCIOption {
+        name: "UCI_ShowCurrLine",
+        option_type: OptionType::Check { default: false },
+    },
+    UCIOption {
+        name: "ProofNumberSearch",
+        option_type: OptionType::Check { default: true },
+    },
+    UCIOption {
+        name: "Profile",
+        option_type: OptionType::Combo {
+            default: "blitz",
+            vars: &["bullet", "blitz", "analysis"],
+        },
+    },
+    UCIOption {
+        name: "OutputFormat",
+        option_type: OptionType::Combo {
+            default: "uci",
+            vars: &["uci", "json", "both"],
+        },
+    },
+];
+
+/// Values of the options that shape a search. The UCI loop owns them and
+/// hands a shared, read-only copy to the thread pool on every `go`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineOptions {
+    /// Caps iterative deepening, independently of `go depth`
+    pub max_depth: usize,
+    /// Caps the nodes of every search, independently of the time control
+    pub max_nodes: Option<u64>,
+    /// Orders quiet checks first and extends every check and recapture
+    pub tactical: bool,
+    /// Draws at random among root moves tied with the best one
+    pub tie_break: bool,
+    pub tie_seed: u64,
+    /// Centipawns of evaluation noise for self-play, 0 when disabled
+    pub eval_noise: i32,
+    /// Game plies from the start position during which the noise applies
+    pub noise_plies: usize,
+    pub diagnostics: bool,
+    pub show_refutations: bool,
+    pub show_currline: bool,
+    /// Proof-number search (instead of alpha-beta) for `go mate N`
+    pub proof_number_search: bool,
+    pub profile: SearchProfile,
+    pub output: OutputFormat,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_PLY,
+            max_nodes: None,
+            tactical: false,
+            tie_break: false,
+            tie_seed: 0,
+            eval_noise: 0,
+            noise_plies: 16,
+            diagnostics: false,
+            show_refutations: false,
+            show_currline: false,
+            proof_number_search: true,
+            profile: SearchProfile::default(),
+            output: OutputFormat::default(),
+        }
+    }
+}
+
+/// Describes the compile-time CPU features, build profile and running cpu.
+pub fn build_info() -> String {
+    let features: [(&str, bool); 4] = [
+        ("bmi2", cfg!(feature = "bmi2")),
+        ("pext", cfg!(target_feature = "bmi2")),
+        ("popcnt", cfg!(target_feature = "popcnt")),
+        ("debug", cfg!(debug_assertions)),
+    ];
+
+    let enabled: Vec<&str> = features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+
+    format!(
+        "{} {} (cpu: {})",
+        std::env::consts::ARCH,
+        if enabled.is_empty() {
+            "generic".to_string()
+        } else {
+            enabled.join(" ")
+        },
+        if cpu::fast_pext() {
+            "fast-pext"
+        } else if cpu::has_bmi2() {
+            "slow-pext"
+        } else {
+            "no-bmi2"
+        }
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OPTIONS, OptionType, UCIOption};
+
+    #[test]
+    fn test_option_display() {
+        let option: UCIOption = UCIOption {
+            name: "Threads",
+            option_type: OptionType::Spin {
+                default: 1,
+                min: 1,
+                max: 512,
+            },
+        };
+        assert_eq!(
+            option.to_string(),
+            "option name Threads type spin default 1 min 1 max 512"
+        );
+
+        for option in OPTIONS {
+            println!("{option}");
+        }
+    }
+
+    #[test]
+    fn test_engine_about() {
+        let about: &UCIOption = OPTIONS
+            .iter()
+            .find(|option| option.name == "UCI_EngineAbout")
+            .unwrap();
+        assert!(
+            about
+                .to_string()
+                .starts_with("option name UCI_EngineAbout type string default Laura ")
+        );
+    }
+}
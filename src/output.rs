@@ -0,0 +1,179 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/output.rs
+
+//! Search output formats.
+
+use crate::{
+    config::{MATE, MAX_MATE},
+    search::PrincipalVariation,
+};
+use laura_core::Move;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Uci,
+    Json,
+    Both,
+}
+
+impl FromStr for OutputFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "uci" => Ok(Self::Uci),
+            "json" => Ok(Self::Json),
+            "both" => Ok(Self::Both),
+            _ => Err(()),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn uci(&self) -> bool {
+        matches!(self, Self::Uci | Self::Both)
+    }
+
+    pub fn json(&self) -> bool {
+        matches!(self, Self::Json | Self::Both)
+    }
+}
+
+/// Score as reported to the GUI: centipawns or moves to mate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Cp(i32),
+    Mate(i32),
+}
+
+impl Score {
+    pub fn new(score: i32) -> Self {
+        if score.abs() >= MAX_MATE {
+            let mate_in: i32 = (MATE - score.abs() + 1) / 2;
+            Self::Mate(if score > 0 { mate_in } else { -mate_in })
+        } else {
+            Self::Cp(score)
+        }
+    }
+}
+
+/// Data of a single search `info` report.
+#[derive(Debug, Clone, Copy)]
+pub struct SearchInfo<'a> {
+    pub depth: usize,
+    pub seldepth: usize,
+    pub score: Score,
+    pub time: u128,
+    pub nodes: u64,
+    pub nps: u64,
+    pub hashfull: usize,
+    pub pv: &'a PrincipalVariation,
+}
+
+impl SearchInfo<'_> {
+    pub fn to_uci(self) -> String {
+        let score: String = match self.score {
+            Score::Cp(cp) => format!("cp {cp}"),
+            Score::Mate(mate) => format!("mate {mate}"),
+        };
+        format!(
+            "info depth {} seldepth {} score {} time {} nodes {} nps {} hashfull {} {}",
+            self.depth,
+            self.seldepth,
+            score,
+            self.time,
+            self.nodes,
+            self.nps,
+            self.hashfull,
+            self.pv
+        )
+    }
+
+    pub fn to_json(self) -> String {
+        let score: String = match self.score {
+            Score::Cp(cp) => format!("{{\"cp\":{cp}}}"),
+            Score::Mate(mate) => format!("{{\"mate\":{mate}}}"),
+        };
+        let pv: Vec<String> = self
+            .pv
+            .as_slice()
+            .iter()
+            .map(|mv| format!("\"{mv}\""))
+            .collect();
+        format!(
+            "{{\"type\":\"info\",\"depth\":{},\"seldepth\":{},\"score\":{},\"time\":{},\"nodes\":{},\"nps\":{},\"hashfull\":{},\"pv\":[{}]}}",
+            self.depth,
+            self.seldepth,
+            score,
+            self.time,
+            self.nodes,
+            self.nps,
+            self.hashfull,
+            pv.join(",")
+        )
+    }
+}
+
+pub fn bestmove_json(mv: Move) -> String {
+    format!("{{\"type\":\"bestmove\",\"move\":\"{mv}\"}}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OutputFormat, Score, SearchInfo};
+    use crate::{config::MATE, search::PrincipalVariation};
+    use laura_core::Board;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_json_info() {
+        let board: Board = Board::default();
+        let mut pv: PrincipalVariation = PrincipalVariation::default();
+        pv.push(board.find_move("e2e4").unwrap());
+        pv.push(board.make_move(pv.moves[0]).find_move("e7e5").unwrap());
+
+        let info: SearchInfo<'_> = SearchInfo {
+            depth: 5,
+            seldepth: 7,
+            score: Score::new(MATE - 3),
+            time: 12,
+            nodes: 3400,
+            nps: 283_333,
+            hashfull: 1,
+            pv: &pv,
+        };
+
+        assert_eq!(
+            info.to_json(),
+            "{\"type\":\"info\",\"depth\":5,\"seldepth\":7,\"score\":{\"mate\":2},\"time\":12,\"nodes\":3400,\"nps\":283333,\"hashfull\":1,\"pv\":[\"e2e4\",\"e7e5\"]}"
+        );
+        assert_eq!(
+            info.to_uci(),
+            "info depth 5 seldepth 7 score mate 2 time 12 nodes 3400 nps 283333 hashfull 1 pv e2e4 e7e5 "
+        );
+
+        assert_eq!(Score::new(-25), Score::Cp(-25));
+        assert_eq!(OutputFormat::from_str("JSON"), Ok(OutputFormat::Json));
+        assert!(OutputFormat::from_str("xml").is_err());
+    }
+}
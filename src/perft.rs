@@ -0,0 +1,131 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/perft.rs
+
+//! Hash table for perft, keyed by position and remaining depth.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use laura_core::Board;
+
+use crate::{
+    movelist::for_each_legal_move,
+    position::inner_perft,
+    transposition::{AllocationError, allocate_zeroed},
+};
+
+/// A node count stored lockless: `check` holds the key xor the data, so a
+/// torn write from another thread simply reads back as a miss.
+#[derive(Debug, Default)]
+struct PerftEntry {
+    check: AtomicU64,
+    data: AtomicU64,
+}
+
+/// Perft counts keyed by (zobrist, depth). Counts are exact, so the table
+/// never changes the result, only how long it takes.
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+}
+
+impl PerftTable {
+    /// Allocates a zeroed table of `megabytes`.
+    pub fn new(megabytes: usize) -> Result<Self, AllocationError> {
+        // SAFETY: an entry of zeroed atomics is an empty entry
+        let entries: Vec<PerftEntry> = unsafe { allocate_zeroed::<PerftEntry>(megabytes)? };
+        Ok(Self { entries })
+    }
+
+    #[inline(always)]
+    fn entry(&self, key: u64) -> &PerftEntry {
+        let index: usize = ((u128::from(key) * self.entries.len() as u128) >> 64) as usize;
+        &self.entries[index]
+    }
+
+    /// Node count for `key` at `depth`, if stored.
+    pub fn probe(&self, key: u64, depth: u8) -> Option<u64> {
+        let entry: &PerftEntry = self.entry(key);
+        let data: u64 = entry.data.load(Ordering::Relaxed);
+        let check: u64 = entry.check.load(Ordering::Relaxed);
+        (data != 0 && check ^ data == key && data as u8 == depth).then_some(data >> 8)
+    }
+
+    /// Stores the node count of `key` at `depth`, always replacing.
+    pub fn store(&self, key: u64, depth: u8, nodes: u64) {
+        let data: u64 = (nodes << 8) | u64::from(depth);
+        let entry: &PerftEntry = self.entry(key);
+        entry.check.store(key ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+}
+
+/// Perft below the root, reusing the counts of transposed subtrees.
+pub fn hashed_perft(board: &Board, depth: u8, table: &PerftTable) -> u64 {
+    if depth <= 2 {
+        return inner_perft::<false>(board, depth);
+    }
+
+    let key: u64 = board.zobrist.0;
+    if let Some(nodes) = table.probe(key, depth) {
+        return nodes;
+    }
+
+    let mut total: u64 = 0;
+    for_each_legal_move(board, |mv| {
+        total += hashed_perft(&board.make_move(mv), depth - 1, table);
+    });
+
+    table.store(key, depth, total);
+    total
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use laura_core::Board;
+
+    use crate::{
+        perft::{PerftTable, hashed_perft},
+        position::inner_perft,
+    };
+
+    #[test]
+    fn test_hashed_perft() {
+        let table: PerftTable = PerftTable::new(1).unwrap();
+        assert_eq!(table.probe(0x1234, 3), None);
+        table.store(0x1234, 3, 8_902);
+        assert_eq!(table.probe(0x1234, 3), Some(8_902));
+        assert_eq!(table.probe(0x1234, 4), None);
+
+        let board: Board = Board::default();
+        assert_eq!(hashed_perft(&board, 4, &table), 197_281);
+        assert_eq!(hashed_perft(&board, 4, &table), 197_281);
+
+        let kiwipete: Board =
+            Board::from_str("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(hashed_perft(&kiwipete, 3, &table), 97_862);
+        assert_eq!(
+            hashed_perft(&kiwipete, 3, &table),
+            inner_perft::<false>(&kiwipete, 3)
+        );
+    }
+}
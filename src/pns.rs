@@ -0,0 +1,256 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/pns.rs
+
+//! Proof-number search for `go mate N`.
+//!
+//! The tree is kept in a flat arena and only stores moves; the board of the
+//! most-proving node is rebuilt by replaying them from the root.
+
+use crate::config::PN_INFINITY;
+use laura_core::{Board, Move, MoveList, legal_moves};
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    mv: Move,
+    first_child: u32,
+    children: u32,
+    proof: u32,
+    disproof: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PnsResult {
+    /// Forced mate, with the mating line.
+    Proven(Vec<Move>),
+    /// No mate within the requested number of moves.
+    Disproven,
+    /// Stopped or out of nodes before a proof was found.
+    Unknown,
+}
+
+#[derive(Debug)]
+pub struct ProofNumberSearch {
+    nodes: Vec<Node>,
+    root: Board,
+    plies: usize,
+}
+
+impl ProofNumberSearch {
+    /// Mate search in `mate` moves of the side to move.
+    pub fn new(root: Board, mate: usize) -> Self {
+        Self {
+            nodes: Vec::new(),
+            root,
+            plies: (2 * mate).saturating_sub(1),
+        }
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn search(&mut self, stop: &AtomicBool, max_nodes: usize) -> PnsResult {
+        let (proof, disproof) = self.evaluate(&self.root, 0);
+        self.nodes.clear();
+        self.nodes.push(Node {
+            mv: Move::null(),
+            first_child: 0,
+            children: 0,
+            proof,
+            disproof,
+        });
+
+        let mut iterations: usize = 0;
+        while self.nodes[0].proof != 0 && self.nodes[0].disproof != 0 {
+            iterations += 1;
+            if self.nodes.len() >= max_nodes
+                || (iterations & 1023 == 0 && stop.load(Ordering::Relaxed))
+            {
+                return PnsResult::Unknown;
+            }
+
+            // Select the most-proving node
+            let mut path: Vec<usize> = vec![0];
+            let mut board: Board = self.root;
+            let mut index: usize = 0;
+            while self.nodes[index].children > 0 {
+                index = self.most_proving_child(index, path.len() - 1);
+                board = board.make_move(self.nodes[index].mv);
+                path.push(index);
+            }
+
+            self.expand(index, &board, path.len() - 1);
+
+            // Back up proof and disproof numbers to the root
+            for (ply, &index) in path.iter().enumerate().rev() {
+                self.update(index, ply);
+            }
+        }
+
+        if self.nodes[0].proof == 0 {
+            PnsResult::Proven(self.principal_variation())
+        } else {
+            PnsResult::Disproven
+        }
+    }
+
+    /// Initial numbers of a node `ply` plies below the root.
+    /// The attacker moves at even plies (OR nodes), the defender at odd ones.
+    fn evaluate(&self, board: &Board, ply: usize) -> (u32, u32) {
+        let or_node: bool = ply % 2 == 0;
+        let replies: MoveList = legal_moves!(board);
+
+        if replies.is_empty() {
+            let mated: bool = board.checkers.count_bits() != 0;
+            if mated && !or_node {
+                (0, PN_INFINITY)
+            } else {
+                (PN_INFINITY, 0)
+            }
+        } else if ply >= self.plies {
+            (PN_INFINITY, 0)
+        } else if or_node {
+            (1, 1)
+        } else {
+            // Every defence has to be refuted
+            (replies.len() as u32, 1)
+        }
+    }
+
+    fn expand(&mut self, index: usize, board: &Board, ply: usize) {
+        let moves: MoveList = legal_moves!(board);
+        let first_child: usize = self.nodes.len();
+
+        for &mv in moves.iter() {
+            let (proof, disproof) = self.evaluate(&board.make_move(mv), ply + 1);
+            self.nodes.push(Node {
+                mv,
+                first_child: 0,
+                children: 0,
+                proof,
+                disproof,
+            });
+        }
+
+        self.nodes[index].first_child = first_child as u32;
+        self.nodes[index].children = moves.len() as u32;
+    }
+
+    fn update(&mut self, index: usize, ply: usize) {
+        let node: Node = self.nodes[index];
+        if node.children == 0 {
+            return;
+        }
+
+        let first: usize = node.first_child as usize;
+        let children: &[Node] = &self.nodes[first..first + node.children as usize];
+        let min_proof: u32 = children.iter().map(|c| c.proof).min().unwrap();
+        let min_disproof: u32 = children.iter().map(|c| c.disproof).min().unwrap();
+        let sum_proof: u32 = children
+            .iter()
+            .fold(0u32, |sum, c| sum.saturating_add(c.proof))
+            .min(PN_INFINITY);
+        let sum_disproof: u32 = children
+            .iter()
+            .fold(0u32, |sum, c| sum.saturating_add(c.disproof))
+            .min(PN_INFINITY);
+
+        let (proof, disproof) = if ply % 2 == 0 {
+            (min_proof, sum_disproof)
+        } else {
+            (sum_proof, min_disproof)
+        };
+        self.nodes[index].proof = proof;
+        self.nodes[index].disproof = disproof;
+    }
+
+    fn most_proving_child(&self, index: usize, ply: usize) -> usize {
+        let node: Node = self.nodes[index];
+        let range: Range<usize> =
+            node.first_child as usize..(node.first_child + node.children) as usize;
+        if ply % 2 == 0 {
+            range.min_by_key(|&child| self.nodes[child].proof).unwrap()
+        } else {
+            range
+                .min_by_key(|&child| self.nodes[child].disproof)
+                .unwrap()
+        }
+    }
+
+    /// Follows proven children from the root down to the mate.
+    fn principal_variation(&self) -> Vec<Move> {
+        let mut pv: Vec<Move> = Vec::new();
+        let mut index: usize = 0;
+        while self.nodes[index].children > 0 {
+            let node: Node = self.nodes[index];
+            let mut range: Range<usize> =
+                node.first_child as usize..(node.first_child + node.children) as usize;
+            index = match range.find(|&child| self.nodes[child].proof == 0) {
+                Some(child) => child,
+                None => break,
+            };
+            pv.push(self.nodes[index].mv);
+        }
+        pv
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PnsResult, ProofNumberSearch};
+    use laura_core::Board;
+    use std::{str::FromStr, sync::atomic::AtomicBool};
+
+    #[test]
+    fn test_forced_mates() {
+        let stop: AtomicBool = AtomicBool::new(false);
+        let board: Board = Board::from_str("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+
+        let mut pns: ProofNumberSearch = ProofNumberSearch::new(board, 1);
+        match pns.search(&stop, 1_000_000) {
+            PnsResult::Proven(pv) => assert_eq!(pv[0].to_string(), "a1a8"),
+            result => panic!("expected a mate, got {result:?}"),
+        }
+
+        // 1. Ra6 bxa6 2. b7#
+        let board: Board = Board::from_str("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1").unwrap();
+        let mut pns: ProofNumberSearch = ProofNumberSearch::new(board, 2);
+        match pns.search(&stop, 1_000_000) {
+            PnsResult::Proven(pv) => {
+                assert_eq!(pv[0].to_string(), "a1a6");
+                assert_eq!(pv.len(), 3);
+            }
+            result => panic!("expected a mate, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_no_mate() {
+        let stop: AtomicBool = AtomicBool::new(false);
+        let board: Board = Board::default();
+        let mut pns: ProofNumberSearch = ProofNumberSearch::new(board, 1);
+        assert_eq!(pns.search(&stop, 1_000_000), PnsResult::Disproven);
+    }
+}
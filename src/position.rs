@@ -21,10 +21,24 @@
 
 //! Position management.
 
-use crate::{evaluation, thread::Thread};
-use laura_core::{AllMoves, BitBoard, Board, Color, Move, enumerate_legal_moves};
+use crate::{
+    bitboard::Shift,
+    config::KEY_HISTORY_SIZE,
+    evaluation::{self, BLACK, Value, WHITE},
+    game::GameState,
+    geometry::{file_distance, rank_distance},
+    movelist::{enumerate_until, for_each_legal_move},
+    stack::StackEntry,
+    thread::Thread,
+    zobrist,
+};
+use laura_core::{
+    BitBoard, Board, Color, Move, MoveList, MoveType, PieceType, Square, get_bishop_attacks,
+    get_knight_attacks, get_rook_attacks, legal_moves,
+};
 use std::{
     mem::replace,
+    ops::ControlFlow,
     time::{Duration, Instant},
 };
 
@@ -40,18 +54,15 @@ fn perft<const DIV: bool>(board: &Board, depth: u8) -> u64 {
 }
 
 #[allow(unused_assignments)]
-fn inner_perft<const DIV: bool>(board: &Board, depth: u8) -> u64 {
+pub fn inner_perft<const DIV: bool>(board: &Board, depth: u8) -> u64 {
     let mut total: u64 = 0;
 
     if !DIV && depth <= 1 {
-        enumerate_legal_moves::<AllMoves, _>(board, |_| -> bool {
-            total += 1;
-            true
-        });
+        for_each_legal_move(board, |_| total += 1);
         return total;
     }
 
-    enumerate_legal_moves::<AllMoves, _>(board, |mv| -> bool {
+    for_each_legal_move(board, |mv| {
         let mut nodes: u64 = 0;
         if DIV && depth == 1 {
             nodes = 1;
@@ -69,17 +80,318 @@ fn inner_perft<const DIV: bool>(board: &Board, depth: u8) -> u64 {
         if DIV && nodes > 0 {
             println!("{} -> {}", mv, nodes);
         }
-
-        true
     });
 
     total
 }
 
+/// Cheap consistency check of a move's squares against its flag, without
+/// looking at any position. Rejects corrupted moves (e.g. from a loaded or
+/// shared hash table) before the real legality check.
+pub fn is_pseudo_sane(mv: Move) -> bool {
+    let src: usize = mv.get_src().to_index();
+    let dest: usize = mv.get_dest().to_index();
+    if src == dest {
+        return false;
+    }
+
+    let (src_rank, dest_rank): (usize, usize) = (src / 8, dest / 8);
+    let files: usize = file_distance(src, dest);
+    let forward: bool = (src_rank == 6 && dest_rank == 7) || (src_rank == 1 && dest_rank == 0);
+
+    if mv.is_promotion() {
+        // One step to the last rank, diagonal only when capturing
+        return forward && files == mv.is_capture() as usize;
+    }
+
+    match mv.get_type() {
+        MoveType::EnPassant => {
+            let from_fifth: bool =
+                (src_rank == 4 && dest_rank == 5) || (src_rank == 3 && dest_rank == 2);
+            from_fifth && files == 1
+        }
+        MoveType::KingCastle | MoveType::QueenCastle => {
+            let home: bool = src == 4 || src == 60;
+            home && rank_distance(src, dest) == 0 && files == 2
+        }
+        _ => true,
+    }
+}
+
+/// Whether `mv` gives check, resolved from attack sets without making the move.
+/// Castling, en passant and promotions fall back to playing it.
+pub fn gives_check(board: &Board, mv: Move) -> bool {
+    let move_type: MoveType = mv.get_type();
+    if mv.is_promotion()
+        || matches!(
+            move_type,
+            MoveType::EnPassant | MoveType::KingCastle | MoveType::QueenCastle
+        )
+    {
+        return board.make_move(mv).checkers.count_bits() != 0;
+    }
+
+    let us: usize = board.side() as usize;
+    let src: Square = mv.get_src();
+    let dest: Square = mv.get_dest();
+    let src_bb: BitBoard = BitBoard(1 << src.to_index());
+    let dest_bb: BitBoard = BitBoard(1 << dest.to_index());
+    let king_bb: BitBoard = board.pieces_bitboard[PieceType::KING] & board.sides_bitboard[us ^ 1];
+    let king: Square = king_bb.to_square().unwrap();
+    let occupied: BitBoard = (board.combined_bitboard() & !src_bb) | dest_bb;
+
+    // Direct check from the moved piece
+    let direct: BitBoard = match board.piece_on(src).map(|piece| piece.piece_type()) {
+        Some(PieceType::Pawn) => {
+            if us == WHITE {
+                dest_bb.pawn_attacks::<WHITE>()
+            } else {
+                dest_bb.pawn_attacks::<BLACK>()
+            }
+        }
+        Some(PieceType::Knight) => get_knight_attacks(dest),
+        Some(PieceType::Bishop) => get_bishop_attacks(dest, occupied),
+        Some(PieceType::Rook) => get_rook_attacks(dest, occupied),
+        Some(PieceType::Queen) => {
+            get_bishop_attacks(dest, occupied) | get_rook_attacks(dest, occupied)
+        }
+        Some(PieceType::King) | None => BitBoard::EMPTY,
+    };
+    if !(direct & king_bb).is_empty() {
+        return true;
+    }
+
+    // Discovered check from a slider unmasked by the moved piece
+    let ours: BitBoard = board.sides_bitboard[us] & !src_bb;
+    let queens: BitBoard = board.pieces_bitboard[PieceType::QUEEN];
+    let diagonals: BitBoard = (board.pieces_bitboard[PieceType::BISHOP] | queens) & ours;
+    let lines: BitBoard = (board.pieces_bitboard[PieceType::ROOK] | queens) & ours;
+
+    !((get_bishop_attacks(king, occupied) & diagonals) | (get_rook_attacks(king, occupied) & lines))
+        .is_empty()
+}
+
+/// Legality of a single move without generating the move list, for moves of
+/// knights, bishops, rooks, queens and non-castling kings. Returns `None` for
+/// pawn moves, castling, en passant and promotions, which need the generator.
+pub fn quick_legality(board: &Board, mv: Move) -> Option<bool> {
+    let us: usize = board.side() as usize;
+    let src: Square = mv.get_src();
+    let dest: Square = mv.get_dest();
+    let src_bb: BitBoard = BitBoard(1 << src.to_index());
+    let dest_bb: BitBoard = BitBoard(1 << dest.to_index());
+
+    // Only our own pieces move, and never onto our own pieces
+    if (board.sides_bitboard[us] & src_bb).is_empty()
+        || !(board.sides_bitboard[us] & dest_bb).is_empty()
+    {
+        return Some(false);
+    }
+
+    let piece: PieceType = board.piece_on(src)?.piece_type();
+    if piece == PieceType::Pawn
+        || mv.is_promotion()
+        || matches!(
+            mv.get_type(),
+            MoveType::EnPassant | MoveType::KingCastle | MoveType::QueenCastle
+        )
+    {
+        return None;
+    }
+
+    // The destination must be reachable, with a flag matching its occupancy
+    let occupied: BitBoard = board.combined_bitboard();
+    let reach: BitBoard = match piece {
+        PieceType::Knight => get_knight_attacks(src),
+        PieceType::Bishop => get_bishop_attacks(src, occupied),
+        PieceType::Rook => get_rook_attacks(src, occupied),
+        PieceType::Queen => get_bishop_attacks(src, occupied) | get_rook_attacks(src, occupied),
+        _ => src_bb.king_attacks(),
+    };
+    let capture: bool = !(board.sides_bitboard[us ^ 1] & dest_bb).is_empty();
+    let flag_matches: bool = if capture {
+        mv.is_capture()
+    } else {
+        matches!(mv.get_type(), MoveType::Quiet)
+    };
+    if (reach & dest_bb).is_empty() || !flag_matches {
+        return Some(false);
+    }
+
+    // Our king must not be attacked once the move is made
+    let king_bb: BitBoard = if piece == PieceType::King {
+        dest_bb
+    } else {
+        board.pieces_bitboard[PieceType::KING] & board.sides_bitboard[us]
+    };
+    let king: Square = king_bb.to_square().unwrap();
+    let after: BitBoard = (occupied & !src_bb) | dest_bb;
+    let enemies: BitBoard = board.sides_bitboard[us ^ 1] & !dest_bb;
+    let pieces: &[BitBoard] = &board.pieces_bitboard;
+    let pawn_attackers: BitBoard = if us == WHITE {
+        king_bb.pawn_attacks::<WHITE>()
+    } else {
+        king_bb.pawn_attacks::<BLACK>()
+    };
+    let attackers: BitBoard = (get_knight_attacks(king) & pieces[PieceType::KNIGHT])
+        | (get_bishop_attacks(king, after)
+            & (pieces[PieceType::BISHOP] | pieces[PieceType::QUEEN]))
+        | (get_rook_attacks(king, after) & (pieces[PieceType::ROOK] | pieces[PieceType::QUEEN]))
+        | (king_bb.king_attacks() & pieces[PieceType::KING])
+        | (pawn_attackers & pieces[PieceType::PAWN]);
+
+    Some((attackers & enemies).is_empty())
+}
+
+/// Reasons a parsed board cannot arise in a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IllegalPosition {
+    /// The side does not have exactly one king
+    KingCount(usize),
+    AdjacentKings,
+    /// The side that just moved left its own king in check
+    OpponentInCheck,
+    PawnOnBackRank,
+    /// More pawns than a side starts with
+    TooManyPawns(usize),
+    /// More pieces than promotions could explain
+    TooManyPieces(usize),
+}
+
+impl std::fmt::Display for IllegalPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const SIDES: [&str; 2] = ["white", "black"];
+        match self {
+            Self::KingCount(side) => write!(f, "{} must have exactly one king", SIDES[*side]),
+            Self::AdjacentKings => write!(f, "the kings are adjacent"),
+            Self::OpponentInCheck => write!(f, "the side not to move is in check"),
+            Self::PawnOnBackRank => write!(f, "pawns on the first or last rank"),
+            Self::TooManyPawns(side) => write!(f, "{} has more than 8 pawns", SIDES[*side]),
+            Self::TooManyPieces(side) => {
+                write!(f, "{} has more pieces than promotions allow", SIDES[*side])
+            }
+        }
+    }
+}
+
+/// Rejects boards no game can reach and that the search cannot handle, such as
+/// a missing king or a capturable one.
+pub fn validate(board: &Board) -> Result<(), IllegalPosition> {
+    const BACK_RANKS: BitBoard = BitBoard(0xFF00_0000_0000_00FF);
+    let pieces: &[BitBoard] = &board.pieces_bitboard;
+    let count = |piece: usize, side: usize| -> u32 {
+        (pieces[piece] & board.sides_bitboard[side]).count_bits()
+    };
+
+    for side in [WHITE, BLACK] {
+        if count(PieceType::KING, side) != 1 {
+            return Err(IllegalPosition::KingCount(side));
+        }
+
+        let pawns: u32 = count(PieceType::PAWN, side);
+        if pawns > 8 {
+            return Err(IllegalPosition::TooManyPawns(side));
+        }
+
+        // Every piece beyond the initial set needs a promoted pawn
+        let promoted: u32 = count(PieceType::KNIGHT, side).saturating_sub(2)
+            + count(PieceType::BISHOP, side).saturating_sub(2)
+            + count(PieceType::ROOK, side).saturating_sub(2)
+            + count(PieceType::QUEEN, side).saturating_sub(1);
+        if pawns + promoted > 8 {
+            return Err(IllegalPosition::TooManyPieces(side));
+        }
+    }
+
+    if !(pieces[PieceType::PAWN] & BACK_RANKS).is_empty() {
+        return Err(IllegalPosition::PawnOnBackRank);
+    }
+
+    let us: usize = board.side() as usize;
+    let king_of =
+        |side: usize| -> BitBoard { pieces[PieceType::KING] & board.sides_bitboard[side] };
+    if !(king_of(us).king_attacks() & king_of(us ^ 1)).is_empty() {
+        return Err(IllegalPosition::AdjacentKings);
+    }
+
+    // The king of the side not to move must not be capturable
+    let king_bb: BitBoard = king_of(us ^ 1);
+    let king: Square = king_bb.to_square().unwrap();
+    let occupied: BitBoard = board.combined_bitboard();
+    let pawn_attackers: BitBoard = if us == WHITE {
+        king_bb.pawn_attacks::<BLACK>()
+    } else {
+        king_bb.pawn_attacks::<WHITE>()
+    };
+    let attackers: BitBoard = (get_knight_attacks(king) & pieces[PieceType::KNIGHT])
+        | (get_bishop_attacks(king, occupied)
+            & (pieces[PieceType::BISHOP] | pieces[PieceType::QUEEN]))
+        | (get_rook_attacks(king, occupied) & (pieces[PieceType::ROOK] | pieces[PieceType::QUEEN]))
+        | (pawn_attackers & pieces[PieceType::PAWN]);
+    if !(attackers & board.sides_bitboard[us]).is_empty() {
+        return Err(IllegalPosition::OpponentInCheck);
+    }
+
+    Ok(())
+}
+
+/// Ring buffer with the zobrist keys of the previous positions.
+/// Only the plies since the last irreversible move matter for repetitions,
+/// so older keys can safely be overwritten.
+#[derive(Debug, Clone)]
+pub struct KeyHistory {
+    keys: [u64; KEY_HISTORY_SIZE],
+    len: usize,
+}
+
+impl Default for KeyHistory {
+    fn default() -> Self {
+        Self {
+            keys: [0; KEY_HISTORY_SIZE],
+            len: 0,
+        }
+    }
+}
+
+impl KeyHistory {
+    #[inline(always)]
+    pub fn push(&mut self, key: u64) {
+        self.keys[self.len % KEY_HISTORY_SIZE] = key;
+        self.len += 1;
+    }
+
+    #[inline(always)]
+    pub fn pop(&mut self) {
+        self.len -= 1;
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Key pushed `index` pushes ago, counting from 1.
+    #[inline(always)]
+    pub fn back(&self, index: usize) -> u64 {
+        self.keys[(self.len - index) % KEY_HISTORY_SIZE]
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Position {
     board: Board,
     game: Vec<Board>,
+    // Zobrist keys of the previous positions, for repetition detection
+    keys: KeyHistory,
+    // Number of keys before the search root
+    root: usize,
+    // Incremental material and piece-square scores, one per board
+    psqt: Vec<Value>,
 }
 
 impl Position {
@@ -93,16 +405,53 @@ impl Position {
         self.board.zobrist.0
     }
 
+    /// Key of the position after `mv`, without making the move.
+    #[inline(always)]
+    pub fn key_after(&self, mv: Move) -> u64 {
+        zobrist::key_after(&self.board, mv)
+    }
+
     #[inline(always)]
     pub fn set_board(&mut self, board: Board) {
-        self.board = board
+        self.board = board;
+        self.psqt = vec![evaluation::psqt(&board)];
     }
 
     #[inline(always)]
     pub fn set_game(&mut self, history: Vec<Board>) {
+        self.keys = KeyHistory::default();
+        for board in history.iter() {
+            self.keys.push(board.zobrist.0);
+        }
+        self.root = self.keys.len();
         self.game = history;
     }
 
+    /// Marks the current position as the search root.
+    #[inline(always)]
+    pub fn set_root(&mut self) {
+        self.root = self.keys.len();
+    }
+
+    /// Lightweight copy for search threads: the current board and only the keys
+    /// still relevant for repetitions, without the game history boards.
+    pub fn search_view(&self) -> Position {
+        let relevant: usize = (self.board.fifty_move as usize)
+            .min(self.keys.len())
+            .min(KEY_HISTORY_SIZE);
+        let mut keys: KeyHistory = KeyHistory::default();
+        for index in (1..=relevant).rev() {
+            keys.push(self.keys.back(index));
+        }
+        Position {
+            board: self.board,
+            game: Vec::new(),
+            root: keys.len(),
+            keys,
+            psqt: vec![self.psqt()],
+        }
+    }
+
     pub fn perft(&self, depth: u8) -> u64 {
         let total_nodes: u64 = perft::<false>(&self.board, depth);
         total_nodes
@@ -113,11 +462,59 @@ impl Position {
         total_nodes
     }
 
+    /// Plays a move on the board, keeping the history needed to undo it.
     #[inline(always)]
-    pub fn push_move(&mut self, mv: Move, thread: &mut Thread) {
+    pub fn do_move(&mut self, mv: Move) {
         let new: Board = self.board.make_move(mv);
+        debug_assert_eq!(
+            new.zobrist.0,
+            zobrist::recompute_key(&new),
+            "key drift after {mv}"
+        );
+        let psqt: Value = self.psqt();
         let old: Board = replace(&mut self.board, new);
+        self.psqt
+            .push(psqt + evaluation::psqt_delta(&old, &self.board));
+        self.keys.push(old.zobrist.0);
         self.game.push(old);
+    }
+
+    /// Passes the turn to the opponent.
+    #[inline(always)]
+    pub fn do_null_move(&mut self) {
+        let new: Board = self.board.null_move();
+        debug_assert_eq!(
+            new.zobrist.0,
+            zobrist::recompute_key(&new),
+            "key drift after null"
+        );
+        let psqt: Value = self.psqt();
+        let old: Board = replace(&mut self.board, new);
+        self.psqt.push(psqt);
+        self.keys.push(old.zobrist.0);
+        self.game.push(old);
+    }
+
+    /// Takes back the last move or null move.
+    #[inline(always)]
+    pub fn undo_move(&mut self) {
+        let old: Board = self.game.pop().unwrap();
+        self.keys.pop();
+        self.psqt.pop();
+        self.board = old;
+    }
+
+    /// `do_move` plus the search bookkeeping of the thread.
+    #[inline(always)]
+    pub fn push_move(&mut self, mv: Move, thread: &mut Thread) {
+        let entry: &mut StackEntry = &mut thread.stack[thread.ply];
+        entry.current = self
+            .board
+            .piece_on(mv.get_src())
+            .map(|piece| (mv, piece.piece_type()));
+        entry.null_move = false;
+
+        self.do_move(mv);
 
         thread.ply += 1;
         thread.nodes += 1;
@@ -125,9 +522,11 @@ impl Position {
 
     #[inline(always)]
     pub fn push_null(&mut self, thread: &mut Thread) {
-        let new: Board = self.board.null_move();
-        let old: Board = replace(&mut self.board, new);
-        self.game.push(old);
+        let entry: &mut StackEntry = &mut thread.stack[thread.ply];
+        entry.current = None;
+        entry.null_move = true;
+
+        self.do_null_move();
 
         thread.ply += 1;
         thread.nodes += 1;
@@ -135,15 +534,46 @@ impl Position {
 
     #[inline(always)]
     pub fn pop_move(&mut self, thread: &mut Thread) {
-        let old: Board = self.game.pop().unwrap();
-        self.board = old;
+        self.undo_move();
 
         thread.ply -= 1;
     }
 
     #[inline(always)]
     pub fn evaluate(&self) -> i32 {
-        evaluation::evaluate(&self.board)
+        let psqt: Value = self.psqt();
+        debug_assert_eq!(psqt, evaluation::psqt(&self.board));
+        evaluation::evaluate_with(&self.board, psqt)
+    }
+
+    /// Current material and piece-square score, recomputed only if the
+    /// board was never set through `set_board`.
+    #[inline(always)]
+    fn psqt(&self) -> Value {
+        match self.psqt.last() {
+            Some(psqt) => *psqt,
+            None => evaluation::psqt(&self.board),
+        }
+    }
+
+    #[inline(always)]
+    pub fn phase(&self) -> i32 {
+        evaluation::phase(&self.board)
+    }
+
+    #[inline(always)]
+    pub fn non_pawn_material(&self) -> i32 {
+        if self.white() {
+            evaluation::non_pawn_material::<WHITE>(&self.board)
+        } else {
+            evaluation::non_pawn_material::<BLACK>(&self.board)
+        }
+    }
+
+    /// Whether the side to move has a rook or queen en prise.
+    #[inline(always)]
+    pub fn hanging_major(&self) -> bool {
+        evaluation::hanging_major(&self.board)
     }
 
     #[inline(always)]
@@ -151,11 +581,69 @@ impl Position {
         self.game.len()
     }
 
+    /// Number of legal moves, counted without building a move list.
+    pub fn count_legal_moves(&self) -> usize {
+        let mut count: usize = 0;
+        for_each_legal_move(&self.board, |_| count += 1);
+        count
+    }
+
+    /// First legal move found by the generator, stopping right after it.
+    pub fn first_legal_move(&self) -> Option<Move> {
+        enumerate_until(&self.board, ControlFlow::Break)
+    }
+
+    #[inline(always)]
+    pub fn has_legal_moves(&self) -> bool {
+        self.first_legal_move().is_some()
+    }
+
+    /// Plies since the last capture or pawn move.
+    #[inline(always)]
+    pub fn halfmove_clock(&self) -> u16 {
+        self.board.fifty_move as u16
+    }
+
+    /// Checkmate and stalemate take precedence over the fifty-move rule,
+    /// which in turn precedes insufficient material.
+    pub fn game_state(&self) -> GameState {
+        if !self.has_legal_moves() {
+            if self.in_check() {
+                GameState::Checkmate
+            } else {
+                GameState::Stalemate
+            }
+        } else if self.board.fifty_move >= 100 {
+            GameState::DrawByFifty
+        } else if self.is_insufficient_material() {
+            GameState::DrawByInsufficientMaterial
+        } else {
+            GameState::Ongoing
+        }
+    }
+
     #[inline(always)]
     pub fn in_check(&self) -> bool {
         self.board.checkers.count_bits() != 0
     }
 
+    /// Whether `mv` gives check from the current position.
+    #[inline(always)]
+    pub fn gives_check(&self, mv: Move) -> bool {
+        gives_check(&self.board, mv)
+    }
+
+    /// Whether `mv` delivers checkmate. Quiet non-checking moves are rejected
+    /// before any move generation on the child position.
+    pub fn gives_checkmate(&self, mv: Move) -> bool {
+        if !self.gives_check(mv) {
+            return false;
+        }
+        let child: Board = self.board.make_move(mv);
+        let replies: MoveList = legal_moves!(&child);
+        replies.is_empty()
+    }
+
     #[inline(always)]
     pub fn white(&self) -> bool {
         self.board.side == Color::White
@@ -163,8 +651,7 @@ impl Position {
 
     #[inline(always)]
     pub fn possible_zugzwang(&self) -> bool {
-        (self.board.allied_presence() ^ self.board.allied_king() ^ self.board.allied_pawns())
-            .is_empty()
+        self.non_pawn_material() == 0
     }
 
     #[inline(always)]
@@ -174,12 +661,17 @@ impl Position {
             return true;
         }
 
-        // Threefold repetition
+        // Repetitions: twofold inside the search tree, threefold before the root
         let key: u64 = self.key();
         let mut count: i32 = 1;
-        let max_back: usize = self.board.fifty_move as usize;
-        for board in self.game.iter().rev().take(max_back).skip(1).step_by(2) {
-            if board.zobrist.0 == key {
+        let max_back: usize = (self.board.fifty_move as usize)
+            .min(self.keys.len())
+            .min(KEY_HISTORY_SIZE);
+        for back in (2..=max_back).step_by(2) {
+            if self.keys.back(back) == key {
+                if self.keys.len() - back >= self.root {
+                    return true;
+                }
                 count += 1;
                 if count >= 3 {
                     return true;
@@ -196,7 +688,7 @@ impl Position {
     }
 
     #[inline(always)]
-    fn is_insufficient_material(&self) -> bool {
+    pub fn is_insufficient_material(&self) -> bool {
         let board: &Board = &self.board;
         let pawns: BitBoard = board.pawns();
         let rooks: BitBoard = board.rooks();
@@ -245,3 +737,270 @@ impl Position {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Position, is_pseudo_sane, quick_legality};
+    use crate::{
+        evaluation,
+        game::{GameResult, GameState},
+    };
+    use laura_core::{BitBoard, Board, Color, Move, MoveList, MoveType, Square, legal_moves};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_gives_check() {
+        let fens: [&str; 5] = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "4k3/8/8/8/8/8/4B3/4R1K1 w - - 0 1",
+        ];
+        for fen in fens {
+            let mut position: Position = Position::default();
+            position.set_board(Board::from_str(fen).unwrap());
+            let moves: MoveList = legal_moves!(&position.board());
+            for mv in moves.iter() {
+                let expected: bool = position.board().make_move(*mv).checkers.count_bits() != 0;
+                assert_eq!(position.gives_check(*mv), expected, "{fen} {mv}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_gives_checkmate() {
+        let mut position: Position = Position::default();
+        position.set_board(Board::from_str("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap());
+        let mate: Move = position.board().find_move("a1a8").unwrap();
+        let check: Move = position.board().find_move("a1a7").unwrap();
+        assert!(position.gives_checkmate(mate));
+        assert!(!position.gives_checkmate(check));
+    }
+
+    #[test]
+    fn test_quick_legality() {
+        let fens: [&str; 4] = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ];
+        for fen in fens {
+            let board: Board = Board::from_str(fen).unwrap();
+            let moves: MoveList = legal_moves!(&board);
+
+            // Every legal move is accepted or left to the generator
+            for mv in moves.iter() {
+                assert_ne!(quick_legality(&board, *mv), Some(false), "{fen} {mv}");
+            }
+
+            // Any quiet move between two squares is judged like the generator would
+            for src in 0..64 {
+                for dest in 0..64 {
+                    let mv: Move = Move::new(
+                        BitBoard(1 << src).to_square().unwrap(),
+                        BitBoard(1 << dest).to_square().unwrap(),
+                        MoveType::Quiet,
+                    );
+                    if let Some(legal) = quick_legality(&board, mv) {
+                        assert_eq!(legal, moves.contains(&mv), "{fen} {mv}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pseudo_sane() {
+        let fens: [&str; 3] = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ];
+        for fen in fens {
+            let board: Board = Board::from_str(fen).unwrap();
+            for mv in legal_moves!(&board).iter() {
+                assert!(is_pseudo_sane(*mv), "{fen} {mv}");
+            }
+        }
+
+        let board: Board = Board::from_str(fens[1]).unwrap();
+        let promotion: Move = board.find_move("g2g1q").unwrap();
+        let capture: Move = board.find_move("g2f1q").unwrap();
+        let corrupted: [Move; 4] = [
+            Move::new(Square::E2, Square::E2, MoveType::Quiet),
+            Move::new(Square::G2, Square::G3, promotion.get_type()),
+            Move::new(Square::G2, Square::F1, promotion.get_type()),
+            Move::new(Square::G2, Square::G1, capture.get_type()),
+        ];
+        for mv in corrupted {
+            assert!(!is_pseudo_sane(mv), "{mv}");
+        }
+        assert!(!is_pseudo_sane(Move::new(
+            Square::E1,
+            Square::H4,
+            MoveType::KingCastle
+        )));
+    }
+
+    #[test]
+    fn test_count_legal_moves() {
+        let fens: [&str; 4] = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+            "R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+        ];
+        for fen in fens {
+            let mut position: Position = Position::default();
+            position.set_board(Board::from_str(fen).unwrap());
+            let moves: MoveList = legal_moves!(&position.board());
+            assert_eq!(position.count_legal_moves(), moves.len(), "{fen}");
+            assert_eq!(position.first_legal_move().is_some(), !moves.is_empty());
+            if let Some(mv) = position.first_legal_move() {
+                assert!(moves.contains(&mv));
+            }
+        }
+    }
+
+    #[test]
+    fn test_game_state() {
+        let states: [(&str, GameState); 5] = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                GameState::Ongoing,
+            ),
+            ("6k1/5ppp/8/8/8/8/8/R5K1 b - - 0 1", GameState::Ongoing),
+            ("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", GameState::Checkmate),
+            ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", GameState::Stalemate),
+            (
+                "6k1/5ppp/8/8/8/8/8/R5K1 w - - 100 80",
+                GameState::DrawByFifty,
+            ),
+        ];
+        for (fen, state) in states {
+            let mut position: Position = Position::default();
+            position.set_board(Board::from_str(fen).unwrap());
+            assert_eq!(position.game_state(), state, "{fen}");
+        }
+
+        // A mate delivered on the hundredth quiet ply beats the fifty-move rule
+        let mut position: Position = Position::default();
+        position.set_board(Board::from_str("6k1/5ppp/8/8/8/8/8/R5K1 w - - 99 80").unwrap());
+        let mv: Move = position.board.find_move("a1a8").unwrap();
+        position.do_move(mv);
+        assert_eq!(position.halfmove_clock(), 100);
+        assert_eq!(position.game_state(), GameState::Checkmate);
+
+        let mut position: Position = Position::default();
+        position.set_board(Board::from_str("8/8/4k3/8/8/3NK3/8/8 w - - 7 60").unwrap());
+        assert_eq!(position.game_state(), GameState::DrawByInsufficientMaterial);
+        assert_eq!(position.halfmove_clock(), 7);
+        assert_eq!(
+            GameState::Checkmate.result(Color::Black),
+            GameResult::WhiteWins
+        );
+        assert_eq!(GameState::Stalemate.result(Color::White), GameResult::Draw);
+    }
+
+    #[test]
+    fn test_search_view() {
+        let mut board: Board = Board::default();
+        let mut history: Vec<Board> = Vec::new();
+        for uci in ["g1f3", "b8c6", "f3g1", "c6b8", "g1f3", "b8c6", "f3g1"] {
+            let mv: Move = board.find_move(uci).unwrap();
+            history.push(board);
+            board = board.make_move(mv);
+        }
+
+        let mut position: Position = Position::default();
+        position.set_board(board);
+        position.set_game(history);
+
+        let view: Position = position.search_view();
+        assert!(view.game.is_empty());
+        assert!(view.keys.len() <= board.fifty_move as usize);
+        assert!(!view.is_draw());
+
+        // ...c6b8 repeats the start position a third time in both
+        let mv: Move = board.find_move("c6b8").unwrap();
+        let (mut full, mut view) = (position.clone(), view);
+        for p in [&mut full, &mut view] {
+            p.do_move(mv);
+        }
+        assert!(full.is_draw());
+        assert!(view.is_draw());
+    }
+
+    #[test]
+    fn test_twofold_in_tree() {
+        let mut board: Board = Board::default();
+        let mut history: Vec<Board> = Vec::new();
+        for uci in ["g1f3", "g8f6", "f3g1"] {
+            let mv: Move = board.find_move(uci).unwrap();
+            history.push(board);
+            board = board.make_move(mv);
+        }
+
+        // Before the root a single repetition is not a draw...
+        let mut position: Position = Position::default();
+        position.set_board(history[2]);
+        position.set_game(history[..2].to_vec());
+        let mut game: Position = position.clone();
+        for uci in ["f3g1", "f6g8"] {
+            let mv: Move = game.board.find_move(uci).unwrap();
+            game.do_move(mv);
+        }
+        assert!(!game.is_draw());
+
+        // ...but the same repetition reached inside the search tree is
+        let mut tree: Position = Position::default();
+        tree.set_board(Board::default().make_move(Board::default().find_move("g1f3").unwrap()));
+        tree.set_game(vec![Board::default()]);
+        tree.set_root();
+        for uci in ["g8f6", "f3g1", "f6g8", "g1f3"] {
+            let mv: Move = tree.board.find_move(uci).unwrap();
+            tree.do_move(mv);
+        }
+        assert!(tree.is_draw());
+    }
+
+    #[test]
+    fn test_do_undo_move() {
+        let mut position: Position = Position::default();
+        position.set_board(
+            Board::from_str("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap(),
+        );
+        let start: Position = position.clone();
+
+        position.do_null_move();
+        for uci in ["h3g2", "e5f7", "g2h1q"] {
+            let mv: Move = position.board.find_move(uci).unwrap();
+            position.do_move(mv);
+        }
+        assert_eq!(position.ply(), 4);
+        assert_eq!(position.psqt(), evaluation::psqt(&position.board));
+
+        for _ in 0..4 {
+            position.undo_move();
+        }
+        assert_eq!(position.key(), start.key());
+        assert_eq!(position.psqt(), start.psqt());
+        assert_eq!(position.ply(), 0);
+    }
+
+    #[test]
+    fn test_do_move_from_default() {
+        // No board was set, so the psqt stack starts empty
+        let mut position: Position = Position::default();
+        for uci in ["e2e4", "e7e5", "g1f3"] {
+            let mv: Move = position.board.find_move(uci).unwrap();
+            position.do_move(mv);
+            assert_eq!(position.psqt(), evaluation::psqt(&position.board));
+        }
+        position.do_null_move();
+        assert_eq!(position.psqt(), evaluation::psqt(&position.board));
+    }
+}
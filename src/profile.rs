@@ -0,0 +1,120 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/profile.rs
+
+//! Search parameter profiles for different use cases.
+
+use crate::config::ASPIRATION_MARGIN;
+use std::str::FromStr;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SearchProfile {
+    Bullet,
+    #[default]
+    Blitz,
+    Analysis,
+}
+
+impl FromStr for SearchProfile {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bullet" => Ok(Self::Bullet),
+            "blitz" => Ok(Self::Blitz),
+            "analysis" => Ok(Self::Analysis),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Search and time parameters selected by a [`SearchProfile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchParams {
+    pub aspiration_margin: i32,
+    /// Percentage by which the aspiration window grows after each fail
+    pub aspiration_growth: i32,
+    pub rfp_margin: i32,
+    pub futility_margin: i32,
+    pub lmp_moves: usize,
+    /// Percentage applied to the soft time limit
+    pub time_scale: u64,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        SearchProfile::default().params()
+    }
+}
+
+impl SearchProfile {
+    pub fn params(&self) -> SearchParams {
+        match self {
+            // Aggressive pruning and a shorter soft limit for very fast games
+            SearchProfile::Bullet => SearchParams {
+                aspiration_margin: ASPIRATION_MARGIN + 5,
+                aspiration_growth: 175,
+                rfp_margin: 90,
+                futility_margin: 70,
+                lmp_moves: 7,
+                time_scale: 90,
+            },
+            SearchProfile::Blitz => SearchParams {
+                aspiration_margin: ASPIRATION_MARGIN,
+                aspiration_growth: 150,
+                rfp_margin: 100,
+                futility_margin: 80,
+                lmp_moves: 9,
+                time_scale: 100,
+            },
+            // Conservative pruning and tighter windows for deep analysis
+            SearchProfile::Analysis => SearchParams {
+                aspiration_margin: ASPIRATION_MARGIN - 10,
+                aspiration_growth: 130,
+                rfp_margin: 120,
+                futility_margin: 100,
+                lmp_moves: 12,
+                time_scale: 100,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SearchParams, SearchProfile};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_profiles() {
+        assert_eq!(SearchParams::default(), SearchProfile::Blitz.params());
+        assert_eq!(
+            SearchProfile::from_str("Analysis"),
+            Ok(SearchProfile::Analysis)
+        );
+        assert!(SearchProfile::from_str("classical").is_err());
+
+        let bullet: SearchParams = SearchProfile::Bullet.params();
+        let analysis: SearchParams = SearchProfile::Analysis.params();
+        assert!(bullet.lmp_moves < analysis.lmp_moves);
+        assert!(bullet.rfp_margin < analysis.rfp_margin);
+        assert!(analysis.aspiration_growth > 100);
+    }
+}
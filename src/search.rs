@@ -23,16 +23,21 @@
 
 use crate::{
     config::{
-        ASPIRATION_DEPTH_THRESHOLD, ASPIRATION_MARGIN, INFINITY, LMR_TABLE, MATE, MAX_DELTA,
-        MAX_MATE, MAX_PLY,
+        ASPIRATION_DEPTH_BONUS, ASPIRATION_DEPTH_THRESHOLD, ASPIRATION_MAX_REDUCTION,
+        ASPIRATION_SCORE_DIVISOR, CURRLINE_DELAY, CURRLINE_LENGTH, HISTORY_PRUNING_DEPTH,
+        HISTORY_PRUNING_MARGIN, INFINITY, INSTABILITY_MARGIN, INSTABILITY_TIME_SCALE, KILLER_SLOTS,
+        LMP_DEPTH, LMR_TABLE, MATE, MAX_DELTA, MAX_MATE, MAX_PLY, PANIC_DEPTH, QS_THREAT_MARGIN,
+        QUIET_SEE_DEPTH, QUIET_SEE_MARGIN, RAZOR_BASE_MARGIN, RAZOR_DEPTH, RAZOR_DEPTH_MARGIN,
     },
     movepicker::MovePicker,
+    output::{Score, SearchInfo},
     position::Position,
+    sse::SEE,
     thread::Thread,
     transposition::{BoundType, EntryHit, TranspositionTable},
 };
-use laura_core::Move;
-use std::fmt;
+use laura_core::{Board, Move, MoveList, PieceType, legal_moves};
+use std::{fmt, time::Duration};
 
 pub trait ThreadType {
     const MAIN: bool;
@@ -47,6 +52,43 @@ impl ThreadType for WorkerThread {
     const MAIN: bool = false;
 }
 
+pub trait NodeType {
+    const ROOT: bool;
+    const PV: bool;
+    const CUT: bool;
+    /// Expected type of the first child (and of the null move child)
+    type FirstChild: NodeType;
+}
+pub struct RootNode;
+pub struct PvNode;
+pub struct CutNode;
+pub struct AllNode;
+
+impl NodeType for RootNode {
+    const ROOT: bool = true;
+    const PV: bool = true;
+    const CUT: bool = false;
+    type FirstChild = PvNode;
+}
+impl NodeType for PvNode {
+    const ROOT: bool = false;
+    const PV: bool = true;
+    const CUT: bool = false;
+    type FirstChild = PvNode;
+}
+impl NodeType for CutNode {
+    const ROOT: bool = false;
+    const PV: bool = false;
+    const CUT: bool = true;
+    type FirstChild = AllNode;
+}
+impl NodeType for AllNode {
+    const ROOT: bool = false;
+    const PV: bool = false;
+    const CUT: bool = false;
+    type FirstChild = CutNode;
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PrincipalVariation {
     pub moves: [Move; MAX_PLY],
@@ -101,10 +143,11 @@ impl PrincipalVariation {
 
     #[inline(always)]
     pub fn push_line(&mut self, mv: Move, old: &PrincipalVariation) {
+        let len: usize = old.len.min(MAX_PLY - 1);
         self.clear();
         self.push(mv);
-        self.len = old.len + 1;
-        self.moves[1..=old.len].copy_from_slice(old.as_slice());
+        self.len = len + 1;
+        self.moves[1..=len].copy_from_slice(&old.as_slice()[..len]);
     }
 }
 
@@ -113,12 +156,21 @@ impl Position {
     where
         T: ThreadType,
     {
+        self.set_root();
         let start_depth: usize = (thread.id & 0b111) + 1;
-        let max_depth: usize = thread
+        let mut max_depth: usize = thread
             .time_manager
             .time_control()
             .depth()
-            .unwrap_or(MAX_PLY);
+            .unwrap_or(MAX_PLY)
+            .min(thread.max_depth);
+
+        // Under extreme time pressure only a shallow search is affordable
+        if thread.time_manager.panic() {
+            max_depth = max_depth.min(PANIC_DEPTH);
+        }
+
+        let mut iteration_start: u64 = 0;
 
         // Main Iterative Deepening Loop
         for depth in start_depth..=max_depth {
@@ -126,17 +178,42 @@ impl Position {
                 break;
             }
 
+            // Selective depth is reported per iteration
+            thread.seldepth = 0;
             let score: i32 = self.aspiration_window(thread, ttable, depth);
 
             if thread.time_manager.stopped() {
                 break;
             }
 
+            // An unstable score between iterations buys extra time
+            if T::MAIN && depth > start_depth && (score - thread.score).abs() > INSTABILITY_MARGIN {
+                thread.time_manager.scale_soft(INSTABILITY_TIME_SCALE);
+            }
+
             thread.score = score;
             thread.depth += 1;
 
             if T::MAIN {
                 uci_printer(thread, ttable);
+                if thread.show_refutations && thread.output.uci() {
+                    refutation_printer(&self.board(), thread, ttable);
+                }
+
+                if thread.telemetry.enabled {
+                    let nodes: u64 = thread.time_manager.nodes();
+                    let ebf: Option<f64> = thread
+                        .telemetry
+                        .update_ebf(nodes.saturating_sub(iteration_start));
+                    iteration_start = nodes;
+                    println!(
+                        "info string depth {} timetodepth {} ebf {} ttrejected {}",
+                        thread.depth,
+                        thread.time_manager.elapsed().as_millis(),
+                        ebf.map_or("-".to_string(), |ebf| format!("{ebf:.2}")),
+                        thread.telemetry.rejected_tt_moves
+                    );
+                }
             }
         }
 
@@ -152,7 +229,11 @@ impl Position {
         depth: usize,
     ) -> i32 {
         let mut root_pv: PrincipalVariation = PrincipalVariation::default();
-        let mut delta: i32 = ASPIRATION_MARGIN;
+
+        // Shallow iterations and large scores are less stable, so they start wider
+        let mut delta: i32 = thread.params.aspiration_margin
+            + thread.score.abs() / ASPIRATION_SCORE_DIVISOR
+            + ASPIRATION_DEPTH_BONUS / depth as i32;
 
         let (mut alpha, mut beta) = if depth >= ASPIRATION_DEPTH_THRESHOLD {
             (
@@ -163,33 +244,51 @@ impl Position {
             (-INFINITY, INFINITY)
         };
 
+        // Repeated fails on the same side are re-searched with a reduced depth
+        let mut reduction: usize = 0;
+        let mut last_fail_high: Option<bool> = None;
+
         loop {
-            let score: i32 =
-                self.alphabeta::<true>(thread, ttable, depth, alpha, beta, &mut root_pv, true);
+            let score: i32 = self.alphabeta::<RootNode>(
+                thread,
+                ttable,
+                depth - reduction,
+                alpha,
+                beta,
+                &mut root_pv,
+            );
 
             if thread.time_manager.stopped() {
                 return -INFINITY;
             }
 
+            let fail_high: bool = score >= beta;
             match score {
                 s if s <= alpha => {
-                    // Fail-low, expand window down
-                    beta = (alpha + beta) / 2; // Midpoint collapse of beta
-                    alpha = (-INFINITY).max(alpha - delta);
+                    // Fail-low, only the lower bound is widened
+                    alpha = (-INFINITY).max(s - delta);
                 }
                 s if s >= beta => {
-                    // Fail-high, expand window up
-                    beta = (INFINITY).min(beta + delta);
+                    // Fail-high, only the upper bound is widened
+                    beta = INFINITY.min(s + delta);
                 }
                 _ => {
                     // Successful
                     thread.principal_variation = root_pv;
+                    thread.root_ties = thread.ties.clone();
                     thread.completed = depth;
                     return score;
                 }
             }
 
-            delta += delta / 2;
+            reduction = if last_fail_high == Some(fail_high) {
+                (reduction + 1).min(ASPIRATION_MAX_REDUCTION).min(depth - 1)
+            } else {
+                0
+            };
+            last_fail_high = Some(fail_high);
+
+            delta = delta * thread.params.aspiration_growth / 100;
             if delta >= MAX_DELTA {
                 alpha = -INFINITY;
                 beta = INFINITY;
@@ -198,8 +297,8 @@ impl Position {
     }
 
     // Alpha-Beta with Fail-Soft
-    #[allow(non_upper_case_globals, clippy::too_many_arguments)]
-    fn alphabeta<const RootNode: bool>(
+    #[allow(clippy::too_many_arguments)]
+    fn alphabeta<N: NodeType>(
         &mut self,
         thread: &mut Thread,
         ttable: &TranspositionTable,
@@ -207,13 +306,12 @@ impl Position {
         mut alpha: i32,
         mut beta: i32,
         node_pv: &mut PrincipalVariation,
-        do_null: bool,
     ) -> i32 {
         let mut temp_pv: PrincipalVariation = PrincipalVariation::default();
         let child_pv: &mut PrincipalVariation = &mut temp_pv;
         node_pv.clear();
 
-        let is_pv: bool = beta - alpha > 1;
+        let is_pv: bool = N::PV;
 
         // Hard Limit Time Control
         if thread.time_manager.stop_hard(thread.nodes) {
@@ -221,30 +319,32 @@ impl Position {
         }
 
         // Update thread selective depth
-        thread.seldepth = if RootNode {
-            0
-        } else {
-            thread.seldepth.max(thread.ply)
-        };
+        thread.seldepth = thread.seldepth.max(thread.ply);
 
-        // 1. Check Extension
-        // Extends the search depth when the side to move is in check
+        // Ply limit reached, return the static evaluation
         let in_check: bool = self.in_check();
-        if in_check && depth < MAX_PLY {
-            depth += 1;
+        if thread.ply >= MAX_PLY - 1 {
+            return if in_check { 0 } else { self.evaluate() };
+        }
+
+        // 1. Check Extension
+        // Checks are extended selectively by the parent (see 9.1), but a position
+        // in check is never handed over to the quiescence search.
+        if in_check && depth == 0 {
+            depth = 1;
         }
 
         // 2. Quiescence Search
         // Switches to quiescence search at leaf nodes to avoid evaluating
         // tactically unstable positions caused by captures or checks.
-        if depth == 0 || thread.ply >= MAX_PLY {
+        if depth == 0 {
             return self.quiescence(thread, ttable, alpha, beta, child_pv);
         }
 
         // Limit the depth
         depth = depth.min(MAX_PLY - 1);
 
-        if !RootNode {
+        if !N::ROOT {
             // 3. Mate Distance Pruning.
             // Narrows the search window based on the maximum achievable mate
             // distance from the current ply, avoiding redundant mate searches.
@@ -277,37 +377,64 @@ impl Position {
                 }
             }
 
-            if let Some(mv) = entry.legal_move(&self.board()) {
-                tt_move = Some(mv);
+            // A stored move that is not legal here comes from a key collision or a
+            // corrupted entry, and is dropped rather than searched
+            match entry.legal_move(&self.board()) {
+                Some(mv) => tt_move = Some(mv),
+                None if !entry.mv.is_null() => thread.telemetry.rejected_tt_moves += 1,
+                None => {}
             }
         }
 
-        let static_eval: i32 = if in_check { -INFINITY } else { self.evaluate() };
+        // Without a hash move, the root follows the previous principal variation
+        if N::ROOT && tt_move.is_none() {
+            tt_move = thread.root_hint;
+        }
+
+        // Reuse the static evaluation stored in the TT when available
+        let static_eval: i32 = match tt_entry {
+            _ if in_check => -INFINITY,
+            Some(entry) if entry.evaluation != -INFINITY => entry.evaluation,
+            _ => thread.noisy(self.evaluate(), self.key()),
+        };
+        thread.stack[thread.ply].static_eval = static_eval;
 
         // 5. Forward static pruning techniques
         if !in_check && !is_pv {
             // 5.1. Reverse Futility Pruning
             // Skips shallow nodes when the static evaluation already exceeds beta
             // by a safe margin, assuming the position is unlikely to fail low.
-            let rfp_margin: i32 = 100 * depth as i32;
+            let rfp_margin: i32 = thread.params.rfp_margin * depth as i32;
             if depth <= 8 && static_eval >= beta + rfp_margin {
                 return static_eval;
             }
 
-            // 5.2. Null Move Pruning
+            // 5.2. Razoring
+            // Drops into quiescence search at shallow nodes whose static evaluation
+            // is far below alpha, returning early if the fail-low is confirmed.
+            if depth <= RAZOR_DEPTH
+                && static_eval + RAZOR_BASE_MARGIN + RAZOR_DEPTH_MARGIN * (depth as i32) < alpha
+            {
+                let razor_score: i32 = self.quiescence(thread, ttable, alpha, alpha + 1, child_pv);
+                if razor_score <= alpha {
+                    return razor_score;
+                }
+            }
+
+            // 5.3. Null Move Pruning
             // Performs a reduced-depth null move search to detect positions
             // where the side to move can still exceed beta after passing.
-            if depth > 3 && !self.possible_zugzwang() && do_null {
+            let after_null: bool = thread.ply > 0 && thread.stack[thread.ply - 1].null_move;
+            if depth > 3 && !self.possible_zugzwang() && !after_null {
                 let r: usize = (4 + depth / 4).min(depth);
                 self.push_null(thread);
-                let null_score = -self.alphabeta::<false>(
+                let null_score = -self.alphabeta::<N::FirstChild>(
                     thread,
                     ttable,
                     depth - r,
                     -beta,
                     -beta + 1,
                     child_pv,
-                    false,
                 );
                 self.pop_move(thread);
 
@@ -320,7 +447,7 @@ impl Position {
         // 6. Iternal Iterative Reduction
         // Reduces the search depth in positions without a transposition table hit,
         // trading depth for faster move ordering and lower search overhead.
-        if !RootNode && depth >= 4 && tt_entry.is_none() && !in_check {
+        if !N::ROOT && depth >= 4 && tt_entry.is_none() && !in_check {
             depth -= 1;
         }
 
@@ -329,13 +456,30 @@ impl Position {
         let mut best_score: i32 = -INFINITY;
         let mut move_count: usize = 0;
 
-        let killers: [Option<Move>; 2] = thread.killer.get(thread.ply);
-        let mut picker: MovePicker = MovePicker::new(tt_move, killers);
+        // Forced moves: a check with a single legal evasion
+        let single_reply: bool = in_check && !N::ROOT && self.count_legal_moves() == 1;
+
+        // Killers two plies ahead only come from sibling subtrees of this node
+        thread.stack.clear_killers(thread.ply + 2);
+        let killers: [Option<Move>; KILLER_SLOTS] = thread.stack.killers(thread.ply);
+        let previous: Option<(Move, PieceType)> = thread.previous_move();
+        let counter: Option<Move> =
+            previous.and_then(|(prev_mv, prev_piece)| thread.counter.get(prev_mv, prev_piece));
+        let mut picker: MovePicker = MovePicker::new(tt_move, killers, counter);
+        picker.checks_first = thread.tactical;
+        if N::ROOT {
+            thread.ties.clear();
+        }
 
         let mut quiets_tried: Vec<Move> = Vec::with_capacity(32);
 
         // 7. Main Alpha-Beta Loop
         while let Some(mv) = picker.next(&self.board(), &thread.history) {
+            // Root moves left out by `searchmoves` or `excludemoves`
+            if N::ROOT && thread.excluded.contains(&mv) {
+                continue;
+            }
+
             move_count += 1;
             let mut score: i32;
             let is_quiet: bool = mv.is_quiet();
@@ -347,91 +491,144 @@ impl Position {
                 // 8.1. History Leaf Pruning
                 // Skips quiet moves with consistently poor history scores at shallow depth,
                 // assuming they are unlikely to improve alpha.
-                if depth < 6 && thread.history.get_score(mv, self.board().side()) < -5000 {
+                if depth <= HISTORY_PRUNING_DEPTH
+                    && thread.history.get_score(mv, self.board().side())
+                        < -HISTORY_PRUNING_MARGIN * depth as i32
+                {
                     picker.skip_quiets = true;
                 }
 
                 // 8.2. Futility Pruning
                 // Prunes quiet moves when the static evaluation plus a depth-based margin
                 // is still insufficient to raise alpha.
-                let futility_margin: i32 = 80 * depth as i32;
+                let futility_margin: i32 = thread.params.futility_margin * depth as i32;
                 if static_eval + futility_margin < alpha && depth <= 6 {
                     picker.skip_quiets = true;
                 }
 
                 // 8.3. Late Move Pruning
-                // Skips late quiet moves at shallow depth after enough moves
-                // have already been searched.
-                if depth < 4 && move_count >= 9 {
+                // Skips late quiet moves at shallow depth after a depth-dependent
+                // number of moves have already been searched.
+                if depth <= LMP_DEPTH && move_count >= thread.params.lmp_moves + depth * depth {
                     picker.skip_quiets = true;
                 }
+
+                // 8.4. Quiet SEE Pruning
+                // Drops this quiet move if it hangs material beyond a depth-scaled margin.
+                if depth <= QUIET_SEE_DEPTH
+                    && best_score > -MAX_MATE
+                    && !SEE::see(&self.board(), mv, -QUIET_SEE_MARGIN * depth as i32)
+                {
+                    continue;
+                }
+            }
+
+            // 9. Extensions
+            // 9.1. Checks that do not lose material are extended.
+            // 9.2. At PV nodes, recaptures on the square of the previous capture are extended.
+            // 9.3. The only legal reply to a check is extended.
+            // 9.4. In tactical mode every check and recapture is extended.
+            let gives_check: bool = self.gives_check(mv);
+            let recapture: bool = mv.is_capture()
+                && previous.is_some_and(|(prev_mv, _)| {
+                    prev_mv.is_capture() && prev_mv.get_dest() == mv.get_dest()
+                });
+            let extension: usize = if single_reply
+                || (gives_check && (thread.tactical || SEE::see(&self.board(), mv, 0)))
+            {
+                1
+            } else {
+                ((is_pv || thread.tactical) && recapture) as usize
+            };
+            let new_depth: usize = (depth - 1 + extension).min(MAX_PLY - 1);
+
+            if N::ROOT
+                && thread.show_currline
+                && thread.output.uci()
+                && thread.time_manager.elapsed() >= Duration::from_millis(CURRLINE_DELAY)
+            {
+                let line: Vec<String> = tt_line(&self.board(), mv, ttable)
+                    .iter()
+                    .map(|mv| mv.to_string())
+                    .collect();
+                println!("info currline {}", line.join(" "));
             }
 
+            // Prefetch the child entry so it arrives while the move is made
+            ttable.prefetch(self.key_after(mv));
             self.push_move(mv, thread);
-            ttable.prefetch(self.key());
 
-            // 9. Principal Variation Search
+            // 10. Principal Variation Search
             if move_count == 1 {
-                // 9.1. First move: Full Window Search
-                score = -self.alphabeta::<false>(
-                    thread,
-                    ttable,
-                    depth - 1,
-                    -beta,
-                    -alpha,
-                    child_pv,
-                    true,
-                )
+                // 10.1. First move: Full Window Search
+                score = -self
+                    .alphabeta::<N::FirstChild>(thread, ttable, new_depth, -beta, -alpha, child_pv)
             } else {
-                // 9.2. Later moves: Null Window Search + Late Moves Reduction
-                let reduction: usize =
-                    if move_count >= 3 && depth >= 3 && !in_check && mv.is_quiet() {
-                        let r: usize = lmr_reduction(depth, move_count);
-                        r.clamp(1, depth - 1)
-                    } else {
-                        0
-                    };
+                // 10.2. Later moves: Null Window Search + Late Moves Reduction
+                let reduction: usize = if move_count >= 3
+                    && depth >= 3
+                    && !in_check
+                    && !gives_check
+                    && mv.is_quiet()
+                {
+                    // Expected cut-nodes are reduced more aggressively
+                    let r: usize = lmr_reduction(depth, move_count) + N::CUT as usize;
+                    r.clamp(1, new_depth - 1)
+                } else {
+                    0
+                };
 
                 // Reduced depth + Null Window Search
-                score = -self.alphabeta::<false>(
+                score = -self.alphabeta::<CutNode>(
                     thread,
                     ttable,
-                    depth - 1 - reduction,
+                    new_depth - reduction,
                     -alpha - 1,
                     -alpha,
                     child_pv,
-                    true,
                 );
 
-                // 9.3. If it fails high, and it has been reduced, let's re-search with Full depth & Null Window
+                // 10.3. If it fails high, and it has been reduced, let's re-search with Full depth & Null Window
                 if score > alpha && reduction > 0 {
-                    score = -self.alphabeta::<false>(
+                    score = -self.alphabeta::<CutNode>(
                         thread,
                         ttable,
-                        depth - 1,
+                        new_depth,
                         -alpha - 1,
                         -alpha,
                         child_pv,
-                        true,
                     );
                 }
 
-                // 9.4. If it fails high, and we are in a PV node, re-search with Full Window
+                // 10.4. If it fails high, and we are in a PV node, re-search with Full Window
                 if score > alpha && is_pv {
-                    score = -self.alphabeta::<false>(
+                    score = -self
+                        .alphabeta::<PvNode>(thread, ttable, new_depth, -beta, -alpha, child_pv);
+                }
+
+                // 10.5. With tie-breaking, a root move failing low exactly on alpha is
+                // verified on a window around it to tell a real tie apart
+                if N::ROOT && thread.tie_break && score == alpha && alpha > -INFINITY {
+                    let verified: i32 = -self.alphabeta::<PvNode>(
                         thread,
                         ttable,
-                        depth - 1,
-                        -beta,
-                        -alpha,
+                        new_depth,
+                        -alpha - 1,
+                        -alpha + 1,
                         child_pv,
-                        true,
                     );
+                    if verified == alpha {
+                        thread.ties.push(mv);
+                    }
                 }
             }
             self.pop_move(thread);
 
-            if thread.time_manager.stopped() {
+            // Root moves also check the hard limit, so a single slow iteration
+            // cannot overshoot very short time controls.
+            if thread.time_manager.stopped()
+                || (N::ROOT && thread.time_manager.stop_hard(thread.nodes))
+            {
                 return 0;
             }
 
@@ -447,6 +644,9 @@ impl Position {
                 if score > alpha {
                     alpha = score;
                     best_move = mv;
+                    if N::ROOT {
+                        thread.ties.clear();
+                    }
                     if is_pv {
                         node_pv.push_line(best_move, child_pv);
                     }
@@ -465,7 +665,12 @@ impl Position {
                             .update_non_cutoffs(&quiets_tried, depth, self.board().side);
 
                         // Killers updates
-                        thread.killer.store(thread.ply, mv);
+                        thread.stack.store_killer(thread.ply, mv);
+
+                        // Countermove update
+                        if let Some((prev_mv, prev_piece)) = previous {
+                            thread.counter.store(prev_mv, prev_piece, mv);
+                        }
                     }
                     break;
                 }
@@ -524,8 +729,9 @@ impl Position {
             return 0;
         }
 
+        // Ply limit reached, return the static evaluation
         let in_check: bool = self.in_check();
-        if thread.ply >= MAX_PLY {
+        if thread.ply >= MAX_PLY - 1 {
             return if in_check { 0 } else { self.evaluate() };
         }
 
@@ -555,14 +761,29 @@ impl Position {
                 }
             }
 
-            if let Some(mv) = entry.legal_move(&self.board()) {
-                tt_move = Some(mv);
+            // A stored move that is not legal here comes from a key collision or a
+            // corrupted entry, and is dropped rather than searched
+            match entry.legal_move(&self.board()) {
+                Some(mv) => tt_move = Some(mv),
+                None if !entry.mv.is_null() => thread.telemetry.rejected_tt_moves += 1,
+                None => {}
             }
         }
 
         let alpha_orig: i32 = alpha;
 
-        let stand_pat: i32 = self.evaluate();
+        let static_eval: i32 = match tt_entry {
+            Some(entry) if !in_check && entry.evaluation != -INFINITY => entry.evaluation,
+            _ => thread.noisy(self.evaluate(), self.key()),
+        };
+
+        // A rook or queen en prise is likely lost on the next move, so the static
+        // evaluation overstates what standing pat is worth
+        let stand_pat: i32 = if !in_check && self.hanging_major() {
+            static_eval - QS_THREAT_MARGIN
+        } else {
+            static_eval
+        };
 
         // Standing Pat Prunning
         // Fail-soft beta cuttof
@@ -579,13 +800,13 @@ impl Position {
         let mut best_move: Move = Move::default();
         let mut move_count: usize = 0;
 
-        let mut picker: MovePicker = MovePicker::new(tt_move, [None, None]);
+        let mut picker: MovePicker = MovePicker::new(tt_move, [None; KILLER_SLOTS], None);
         picker.skip_quiets = true;
 
         // Main Quiescence Loop
         while let Some(mv) = picker.next(&self.board(), &thread.history) {
+            ttable.prefetch(self.key_after(mv));
             self.push_move(mv, thread);
-            ttable.prefetch(self.key());
             move_count += 1;
             let score: i32 = -self.quiescence(thread, ttable, -beta, -alpha, child_pv);
             self.pop_move(thread);
@@ -630,7 +851,7 @@ impl Position {
             self.key(),
             best_move,
             best_score,
-            0,
+            if in_check { -INFINITY } else { static_eval },
             0,
             bound,
             is_pv,
@@ -653,32 +874,57 @@ fn mated_in(ply: usize) -> i32 {
 
 #[inline(always)]
 fn uci_printer(thread: &mut Thread, ttable: &TranspositionTable) {
-    let score: String = if thread.score.abs() >= MAX_MATE {
-        let mate_in: i32 = (MATE - thread.score.abs() + 1) / 2;
+    let elapsed: Duration = thread.time_manager.elapsed();
+    let nodes: u64 = thread.time_manager.nodes();
+    let info: SearchInfo<'_> = SearchInfo {
+        depth: thread.depth,
+        seldepth: thread.seldepth,
+        score: Score::new(thread.score),
+        time: elapsed.as_millis().max(1),
+        nodes,
+        nps: thread.telemetry.update_nps(elapsed, nodes),
+        hashfull: ttable.hash_full(),
+        pv: &thread.principal_variation,
+    };
 
-        if thread.score > 0 {
-            format!("mate {}", mate_in)
-        } else {
-            format!("mate -{}", mate_in)
+    if thread.output.uci() {
+        println!("{}", info.to_uci());
+    }
+    if thread.output.json() {
+        println!("{}", info.to_json());
+    }
+}
+
+/// Prints the best reply found in the transposition table for every root move
+/// other than the best one.
+fn refutation_printer(board: &Board, thread: &Thread, ttable: &TranspositionTable) {
+    let moves: MoveList = legal_moves!(board);
+    for &mv in moves.iter().filter(|&&mv| mv != thread.best_move()) {
+        let child: Board = board.make_move(mv);
+        if let Some(reply) = ttable
+            .probe(child.zobrist.0, 1)
+            .and_then(|hit| hit.legal_move(&child))
+        {
+            println!("info refutation {mv} {reply}");
         }
-    } else {
-        format!("cp {}", thread.score)
-    };
+    }
+}
 
-    let time: u128 = thread.time_manager.elapsed().as_millis().max(1);
-    let nodes: u64 = thread.time_manager.nodes();
-    let nps: u128 = (nodes as u128 * 1000) / time;
-    println!(
-        "info depth {} seldepth {} score {} time {} nodes {} nps {} hashfull {} {}",
-        thread.depth,
-        thread.seldepth,
-        score,
-        time,
-        nodes,
-        nps,
-        ttable.hash_full(),
-        thread.principal_variation
-    );
+/// `first` followed by the line stored in the transposition table.
+fn tt_line(board: &Board, first: Move, ttable: &TranspositionTable) -> Vec<Move> {
+    let mut line: Vec<Move> = vec![first];
+    let mut board: Board = board.make_move(first);
+    while line.len() < CURRLINE_LENGTH {
+        let Some(mv) = ttable
+            .probe(board.zobrist.0, line.len())
+            .and_then(|hit| hit.legal_move(&board))
+        else {
+            break;
+        };
+        line.push(mv);
+        board = board.make_move(mv);
+    }
+    line
 }
 
 #[inline(always)]
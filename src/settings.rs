@@ -0,0 +1,103 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/settings.rs
+
+//! Persistent engine configuration file.
+//!
+//! Default option values are read from a `laura.toml` file next to the binary.
+//! Only a flat subset of TOML is understood: one `Name = value` pair per line,
+//! `#` comments and optionally quoted values.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+pub const CONFIG_FILE: &str = "laura.toml";
+
+/// Path of the configuration file, next to the running binary.
+pub fn config_path() -> Option<PathBuf> {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(CONFIG_FILE)))
+}
+
+/// Parses `Name = value` pairs, skipping blank lines, comments and section headers.
+pub fn parse(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('['))
+        .filter_map(|line| {
+            let (name, value) = line.split_once('=')?;
+            let name: &str = name.trim();
+            let value: &str = value.trim().trim_matches('"');
+            (!name.is_empty() && !value.is_empty()).then(|| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Formats the pairs back into the configuration file syntax.
+pub fn format(settings: &[(String, String)]) -> String {
+    let mut content: String = String::from("# Laura engine configuration\n");
+    for (name, value) in settings {
+        if value.parse::<i64>().is_ok() || value.parse::<bool>().is_ok() {
+            content.push_str(&format!("{name} = {value}\n"));
+        } else {
+            content.push_str(&format!("{name} = \"{value}\"\n"));
+        }
+    }
+    content
+}
+
+/// Loads the configuration file, returning an empty list if it does not exist.
+pub fn load(path: &Path) -> io::Result<Vec<(String, String)>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(parse(&content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes the configuration file.
+pub fn save(path: &Path, settings: &[(String, String)]) -> io::Result<()> {
+    fs::write(path, format(settings))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format, parse};
+
+    #[test]
+    fn test_parse_format() {
+        let content: &str = "# defaults\n[engine]\nHash = 256\nThreads=4 # cores\n\nBookFile = \"book.bin\"\ninvalid line\n";
+        let settings: Vec<(String, String)> = parse(content);
+        assert_eq!(
+            settings,
+            vec![
+                ("Hash".to_string(), "256".to_string()),
+                ("Threads".to_string(), "4".to_string()),
+                ("BookFile".to_string(), "book.bin".to_string()),
+            ]
+        );
+
+        assert_eq!(parse(&format(&settings)), settings);
+    }
+}
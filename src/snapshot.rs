@@ -0,0 +1,87 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/snapshot.rs
+
+//! Checkpoint and resume of analysis sessions.
+//!
+//! A snapshot is the `position` command that rebuilds the current game,
+//! optionally followed by a dump of the transposition table in `<file>.tt`.
+
+use crate::transposition::TranspositionTable;
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+fn table_path(path: &Path) -> PathBuf {
+    let mut name: OsString = path.as_os_str().to_owned();
+    name.push(".tt");
+    PathBuf::from(name)
+}
+
+/// Writes the position command and, if given, the transposition table.
+pub fn save(path: &Path, position: &str, ttable: Option<&TranspositionTable>) -> io::Result<()> {
+    fs::write(path, format!("{position}\n"))?;
+
+    if let Some(ttable) = ttable {
+        let mut writer: BufWriter<File> = BufWriter::new(File::create(table_path(path))?);
+        ttable.save(&mut writer)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Reads the position command, restoring the transposition table if it was saved.
+pub fn load(path: &Path, ttable: &mut TranspositionTable) -> io::Result<String> {
+    let position: String = fs::read_to_string(path)?.trim().to_string();
+
+    match File::open(table_path(path)) {
+        Ok(file) => ttable.load(&mut BufReader::new(file))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    Ok(position)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{load, save};
+    use crate::transposition::TranspositionTable;
+    use std::{env, fs, path::PathBuf};
+
+    #[test]
+    fn test_snapshot() {
+        let path: PathBuf = env::temp_dir().join(format!("laura_snapshot_{}", std::process::id()));
+        let position: &str = "position startpos moves e2e4 e7e5";
+
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        save(&path, position, Some(&ttable)).unwrap();
+
+        let mut restored: TranspositionTable = TranspositionTable::default();
+        assert_eq!(load(&path, &mut restored).unwrap(), position);
+        assert_eq!(restored.hash_full(), 0);
+
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(path.with_extension("tt"));
+    }
+}
@@ -0,0 +1,128 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/stack.rs
+
+//! Per-ply search stack.
+
+use crate::{
+    config::{KILLER_SLOTS, MAX_PLY},
+    tables::KillerMoves,
+};
+use laura_core::{Move, PieceType};
+use std::ops::{Index, IndexMut};
+
+/// Search information stored for a single ply.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StackEntry {
+    /// Move played from this ply and the piece that moved
+    pub current: Option<(Move, PieceType)>,
+    /// Static evaluation of the position at this ply
+    pub static_eval: i32,
+    /// Killer moves found at this ply
+    pub killers: KillerMoves,
+    /// Move excluded from the search at this ply
+    pub excluded: Option<Move>,
+    /// Whether the move played from this ply was a null move
+    pub null_move: bool,
+}
+
+/// Fixed-size stack of [`StackEntry`] indexed by ply.
+#[derive(Debug)]
+pub struct SearchStack {
+    entries: [StackEntry; MAX_PLY],
+}
+
+impl Default for SearchStack {
+    fn default() -> Self {
+        Self {
+            entries: [StackEntry::default(); MAX_PLY],
+        }
+    }
+}
+
+impl SearchStack {
+    /// Resets every per-ply entry before a new search.
+    pub fn clear(&mut self) {
+        self.entries.fill(StackEntry::default());
+    }
+
+    /// Stores a killer move, ignoring plies beyond the stack.
+    #[inline(always)]
+    pub fn store_killer(&mut self, ply: usize, mv: Move) {
+        if let Some(entry) = self.entries.get_mut(ply) {
+            entry.killers.store(mv);
+        }
+    }
+
+    /// Killer moves of a ply, none for plies beyond the stack.
+    #[inline(always)]
+    pub fn killers(&self, ply: usize) -> [Option<Move>; KILLER_SLOTS] {
+        self.entries
+            .get(ply)
+            .map_or([None; KILLER_SLOTS], |entry| entry.killers.get())
+    }
+
+    /// Clears the killers of a ply, ignoring plies beyond the stack.
+    #[inline(always)]
+    pub fn clear_killers(&mut self, ply: usize) {
+        if let Some(entry) = self.entries.get_mut(ply) {
+            entry.killers.clear();
+        }
+    }
+}
+
+impl Index<usize> for SearchStack {
+    type Output = StackEntry;
+
+    #[inline(always)]
+    fn index(&self, ply: usize) -> &Self::Output {
+        &self.entries[ply]
+    }
+}
+
+impl IndexMut<usize> for SearchStack {
+    #[inline(always)]
+    fn index_mut(&mut self, ply: usize) -> &mut Self::Output {
+        &mut self.entries[ply]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SearchStack;
+    use crate::config::{KILLER_SLOTS, MAX_PLY};
+    use laura_core::{Move, MoveType, Square};
+
+    #[test]
+    fn test_killer_bounds() {
+        let mut stack: SearchStack = SearchStack::default();
+        let mv: Move = Move::new(Square::A2, Square::A3, MoveType::Quiet);
+
+        stack.store_killer(3, mv);
+        assert_eq!(stack.killers(3)[0], Some(mv));
+        stack.clear_killers(3);
+        assert_eq!(stack.killers(3), [None; KILLER_SLOTS]);
+
+        // Out of range plies are ignored instead of panicking
+        stack.store_killer(MAX_PLY, mv);
+        stack.clear_killers(MAX_PLY + 1);
+        assert_eq!(stack.killers(MAX_PLY), [None; KILLER_SLOTS]);
+    }
+}
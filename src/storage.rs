@@ -0,0 +1,245 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/storage.rs
+
+//! Backing memory of the transposition table.
+//!
+//! The table is normally a private heap allocation. In cluster mode it is a
+//! named shared-memory segment instead, so several engine processes probe
+//! and store into the same cells. Cells only hold lock-free atomics, which
+//! stay valid when the memory is mapped by more than one process.
+
+use crate::transposition::Cell;
+use std::fmt;
+
+/// Memory holding the cells of a transposition table.
+pub trait TableStorage: fmt::Debug + Send + Sync {
+    fn cells(&self) -> &[Cell];
+    fn cells_mut(&mut self) -> &mut [Cell];
+}
+
+impl TableStorage for Vec<Cell> {
+    fn cells(&self) -> &[Cell] {
+        self
+    }
+
+    fn cells_mut(&mut self) -> &mut [Cell] {
+        self
+    }
+}
+
+impl Default for Box<dyn TableStorage> {
+    fn default() -> Self {
+        Box::new(Vec::<Cell>::new())
+    }
+}
+
+/// A shared-memory segment could not be opened or mapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedMemoryError {
+    pub name: String,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for SharedMemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "shared hash '{}': {}", self.name, self.reason)
+    }
+}
+
+impl std::error::Error for SharedMemoryError {}
+
+/// Cells mapped from a named shared-memory segment.
+#[derive(Debug)]
+pub struct SharedStorage {
+    ptr: *mut Cell,
+    len: usize,
+}
+
+// SAFETY: the mapping is only accessed through atomics and lives as long as the storage
+unsafe impl Send for SharedStorage {}
+unsafe impl Sync for SharedStorage {}
+
+impl TableStorage for SharedStorage {
+    fn cells(&self) -> &[Cell] {
+        // SAFETY: ptr maps len cells until the storage is dropped
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    fn cells_mut(&mut self) -> &mut [Cell] {
+        // SAFETY: ptr maps len cells until the storage is dropped
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+/// Names are restricted so they map to a single `/dev/shm` entry.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    pub const O_RDWR: i32 = 0o2;
+    pub const O_CREAT: i32 = 0o100;
+    pub const PROT_READ: i32 = 0x1;
+    pub const PROT_WRITE: i32 = 0x2;
+    pub const MAP_SHARED: i32 = 0x1;
+    pub const SEEK_END: i32 = 2;
+
+    unsafe extern "C" {
+        pub fn shm_open(name: *const u8, oflag: i32, mode: u32) -> i32;
+        #[cfg(test)]
+        pub fn shm_unlink(name: *const u8) -> i32;
+        pub fn ftruncate(fd: i32, length: i64) -> i32;
+        pub fn lseek(fd: i32, offset: i64, whence: i32) -> i64;
+        pub fn mmap(addr: *mut u8, len: usize, prot: i32, flags: i32, fd: i32, off: i64)
+        -> *mut u8;
+        pub fn munmap(addr: *mut u8, len: usize) -> i32;
+        pub fn close(fd: i32) -> i32;
+    }
+
+    /// Null-terminated segment path for a table name.
+    pub fn path(name: &str) -> Vec<u8> {
+        format!("/laura-{name}\0").into_bytes()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SharedStorage {
+    /// Opens the segment `name`, creating it zeroed if it does not exist yet.
+    /// Every process sharing a segment must use the same hash size.
+    pub fn open(name: &str, megabytes: usize) -> Result<Self, SharedMemoryError> {
+        use crate::config::MEGABYTE;
+
+        let error = |reason: &'static str| SharedMemoryError {
+            name: name.to_string(),
+            reason,
+        };
+        if !valid_name(name) {
+            return Err(error("names may only contain letters, digits, '-' and '_'"));
+        }
+
+        let len: usize = megabytes
+            .checked_mul(MEGABYTE)
+            .ok_or(error("invalid size"))?
+            / size_of::<Cell>();
+        let bytes: usize = len * size_of::<Cell>();
+        if bytes == 0 {
+            return Err(error("invalid size"));
+        }
+
+        let path: Vec<u8> = sys::path(name);
+        unsafe {
+            let fd: i32 = sys::shm_open(path.as_ptr(), sys::O_RDWR | sys::O_CREAT, 0o600);
+            if fd < 0 {
+                return Err(error("could not open the segment"));
+            }
+
+            // A new segment is empty, an existing one must match our size
+            let size: i64 = sys::lseek(fd, 0, sys::SEEK_END);
+            let sized: bool = match size {
+                0 => sys::ftruncate(fd, bytes as i64) == 0,
+                size => size == bytes as i64,
+            };
+            if !sized {
+                sys::close(fd);
+                return Err(error("the segment exists with a different hash size"));
+            }
+
+            let ptr: *mut u8 = sys::mmap(
+                std::ptr::null_mut(),
+                bytes,
+                sys::PROT_READ | sys::PROT_WRITE,
+                sys::MAP_SHARED,
+                fd,
+                0,
+            );
+            sys::close(fd);
+            if ptr as isize == -1 {
+                return Err(error("could not map the segment"));
+            }
+
+            Ok(Self {
+                ptr: ptr as *mut Cell,
+                len,
+            })
+        }
+    }
+
+    /// Removes the segment, for tests that must not leave it behind.
+    #[cfg(test)]
+    pub fn unlink(name: &str) {
+        unsafe { sys::shm_unlink(sys::path(name).as_ptr()) };
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for SharedStorage {
+    fn drop(&mut self) {
+        // The segment itself outlives us, for the other processes using it
+        unsafe {
+            sys::munmap(self.ptr as *mut u8, self.len * size_of::<Cell>());
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl SharedStorage {
+    pub fn open(name: &str, _megabytes: usize) -> Result<Self, SharedMemoryError> {
+        let reason: &'static str = if valid_name(name) {
+            "shared memory tables are only supported on Linux"
+        } else {
+            "names may only contain letters, digits, '-' and '_'"
+        };
+        Err(SharedMemoryError {
+            name: name.to_string(),
+            reason,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SharedStorage, TableStorage, valid_name};
+
+    #[test]
+    fn test_names() {
+        assert!(valid_name("cluster-1_a"));
+        assert!(!valid_name(""));
+        assert!(!valid_name("../etc"));
+        assert!(SharedStorage::open("bad/name", 1).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_shared_size() {
+        let name: String = format!("size-{}", std::process::id());
+        let storage: SharedStorage = SharedStorage::open(&name, 1).unwrap();
+        assert_eq!(storage.cells().len(), 32_768);
+
+        // Processes sharing a segment must agree on its size
+        assert!(SharedStorage::open(&name, 2).is_err());
+        SharedStorage::unlink(&name);
+    }
+}
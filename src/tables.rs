@@ -21,64 +21,104 @@
 
 //! Search tables for move ordering.
 
-use crate::config::{HIST_CLAMP, KILLER_SLOTS, MAX_PLY};
+use crate::config::{HIST_CLAMP, HIST_DECAY, KILLER_SLOTS};
 use laura_core::{Color, Move, PieceType};
+use std::sync::{
+    Arc,
+    atomic::{AtomicI32, Ordering},
+};
 
 // Killer Moves
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct KillerMoves {
-    table: [[Option<Move>; KILLER_SLOTS]; MAX_PLY],
+    slots: [Option<Move>; KILLER_SLOTS],
 }
 
 impl Default for KillerMoves {
     fn default() -> Self {
         Self {
-            table: [[None; KILLER_SLOTS]; MAX_PLY],
+            slots: [None; KILLER_SLOTS],
         }
     }
 }
 
 impl KillerMoves {
+    /// Puts the move in the first slot. Older killers shift down one slot,
+    /// and a move already stored is moved up instead of duplicated.
     #[inline(always)]
-    pub fn store(&mut self, ply: usize, mv: Move) {
-        if self.table[ply][0] != Some(mv) {
-            self.table[ply][1] = self.table[ply][0];
-            self.table[ply][0] = Some(mv);
-        }
+    pub fn store(&mut self, mv: Move) {
+        let last: usize = self
+            .slots
+            .iter()
+            .position(|slot| *slot == Some(mv))
+            .unwrap_or(KILLER_SLOTS - 1);
+        self.slots[..=last].rotate_right(1);
+        self.slots[0] = Some(mv);
+    }
+
+    #[inline(always)]
+    pub fn get(&self) -> [Option<Move>; KILLER_SLOTS] {
+        self.slots
     }
 
     #[inline(always)]
-    pub fn get(&self, ply: usize) -> [Option<Move>; KILLER_SLOTS] {
-        self.table[ply]
+    pub fn clear(&mut self) {
+        self.slots = [None; KILLER_SLOTS];
     }
 }
 
 // History Table
-#[derive(Debug, Copy, Clone)]
+/// Butterfly history table. The scores are stored in atomics so a single table
+/// can be shared between search threads with relaxed, lock-free updates.
+#[derive(Debug)]
 pub struct HistoryTable {
     // [side_to_move][from_square][to_square]
-    table: [[[i32; 64]; 64]; 2],
+    table: Arc<[AtomicI32]>,
 }
 
 impl Default for HistoryTable {
     fn default() -> Self {
         Self {
-            table: [[[0; 64]; 64]; 2],
+            table: (0..2 * 64 * 64).map(|_| AtomicI32::new(0)).collect(),
         }
     }
 }
 
 impl HistoryTable {
+    /// Returns a handle to the same underlying table.
+    #[inline(always)]
+    pub fn share(&self) -> Self {
+        Self {
+            table: Arc::clone(&self.table),
+        }
+    }
+
+    /// Resets every score to zero
+    pub fn clear(&self) {
+        for entry in self.table.iter() {
+            entry.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Shrinks every score towards zero, so that long games do not saturate
+    /// the table with stale scores from earlier positions.
+    pub fn decay(&self) {
+        for entry in self.table.iter() {
+            let score: i32 = entry.load(Ordering::Relaxed);
+            entry.store(score - score / HIST_DECAY, Ordering::Relaxed);
+        }
+    }
+
     /// Updates de move that causes the beta cutoff
     #[inline(always)]
-    pub fn update_cutoff(&mut self, mv: Move, depth: usize, color: Color) {
+    pub fn update_cutoff(&self, mv: Move, depth: usize, color: Color) {
         let bonus: i32 = calculate_bonus(depth as i32);
         self.add_score(mv, color, bonus);
     }
 
     /// Penalizes the moves that were searched but didn't cause a beta cutoff
     #[inline(always)]
-    pub fn update_non_cutoffs(&mut self, quiets: &[Move], depth: usize, color: Color) {
+    pub fn update_non_cutoffs(&self, quiets: &[Move], depth: usize, color: Color) {
         let penalty: i32 = -calculate_bonus(depth as i32);
         for &mv in quiets {
             self.add_score(mv, color, penalty);
@@ -88,27 +128,34 @@ impl HistoryTable {
     /// Gets the score of a Move
     #[inline(always)]
     pub fn get_score(&self, mv: Move, color: Color) -> i32 {
-        let color_idx: usize = color as usize;
-        let from: usize = mv.get_src() as usize;
-        let to: usize = mv.get_dest() as usize;
-
-        self.table[color_idx][from][to]
+        self.table[history_index(mv, color)].load(Ordering::Relaxed)
     }
 
     /// Adds a score (bonus or penalties) to a move
     #[inline(always)]
-    fn add_score(&mut self, mv: Move, color: Color, delta: i32) {
-        let color_idx: usize = color as usize;
-        let from: usize = mv.get_src() as usize;
-        let to: usize = mv.get_dest() as usize;
-
-        let old_score: i32 = self.table[color_idx][from][to];
+    fn add_score(&self, mv: Move, color: Color, delta: i32) {
+        let entry: &AtomicI32 = &self.table[history_index(mv, color)];
+
+        // The load/store pair is not atomic as a whole: a racing update from
+        // another thread may be lost, which is harmless for move ordering.
+        // History gravity: the closer a score is to the clamp, the less a
+        // bonus of the same sign moves it.
+        let old_score: i32 = entry.load(Ordering::Relaxed);
         let new_score: i32 = old_score + delta - (old_score * delta.abs()) / HIST_CLAMP;
 
-        self.table[color_idx][from][to] = new_score.clamp(-HIST_CLAMP, HIST_CLAMP);
+        entry.store(new_score.clamp(-HIST_CLAMP, HIST_CLAMP), Ordering::Relaxed);
     }
 }
 
+#[inline(always)]
+fn history_index(mv: Move, color: Color) -> usize {
+    let color_idx: usize = color as usize;
+    let from: usize = mv.get_src() as usize;
+    let to: usize = mv.get_dest() as usize;
+
+    (color_idx * 64 + from) * 64 + to
+}
+
 // Countermove History
 #[derive(Debug, Copy, Clone)]
 pub struct CountermoveTable {
@@ -265,3 +312,63 @@ fn calculate_bonus(depth: i32) -> i32 {
     let depth: i32 = depth.min(12);
     depth * depth + 2 * depth - 2
 }
+
+#[cfg(test)]
+mod test {
+    use super::{HistoryTable, KillerMoves};
+    use crate::config::{HIST_CLAMP, HIST_DECAY, KILLER_SLOTS};
+    use laura_core::{Board, Color, Move};
+
+    #[test]
+    fn test_killer_slots() {
+        let board: Board = Board::default();
+        let moves: Vec<Move> = ["a2a3", "b2b3", "c2c3", "d2d3"]
+            .iter()
+            .map(|mv| board.find_move(mv).unwrap())
+            .collect();
+        let mut killers: KillerMoves = KillerMoves::default();
+        assert_eq!(killers.get(), [None; KILLER_SLOTS]);
+
+        // Newer killers push the older ones down and the oldest falls off
+        for mv in &moves {
+            killers.store(*mv);
+        }
+        let slots: [Option<Move>; KILLER_SLOTS] = killers.get();
+        for (slot, mv) in slots.iter().zip(moves.iter().rev()) {
+            assert_eq!(*slot, Some(*mv));
+        }
+
+        // Storing a killer again moves it to the front without duplicates
+        let last: Move = slots[KILLER_SLOTS - 1].unwrap();
+        killers.store(last);
+        let slots: [Option<Move>; KILLER_SLOTS] = killers.get();
+        assert_eq!(slots[0], Some(last));
+        assert_eq!(slots.iter().filter(|slot| **slot == Some(last)).count(), 1);
+        assert_eq!(slots[1], Some(moves[3]));
+
+        killers.store(last);
+        assert_eq!(killers.get(), slots);
+
+        killers.clear();
+        assert_eq!(killers.get(), [None; KILLER_SLOTS]);
+    }
+
+    #[test]
+    fn test_history_gravity_and_decay() {
+        let board: Board = Board::default();
+        let mv: Move = board.find_move("g1f3").unwrap();
+        let history: HistoryTable = HistoryTable::default();
+
+        // Repeated cutoffs converge below the clamp instead of saturating
+        for _ in 0..1_000 {
+            history.update_cutoff(mv, 20, Color::White);
+        }
+        let saturated: i32 = history.get_score(mv, Color::White);
+        assert!(saturated > 0 && saturated <= HIST_CLAMP);
+
+        history.decay();
+        let decayed: i32 = history.get_score(mv, Color::White);
+        assert_eq!(decayed, saturated - saturated / HIST_DECAY);
+        assert_eq!(history.get_score(mv, Color::Black), 0);
+    }
+}
@@ -0,0 +1,117 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/telemetry.rs
+
+//! Search telemetry: smoothed NPS, time-to-depth, effective branching factor
+//! and rejected hash moves.
+
+use crate::config::NPS_SMOOTHING;
+use std::time::Duration;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Telemetry {
+    // Diagnostic reporting enabled
+    pub enabled: bool,
+    // Rolling NPS average
+    nps: f64,
+    // Time and nodes at the previous report
+    last_time: Duration,
+    last_nodes: u64,
+    // Nodes spent on the previous iteration
+    last_iteration: u64,
+    // Hash moves that failed validation, from collisions or corrupted entries
+    pub rejected_tt_moves: u64,
+}
+
+impl Telemetry {
+    pub fn reset(&mut self) {
+        *self = Self {
+            enabled: self.enabled,
+            ..Self::default()
+        };
+    }
+
+    /// Folds the interval since the previous report into the rolling NPS average.
+    pub fn update_nps(&mut self, elapsed: Duration, nodes: u64) -> u64 {
+        let interval: f64 = (elapsed.saturating_sub(self.last_time)).as_secs_f64();
+        let searched: u64 = nodes.saturating_sub(self.last_nodes);
+
+        if interval > 0.0 {
+            let current: f64 = searched as f64 / interval;
+            self.nps = if self.last_time.is_zero() {
+                current
+            } else {
+                self.nps + NPS_SMOOTHING * (current - self.nps)
+            };
+            self.last_time = elapsed;
+            self.last_nodes = nodes;
+        }
+
+        self.nps as u64
+    }
+
+    /// Effective branching factor of the iteration that just finished,
+    /// as the ratio between its node count and the previous one.
+    pub fn update_ebf(&mut self, iteration_nodes: u64) -> Option<f64> {
+        let ebf: Option<f64> =
+            (self.last_iteration > 0).then(|| iteration_nodes as f64 / self.last_iteration as f64);
+        self.last_iteration = iteration_nodes;
+        ebf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Telemetry;
+    use std::time::Duration;
+
+    #[test]
+    fn test_nps_smoothing() {
+        let mut telemetry: Telemetry = Telemetry::default();
+        assert_eq!(
+            telemetry.update_nps(Duration::from_millis(100), 100_000),
+            1_000_000
+        );
+
+        // A single slow interval only moves the average part of the way
+        let nps: u64 = telemetry.update_nps(Duration::from_millis(200), 150_000);
+        assert!(nps < 1_000_000 && nps > 500_000);
+
+        // No elapsed time leaves the average untouched
+        assert_eq!(
+            telemetry.update_nps(Duration::from_millis(200), 160_000),
+            nps
+        );
+    }
+
+    #[test]
+    fn test_ebf() {
+        let mut telemetry: Telemetry = Telemetry {
+            enabled: true,
+            ..Telemetry::default()
+        };
+        assert_eq!(telemetry.update_ebf(100), None);
+        assert_eq!(telemetry.update_ebf(400), Some(4.0));
+
+        telemetry.reset();
+        assert!(telemetry.enabled);
+        assert_eq!(telemetry.update_ebf(100), None);
+    }
+}
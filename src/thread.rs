@@ -23,22 +23,34 @@
 
 use crate::tables::HistoryTable;
 use crate::{
-    TimeManager,
-    position::Position,
+    TimeManager, book,
+    config::{FEW_MOVES, FEW_MOVES_TIME_SCALE, MAX_PLY, PNS_MAX_NODES},
+    game::GameState,
+    movelist::MoveListExt,
+    options::EngineOptions,
+    output::OutputFormat,
+    perft::{PerftTable, hashed_perft},
+    pns::{PnsResult, ProofNumberSearch},
+    position::{Position, inner_perft},
+    profile::SearchParams,
     search::{MainThread, PrincipalVariation, WorkerThread},
-    tables::KillerMoves,
+    stack::SearchStack,
+    tables::CountermoveTable,
+    telemetry::Telemetry,
     timer::TimeControl,
     transposition::TranspositionTable,
+    workers::{Job, WorkerPool},
 };
-use laura_core::{Move, MoveList, legal_moves};
+use laura_core::{Board, Move, MoveList, PieceType, legal_moves};
 use std::{
     collections::HashMap,
     iter::once,
     sync::{
         Arc,
-        atomic::{AtomicBool, AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     },
-    thread,
+    thread::{self, ScopedJoinHandle},
+    time::{Duration, Instant},
 };
 
 #[derive(Debug)]
@@ -46,14 +58,39 @@ pub struct Thread {
     pub id: usize,
     pub time_manager: TimeManager,
     pub principal_variation: PrincipalVariation,
-    pub killer: KillerMoves,
+    pub stack: SearchStack,
     pub history: HistoryTable,
+    pub counter: CountermoveTable,
     pub nodes: u64,
     pub ply: usize,
     pub seldepth: usize,
     pub score: i32,
     pub depth: usize,
     pub completed: usize,
+    pub telemetry: Telemetry,
+    pub output: OutputFormat,
+    pub params: SearchParams,
+    pub show_refutations: bool,
+    pub show_currline: bool,
+    pub max_depth: usize,
+    /// Favours forcing lines in move ordering and extensions
+    pub tactical: bool,
+    /// Root moves left out of the search
+    pub excluded: Vec<Move>,
+    /// Move the previous principal variation expects at the root
+    pub root_hint: Option<Move>,
+    /// Verifies root moves scoring exactly as the best one
+    pub tie_break: bool,
+    /// Root moves tied with the best one in the current iteration
+    pub ties: Vec<Move>,
+    /// Root moves tied with the best one in the last completed iteration
+    pub root_ties: Vec<Move>,
+    /// Xorshift state of the thread
+    pub rng: u64,
+    /// Amplitude in centipawns of the static evaluation noise, 0 when disabled
+    pub noise: i32,
+    /// Seed shared by all threads, so a position always gets the same noise
+    pub noise_seed: u64,
 }
 
 impl Thread {
@@ -67,25 +104,57 @@ impl Thread {
             score: 0,
             depth: 0,
             time_manager,
-            killer: KillerMoves::default(),
+            stack: SearchStack::default(),
             history: HistoryTable::default(),
+            counter: CountermoveTable::default(),
             completed: 0,
+            telemetry: Telemetry::default(),
+            output: OutputFormat::default(),
+            params: SearchParams::default(),
+            show_refutations: false,
+            show_currline: false,
+            max_depth: MAX_PLY,
+            tactical: false,
+            excluded: Vec::new(),
+            root_hint: None,
+            tie_break: false,
+            ties: Vec::new(),
+            root_ties: Vec::new(),
+            rng: splitmix(id as u64),
+            noise: 0,
+            noise_seed: 0,
         }
     }
 
     pub fn smp(stop: Arc<AtomicBool>, nodes: Arc<AtomicU64>, id: usize) -> Self {
         Self::new(
-            TimeManager::new(stop, nodes, TimeControl::Infinite, false),
+            TimeManager::new(stop, nodes, TimeControl::INFINITE, false),
             id,
         )
     }
 
+    /// Returns the move (and moving piece) that led to the current ply, if any.
+    #[inline(always)]
+    pub fn previous_move(&self) -> Option<(Move, PieceType)> {
+        self.ply
+            .checked_sub(1)
+            .and_then(|ply| self.stack[ply].current)
+    }
+
     pub fn best_move(&self) -> Move {
         self.principal_variation.moves[0]
     }
 
+    /// Forgets everything learned in previous games.
+    pub fn clear(&mut self) {
+        self.stack.clear();
+        self.history.clear();
+        self.counter = CountermoveTable::default();
+    }
+
     pub fn set_up(&mut self) {
         self.principal_variation = PrincipalVariation::default();
+        self.stack.clear();
         self.time_manager.reset_buffer();
         self.nodes = 0;
         self.ply = 0;
@@ -93,7 +162,38 @@ impl Thread {
         self.score = 0;
         self.depth = 0;
         self.completed = 0;
+        self.ties.clear();
+        self.root_ties.clear();
+        self.telemetry.reset();
+    }
+
+    /// Next number of the thread random generator.
+    #[inline(always)]
+    pub fn random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
     }
+
+    /// Adds the evaluation noise to a static evaluation. The offset depends
+    /// only on the position and the seed, so transpositions agree.
+    #[inline(always)]
+    pub fn noisy(&self, eval: i32, key: u64) -> i32 {
+        if self.noise == 0 {
+            return eval;
+        }
+        let span: u64 = 2 * self.noise as u64 + 1;
+        eval + (splitmix(key ^ self.noise_seed) % span) as i32 - self.noise
+    }
+}
+
+/// Scrambles a seed so that close values give unrelated generator states.
+fn splitmix(seed: u64) -> u64 {
+    let mut z: u64 = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) | 1
 }
 
 #[derive(Debug)]
@@ -101,8 +201,15 @@ pub struct ThreadPool {
     main: Thread,
     pool: Vec<Thread>,
     pub threads: usize,
+    shared_history: bool,
+    affinity: bool,
+    // Option values of the last search
+    options: Arc<EngineOptions>,
+    workers: WorkerPool,
     stop: Arc<AtomicBool>,
     nodes: Arc<AtomicU64>,
+    // Root and principal variation of the last completed search
+    previous: Option<(Board, Vec<Move>)>,
 }
 
 impl ThreadPool {
@@ -112,8 +219,13 @@ impl ThreadPool {
             main: Thread::smp(stop.clone(), nodes.clone(), 0),
             pool: Vec::new(),
             threads: 1,
+            shared_history: false,
+            affinity: false,
+            options: Arc::default(),
+            workers: WorkerPool::new(1),
             stop,
             nodes,
+            previous: None,
         }
     }
 
@@ -137,7 +249,192 @@ impl ThreadPool {
         });
 
         self.main = Thread::smp(self.stop.clone(), self.nodes.clone(), 0);
+        self.apply(&Arc::clone(&self.options));
         self.threads = threads.max(1);
+        if self.workers.len() != self.threads {
+            self.workers = self.spawn_workers();
+        }
+        self.link_history();
+    }
+
+    /// Clears the killers, history and counter moves of every thread.
+    pub fn clear(&mut self) {
+        self.main.clear();
+        for thread in self.pool.iter_mut() {
+            thread.clear();
+        }
+    }
+
+    /// Enables or disables sharing a single history table between all threads.
+    pub fn set_shared_history(&mut self, shared: bool) {
+        self.shared_history = shared;
+        self.link_history();
+    }
+
+    /// Hands the option values over to the threads. Reporting options only
+    /// concern the main thread, the search ones apply to every thread.
+    fn apply(&mut self, options: &Arc<EngineOptions>) {
+        self.options = Arc::clone(options);
+        self.main.telemetry.enabled = options.diagnostics;
+        self.main.show_refutations = options.show_refutations;
+        self.main.show_currline = options.show_currline;
+        self.main.output = options.output;
+        self.main.tie_break = options.tie_break;
+
+        let params: SearchParams = options.profile.params();
+        for thread in once(&mut self.main).chain(self.pool.iter_mut()) {
+            thread.params = params;
+            thread.max_depth = options.max_depth.clamp(1, MAX_PLY);
+            thread.tactical = options.tactical;
+        }
+    }
+
+    /// Leaves the given root moves out of the next searches.
+    pub fn set_excluded(&mut self, moves: Vec<Move>) {
+        for thread in self.pool.iter_mut() {
+            thread.excluded = moves.clone();
+        }
+        self.main.excluded = moves;
+    }
+
+    /// Enables or disables pinning every search worker to its own cpu.
+    pub fn set_affinity(&mut self, enabled: bool) {
+        if self.affinity != enabled {
+            self.affinity = enabled;
+            self.workers = self.spawn_workers();
+        }
+    }
+
+    fn spawn_workers(&self) -> WorkerPool {
+        if self.affinity {
+            WorkerPool::pinned(self.threads)
+        } else {
+            WorkerPool::new(self.threads)
+        }
+    }
+
+    fn mate_search(&self, position: &Position, mate: usize) -> Option<Move> {
+        let mut pns: ProofNumberSearch = ProofNumberSearch::new(position.board(), mate);
+        match pns.search(&self.stop, PNS_MAX_NODES) {
+            PnsResult::Proven(pv) => {
+                let time: u128 = self.main.time_manager.elapsed().as_millis().max(1);
+                let line: Vec<String> = pv.iter().map(|mv| mv.to_string()).collect();
+                println!(
+                    "info depth {} score mate {} time {} nodes {} pv {}",
+                    pv.len(),
+                    pv.len().div_ceil(2),
+                    time,
+                    pns.nodes(),
+                    line.join(" ")
+                );
+                pv.first().copied()
+            }
+            PnsResult::Disproven | PnsResult::Unknown => None,
+        }
+    }
+
+    /// Perft with the root moves split across the pool threads, sharing
+    /// `table` between them when given.
+    pub fn perft<const DIV: bool>(
+        &self,
+        position: &Position,
+        depth: u8,
+        table: Option<&PerftTable>,
+    ) -> u64 {
+        let start: Instant = Instant::now();
+        let board: Board = position.board();
+        let moves: MoveList = legal_moves!(&board);
+        let next: AtomicUsize = AtomicUsize::new(0);
+
+        // Each thread takes the next unclaimed root move and keeps its own counts
+        let results: Vec<(usize, u64)> = thread::scope(|s| {
+            let handles: Vec<ScopedJoinHandle<'_, Vec<(usize, u64)>>> = (0..self.threads)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut counts: Vec<(usize, u64)> = Vec::new();
+                        loop {
+                            let index: usize = next.fetch_add(1, Ordering::Relaxed);
+                            if index >= moves.len() {
+                                break counts;
+                            }
+                            let child: Board = board.make_move(moves[index]);
+                            let nodes: u64 = match table {
+                                _ if depth <= 1 => 1,
+                                Some(table) => hashed_perft(&child, depth - 1, table),
+                                None => inner_perft::<false>(&child, depth - 1),
+                            };
+                            counts.push((index, nodes));
+                        }
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let mut counts: Vec<u64> = vec![0; moves.len()];
+        for (index, nodes) in results {
+            counts[index] = nodes;
+        }
+
+        if DIV {
+            for (index, nodes) in counts.iter().enumerate() {
+                println!("{} -> {}", moves[index], nodes);
+            }
+        }
+
+        let total_nodes: u64 = counts.iter().sum();
+        let duration: Duration = start.elapsed();
+        let nps: f64 = total_nodes as f64 / duration.as_secs_f64();
+        println!("{total_nodes} nodes in {duration:?} -> {nps:.0} nodes/s");
+
+        total_nodes
+    }
+
+    /// Depth, score and principal variation of the main thread's last iteration.
+    pub fn principal_variation(&self) -> (usize, i32, Vec<Move>) {
+        (
+            self.main.completed,
+            self.main.score,
+            self.main.principal_variation.as_slice().to_vec(),
+        )
+    }
+
+    /// Whether the last search was started from this same position, in which
+    /// case its transposition table entries are still current.
+    pub fn repeats_root(&self, position: &Position) -> bool {
+        self.previous
+            .as_ref()
+            .is_some_and(|(board, _)| board.zobrist.0 == position.key())
+    }
+
+    /// Follows the previous principal variation from its root and returns the
+    /// move it expects once `board` is reached, if it ever is.
+    fn pv_hint(&self, board: &Board) -> Option<Move> {
+        let (root, pv) = self.previous.as_ref()?;
+        let mut current: Board = *root;
+        for &mv in pv.iter() {
+            if current.zobrist.0 == board.zobrist.0 {
+                let legal: MoveList = legal_moves!(board);
+                return legal.contains(&mv).then_some(mv);
+            }
+            current = current.make_move(mv);
+        }
+        None
+    }
+
+    fn link_history(&mut self) {
+        self.main.history = HistoryTable::default();
+        for thread in self.pool.iter_mut() {
+            thread.history = if self.shared_history {
+                self.main.history.share()
+            } else {
+                HistoryTable::default()
+            };
+        }
     }
 
     pub fn start_search(
@@ -145,46 +442,151 @@ impl ThreadPool {
         position: &mut Position,
         ttable: &TranspositionTable,
         time_control: TimeControl,
+        options: &Arc<EngineOptions>,
     ) -> Option<Move> {
+        self.apply(options);
         self.main.time_manager = TimeManager::new(
             self.stop.clone(),
             self.nodes.clone(),
             time_control,
             position.white(),
         );
+        self.main
+            .time_manager
+            .adjust_for_position(position.phase(), position.in_check());
+        self.main
+            .time_manager
+            .scale_soft(self.main.params.time_scale);
+        if let Some(limit) = self.options.max_nodes {
+            self.main.time_manager.set_max_nodes(limit);
+        }
 
-        let moves: MoveList = legal_moves!(&position.board());
-        if moves.is_empty() {
-            if position.in_check() {
+        match position.game_state() {
+            GameState::Checkmate => {
                 println!("info depth 0 score mate 0 time 0");
-            } else {
+                return None;
+            }
+            GameState::Stalemate => {
                 println!("info depth 0 score cp 0 time 0");
+                return None;
             }
-            return None;
+            _ => {}
         }
 
-        if moves.len() == 1 || self.main.time_manager.not_search() {
-            return Some(moves[0]);
+        // Terminal positions were handled above, so a legal move exists.
+        // Excluded root moves are never among the candidates.
+        let excluded: Vec<Move> = self.main.excluded.clone();
+        let allowed = |mv: &Move| !excluded.contains(mv);
+        let mut candidates: MoveList = legal_moves!(&position.board());
+        candidates.retain(allowed);
+        let first: Move = candidates.as_slice().first().copied()?;
+        let count: usize = candidates.len();
+        if count == 1 {
+            return Some(first);
         }
 
-        self.stop.store(false, Ordering::SeqCst);
-        self.nodes.store(0, Ordering::SeqCst);
+        // With only a couple of choices the search settles quickly
+        if count <= FEW_MOVES {
+            self.main.time_manager.scale_soft(FEW_MOVES_TIME_SCALE);
+        }
+
+        // Dead positions are adjudicated without burning clock time
+        if position.is_insufficient_material() && !self.main.time_manager.infinite() {
+            println!("info depth 0 score cp 0 time 0");
+            return Some(first);
+        }
+
+        // Mate search: an unproven mate falls back to alpha-beta on 2N - 1 plies
+        if let Some(mate) = time_control.mate
+            && self.options.proof_number_search
+            && excluded.is_empty()
+            && let Some(mv) = self.mate_search(position, mate as usize)
+        {
+            return Some(mv);
+        }
+
+        // In a time scramble a known opening position is answered instantly
+        if self.main.time_manager.panic()
+            && let Some(mv) = book::probe(&position.board()).filter(allowed)
+        {
+            println!("info string book move {}", mv);
+            return Some(mv);
+        }
+
+        // No time to search at all: play the TT move or the first legal move
+        if self.main.time_manager.not_search() {
+            return ttable
+                .probe(position.key(), 0)
+                .and_then(|entry| entry.legal_move(&position.board()))
+                .filter(allowed)
+                .or(Some(first));
+        }
 
-        let pcopy: Position = position.clone();
-        thread::scope(|s| {
-            s.spawn(|| {
-                self.main.set_up();
-                position.iterative_deepening::<MainThread>(&mut self.main, ttable);
-                self.stop.store(true, Ordering::SeqCst);
-            });
+        // Every thread draws from its own generator, reproducible for a given
+        // seed and position
+        let seed: u64 = self.options.tie_seed ^ position.key();
+        self.main.rng = splitmix(seed);
+        for thread in self.pool.iter_mut() {
+            thread.rng = splitmix(seed ^ thread.id as u64);
+        }
+
+        // Self-play openings are diversified by evaluation noise in the first plies
+        let noise: i32 = if position.ply() < self.options.noise_plies {
+            self.options.eval_noise
+        } else {
+            0
+        };
+        for thread in once(&mut self.main).chain(self.pool.iter_mut()) {
+            thread.noise = noise;
+            thread.noise_seed = seed;
+        }
+
+        // Scores from earlier searches fade; a shared table is decayed only once
+        self.main.history.decay();
+        if !self.shared_history {
             for thread in self.pool.iter_mut() {
-                s.spawn(|| {
-                    let mut position: Position = pcopy.clone();
-                    thread.set_up();
-                    position.iterative_deepening::<WorkerThread>(thread, ttable);
-                });
+                thread.history.decay();
             }
-        });
+        }
+
+        // A previous search whose PV reaches this root seeds its move ordering
+        let hint: Option<Move> = self.pv_hint(&position.board()).filter(allowed);
+        self.main.root_hint = hint;
+        for thread in self.pool.iter_mut() {
+            thread.root_hint = hint;
+        }
+
+        // The stop flag is armed by the caller when the `go` command is received,
+        // so a `stop` sent before the threads are spawned is never lost.
+        self.nodes.store(0, Ordering::SeqCst);
+
+        let pcopy: Position = position.search_view();
+        let main: &mut Thread = &mut self.main;
+        let stop: &AtomicBool = &self.stop;
+
+        let mut jobs: Vec<Job<'_>> = Vec::with_capacity(self.threads);
+        jobs.push(Box::new(|| {
+            main.set_up();
+            position.iterative_deepening::<MainThread>(main, ttable);
+
+            // In infinite mode the best move is only reported after `stop`
+            if main.time_manager.infinite() {
+                while !stop.load(Ordering::SeqCst) {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+            stop.store(true, Ordering::SeqCst);
+        }));
+        for thread in self.pool.iter_mut() {
+            let mut position: Position = pcopy.clone();
+            jobs.push(Box::new(move || {
+                thread.set_up();
+                position.iterative_deepening::<WorkerThread>(thread, ttable);
+            }));
+        }
+
+        // The searches run on the persistent workers, parked between searches
+        self.workers.execute(jobs);
 
         let threads = once(&self.main).chain(self.pool.iter());
 
@@ -199,20 +601,60 @@ impl ThreadPool {
             }
         }
 
-        let best_move: Option<Move> = freq
+        let mut best_move: Option<Move> = freq
             .into_iter()
             .max_by_key(|&(_, count)| count)
             .map(|(mv, _)| mv);
 
+        // Equal root moves of the main thread are drawn at random
+        let main: &mut Thread = &mut self.main;
+        if main.tie_break
+            && main.completed == max_depth
+            && best_move == main.principal_variation.as_slice().first().copied()
+            && !main.root_ties.is_empty()
+        {
+            let index: usize = (main.random() % (main.root_ties.len() as u64 + 1)) as usize;
+            if let Some(&mv) = main.root_ties.get(index) {
+                best_move = Some(mv);
+            }
+        }
+
+        self.previous = Some((
+            position.board(),
+            self.main.principal_variation.as_slice().to_vec(),
+        ));
+
+        // If the search was stopped before completing the first iteration,
+        // fall back to the TT move or to the first legal move.
         best_move
+            .or_else(|| {
+                ttable
+                    .probe(position.key(), 0)
+                    .and_then(|entry| entry.legal_move(&position.board()))
+                    .filter(allowed)
+            })
+            .or(Some(first))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{Position, ThreadPool, timer::TimeControl, transposition::TranspositionTable};
-    use laura_core::Move;
-    use std::sync::{Arc, atomic::AtomicBool};
+    use crate::{
+        Position, ThreadPool,
+        options::EngineOptions,
+        perft::PerftTable,
+        thread::Thread,
+        timer::TimeControl,
+        transposition::{BoundType, TranspositionTable},
+    };
+    use laura_core::{Board, Move, MoveType, Square};
+    use std::{
+        str::FromStr,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, AtomicU64, Ordering},
+        },
+    };
 
     #[test]
     fn test_best_move() {
@@ -220,11 +662,233 @@ mod test {
         let mut position: Position = Position::default();
         let mut ttable: TranspositionTable = TranspositionTable::default();
         ttable.resize(1);
-        let best: Option<Move> =
-            threadpool.start_search(&mut position, &ttable, TimeControl::Depth(4));
+        let options: Arc<EngineOptions> = Arc::default();
+        let best: Option<Move> = threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(4),
+            &options,
+        );
         println!("bestmove {}", best.unwrap());
     }
 
+    #[test]
+    fn test_max_depth() {
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let mut threadpool: ThreadPool = ThreadPool::new(stop.clone());
+        let mut position: Position = Position::default();
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        threadpool.resize(2);
+        let options: Arc<EngineOptions> = Arc::new(EngineOptions {
+            max_depth: 3,
+            ..EngineOptions::default()
+        });
+
+        // Whichever limit comes first stops the search
+        threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(8),
+            &options,
+        );
+        assert!(threadpool.pool.iter().all(|thread| thread.max_depth == 3));
+        assert_eq!(threadpool.main.completed, 3);
+
+        // The caller re-arms the stop flag before every search
+        stop.store(false, Ordering::SeqCst);
+        threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(2),
+            &options,
+        );
+        assert_eq!(threadpool.main.completed, 2);
+    }
+
+    #[test]
+    fn test_excluded_moves() {
+        let mut threadpool: ThreadPool = ThreadPool::new(Arc::new(AtomicBool::new(false)));
+        let mut position: Position = Position::default();
+        // Only one capture wins the queen, the other recapture is excluded
+        position.set_board(Board::from_str("4k3/8/8/3q4/2P1P3/8/8/4K3 w - - 0 1").unwrap());
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        let options: Arc<EngineOptions> = Arc::default();
+
+        let exd5: Move = position.board().find_move("e4d5").unwrap();
+        let cxd5: Move = position.board().find_move("c4d5").unwrap();
+        let best: Option<Move> = threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(4),
+            &options,
+        );
+        assert!(best == Some(exd5) || best == Some(cxd5));
+
+        threadpool.set_excluded(vec![exd5]);
+        let best: Option<Move> = threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(4),
+            &options,
+        );
+        assert_eq!(best, Some(cxd5));
+    }
+
+    #[test]
+    fn test_pv_hint() {
+        let mut threadpool: ThreadPool = ThreadPool::new(Arc::new(AtomicBool::new(false)));
+        let mut position: Position = Position::default();
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        let options: Arc<EngineOptions> = Arc::default();
+        assert!(!threadpool.repeats_root(&position));
+
+        threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(6),
+            &options,
+        );
+        let (_, _, pv) = threadpool.principal_variation();
+        assert!(pv.len() >= 3);
+        assert!(threadpool.repeats_root(&position));
+        assert_eq!(threadpool.pv_hint(&position.board()), Some(pv[0]));
+
+        // Playing along the PV keeps the recall, leaving it does not
+        let along: Board = position.board().make_move(pv[0]).make_move(pv[1]);
+        assert_eq!(threadpool.pv_hint(&along), Some(pv[2]));
+        let other: Board = Board::from_str("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert_eq!(threadpool.pv_hint(&other), None);
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let mut threadpool: ThreadPool = ThreadPool::new(stop.clone());
+        let mut position: Position = Position::default();
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        let options: Arc<EngineOptions> = Arc::new(EngineOptions {
+            tie_break: true,
+            tie_seed: 7,
+            ..EngineOptions::default()
+        });
+
+        // The drawn move is either the PV move or one verified as tied with it
+        let best: Option<Move> = threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(5),
+            &options,
+        );
+        let (_, _, pv) = threadpool.principal_variation();
+        assert!(best == pv.first().copied() || threadpool.main.root_ties.contains(&best.unwrap()));
+
+        // The same seed and position always draw the same move
+        ttable.clear();
+        threadpool.clear();
+        threadpool.previous = None;
+        stop.store(false, Ordering::SeqCst);
+        let again: Option<Move> = threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(5),
+            &options,
+        );
+        assert_eq!(best, again);
+    }
+
+    #[test]
+    fn test_eval_noise() {
+        let mut thread: Thread = Thread::smp(
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(AtomicU64::new(0)),
+            1,
+        );
+        assert_eq!(thread.noisy(35, 0xABCD), 35);
+
+        thread.noise = 20;
+        thread.noise_seed = 3;
+        let noisy: i32 = thread.noisy(35, 0xABCD);
+        assert!((15..=55).contains(&noisy));
+        assert_eq!(thread.noisy(35, 0xABCD), noisy);
+
+        // Past the noisy plies the search is the plain one
+        let mut threadpool: ThreadPool = ThreadPool::new(Arc::new(AtomicBool::new(false)));
+        let mut position: Position = Position::default();
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        let options: Arc<EngineOptions> = Arc::new(EngineOptions {
+            eval_noise: 50,
+            noise_plies: 1,
+            ..EngineOptions::default()
+        });
+        threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(3),
+            &options,
+        );
+        assert_eq!(threadpool.main.noise, 50);
+
+        position.do_move(position.board().find_move("e2e4").unwrap());
+        threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(3),
+            &options,
+        );
+        assert_eq!(threadpool.main.noise, 0);
+    }
+
+    #[test]
+    fn test_rejected_tt_move() {
+        let mut threadpool: ThreadPool = ThreadPool::new(Arc::new(AtomicBool::new(false)));
+        let mut position: Position = Position::default();
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        let options: Arc<EngineOptions> = Arc::default();
+
+        // A pawn jumping three squares can only come from a corrupted entry
+        let corrupted: Move = Move::new(Square::E2, Square::E5, MoveType::Quiet);
+        ttable.insert(
+            position.key(),
+            corrupted,
+            0,
+            0,
+            1,
+            BoundType::UpperBound,
+            false,
+            0,
+        );
+
+        let best: Option<Move> = threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(1),
+            &options,
+        );
+        assert_ne!(best, Some(corrupted));
+        assert_eq!(threadpool.main.telemetry.rejected_tt_moves, 1);
+    }
+
+    #[test]
+    fn test_panic_book() {
+        let mut threadpool: ThreadPool = ThreadPool::new(Arc::new(AtomicBool::new(false)));
+        let mut position: Position = Position::default();
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        let options: Arc<EngineOptions> = Arc::default();
+
+        let scramble: TimeControl = TimeControl::from_str("wtime 60 btime 60").unwrap();
+        let best: Option<Move> =
+            threadpool.start_search(&mut position, &ttable, scramble, &options);
+        assert_eq!(best, position.board().find_move("e2e4"));
+        assert_eq!(threadpool.main.completed, 0);
+    }
+
     #[test]
     fn test_resize() {
         let mut threadpool = ThreadPool::new(Arc::new(AtomicBool::new(false)));
@@ -237,4 +901,41 @@ mod test {
             println!("ID: {}", t.id)
         }
     }
+
+    #[test]
+    fn test_dead_position() {
+        let mut threadpool: ThreadPool = ThreadPool::new(Arc::new(AtomicBool::new(false)));
+        let mut position: Position = Position::default();
+        position.set_board(Board::from_str("8/8/4k3/8/8/3BK3/8/8 w - - 0 1").unwrap());
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        let options: Arc<EngineOptions> = Arc::default();
+        let best: Option<Move> = threadpool.start_search(
+            &mut position,
+            &ttable,
+            TimeControl::fixed_depth(20),
+            &options,
+        );
+        assert!(best.is_some());
+        assert_eq!(threadpool.main.completed, 0);
+    }
+
+    #[test]
+    fn test_parallel_perft() {
+        let mut threadpool: ThreadPool = ThreadPool::new(Arc::new(AtomicBool::new(false)));
+        threadpool.resize(4);
+        let position: Position = Position::default();
+        assert_eq!(threadpool.perft::<false>(&position, 1, None), 20);
+        assert_eq!(threadpool.perft::<true>(&position, 4, None), 197_281);
+        assert_eq!(
+            threadpool.perft::<false>(&position, 4, None),
+            position.perft(4)
+        );
+
+        let table: PerftTable = PerftTable::new(1).unwrap();
+        assert_eq!(
+            threadpool.perft::<false>(&position, 5, Some(&table)),
+            4_865_609
+        );
+    }
 }
@@ -22,9 +22,12 @@
 //! Timer implementation
 
 use crate::config::{
-    DEFAULT_MOVESTOGO, INCREMENT_TIME_BASE, MINIMUM_TIME, MOVE_OVERHEAD, OPTIMAL_TIME_BASE,
+    BEHIND_CLOCK_RATIO, BEHIND_TIME_SCALE, CHECK_TIME_SCALE, DEFAULT_MOVESTOGO, ENDGAME_TIME_SCALE,
+    HEALTHY_INCREMENTS, INCREMENT_TIME_BASE, MIDDLEGAME_TIME_SCALE, MINIMUM_TIME, MOVE_OVERHEAD,
+    NODE_CHUNK, NODE_CHUNK_DIVISOR, OPTIMAL_TIME_BASE, PANIC_DIVISOR, PANIC_TIME,
 };
 use std::{
+    fmt,
     str::{FromStr, SplitWhitespace},
     sync::{
         Arc,
@@ -33,19 +36,27 @@ use std::{
     time::{Duration, Instant},
 };
 
-#[derive(Debug, Clone, Copy)]
-pub enum TimeControl {
-    Depth(u32),
-    MoveTime(u64),
-    DynamicTime {
-        wtime: u64,
-        btime: u64,
-        winc: Option<u64>,
-        binc: Option<u64>,
-        movestogo: Option<u64>,
-    },
-    Nodes(u64),
-    Infinite,
+/// Limits of a `go` command. Any combination can be given and the search stops
+/// at whichever limit triggers first.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub depth: Option<u32>,
+    pub movetime: Option<u64>,
+    pub clock: Option<Clock>,
+    pub nodes: Option<u64>,
+    pub mate: Option<u32>,
+    /// Searches until `stop`, whatever the other limits
+    pub infinite: bool,
+}
+
+/// Remaining time and increments of both sides, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    pub wtime: u64,
+    pub btime: u64,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -55,75 +66,124 @@ pub enum TimeParserError {
     UnknownParameter(String),
 }
 
+impl fmt::Display for TimeParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingValue(key) => write!(f, "missing go value: {key}"),
+            Self::InvalidValue => write!(f, "invalid go value"),
+            Self::UnknownParameter(token) => write!(f, "unknown go parameter '{token}'"),
+        }
+    }
+}
+
+impl std::error::Error for TimeParserError {}
+
 impl FromStr for TimeControl {
     type Err = TimeParserError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut tokens: SplitWhitespace<'_> = s.split_whitespace();
 
-        let mut depth: Option<u32> = None;
-        let mut movetime: Option<u64> = None;
+        let mut time_control: TimeControl = TimeControl::default();
         let mut wtime: Option<u64> = None;
         let mut btime: Option<u64> = None;
         let mut winc: Option<u64> = None;
         let mut binc: Option<u64> = None;
         let mut movestogo: Option<u64> = None;
-        let mut nodes: Option<u64> = None;
 
         while let Some(token) = tokens.next() {
             match token {
-                "depth" => depth = Some(parse_value::<u32>(&mut tokens, "depth")?),
-                "movetime" => movetime = Some(parse_value::<u64>(&mut tokens, "movetime")?),
+                "depth" => time_control.depth = Some(parse_value::<u32>(&mut tokens, "depth")?),
+                "movetime" => {
+                    time_control.movetime = Some(parse_value::<u64>(&mut tokens, "movetime")?)
+                }
                 "wtime" => wtime = Some(parse_value::<u64>(&mut tokens, "wtime")?),
                 "btime" => btime = Some(parse_value::<u64>(&mut tokens, "btime")?),
                 "winc" => winc = Some(parse_value::<u64>(&mut tokens, "winc")?),
                 "binc" => binc = Some(parse_value::<u64>(&mut tokens, "binc")?),
                 "movestogo" => movestogo = Some(parse_value::<u64>(&mut tokens, "movestogo")?),
-                "nodes" => nodes = Some(parse_value::<u64>(&mut tokens, "nodes")?),
-                "infinite" => return Ok(Self::Infinite),
+                "nodes" => time_control.nodes = Some(parse_value::<u64>(&mut tokens, "nodes")?),
+                "mate" => time_control.mate = Some(parse_value::<u32>(&mut tokens, "mate")?),
+                "infinite" => time_control.infinite = true,
                 _ => return Err(TimeParserError::UnknownParameter(token.to_string())),
             };
         }
 
-        if let Some(depth) = depth {
-            return Ok(Self::Depth(depth));
-        }
-
-        if let Some(movetime) = movetime {
-            return Ok(Self::MoveTime(movetime));
+        if time_control.mate == Some(0) {
+            return Err(TimeParserError::InvalidValue);
         }
 
-        if let (Some(wtime), Some(btime)) = (wtime, btime) {
-            if (winc.is_some() && binc.is_some()) || (winc.is_none() && binc.is_none()) {
-                return Ok(Self::DynamicTime {
+        match (wtime, btime) {
+            (Some(wtime), Some(btime)) if winc.is_some() == binc.is_some() => {
+                time_control.clock = Some(Clock {
                     wtime,
                     btime,
                     winc,
                     binc,
                     movestogo,
                 });
-            } else {
+            }
+            (None, None) => {}
+            _ => {
                 return Err(TimeParserError::MissingValue(
                     "Missing some (wtime, btime, winc, binc, or movestogo)".to_string(),
                 ));
             }
         }
 
-        if let Some(nodes) = nodes {
-            return Ok(Self::Nodes(nodes));
+        // `go infinite` searches until `stop`, so the other limits do not apply
+        if time_control.infinite {
+            return Ok(Self::INFINITE);
+        }
+
+        if time_control == Self::default() {
+            return Err(TimeParserError::InvalidValue);
         }
 
-        Err(TimeParserError::InvalidValue)
+        Ok(time_control)
     }
 }
 
 impl TimeControl {
+    pub const INFINITE: Self = Self {
+        depth: None,
+        movetime: None,
+        clock: None,
+        nodes: None,
+        mate: None,
+        infinite: true,
+    };
+
+    /// Searches to `depth` plies, with no other limit.
+    pub fn fixed_depth(depth: u32) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+
+    /// Searches `nodes` nodes, with no other limit.
+    pub fn fixed_nodes(nodes: u64) -> Self {
+        Self {
+            nodes: Some(nodes),
+            ..Self::default()
+        }
+    }
+
+    /// Deepest iteration allowed by the depth and mate limits.
     pub fn depth(&self) -> Option<usize> {
-        match self {
-            TimeControl::Depth(depth) => Some(*depth as usize),
-            _ => None,
+        // Mate in N is found within 2N - 1 plies
+        let mate: Option<usize> = self.mate.map(|mate| 2 * mate as usize - 1);
+        match (self.depth.map(|depth| depth as usize), mate) {
+            (Some(depth), Some(mate)) => Some(depth.min(mate)),
+            (depth, mate) => depth.or(mate),
         }
     }
+
+    /// Whether the search is limited by the clock or a fixed move time.
+    pub fn timed(&self) -> bool {
+        self.movetime.is_some() || self.clock.is_some()
+    }
 }
 
 fn parse_value<T: FromStr>(
@@ -153,6 +213,10 @@ pub struct TimeManager {
     nodes: Arc<AtomicU64>,
     // Node Count Buffer
     buffer: u64,
+    // Panic Mode (remaining time below PANIC_TIME)
+    panic: bool,
+    // Safety cap on the nodes of a single search, whatever the time control
+    max_nodes: u64,
 }
 
 impl TimeManager {
@@ -162,37 +226,39 @@ impl TimeManager {
         time_control: TimeControl,
         white: bool,
     ) -> Self {
-        let (soft_limit, hard_limit) = match time_control {
-            TimeControl::Depth(_) => (Duration::ZERO, Duration::ZERO),
-            TimeControl::MoveTime(time) => (
-                Duration::from_millis(time - MOVE_OVERHEAD.min(time)),
-                Duration::from_millis(time - MOVE_OVERHEAD.min(time)),
-            ),
-            TimeControl::DynamicTime {
-                wtime,
-                btime,
-                winc,
-                binc,
-                movestogo,
-            } => {
-                let (remaining, increment) = if white {
-                    match winc {
-                        Some(winc) => (wtime, winc),
-                        None => (wtime, 0),
-                    }
-                } else {
-                    match binc {
-                        Some(binc) => (btime, binc),
-                        None => (btime, 0),
-                    }
-                };
-
-                let (soft, hard) = calculate_time(remaining, increment, movestogo);
-
-                (Duration::from_millis(soft), Duration::from_millis(hard))
-            }
-            TimeControl::Nodes(_) => (Duration::ZERO, Duration::ZERO),
-            TimeControl::Infinite => (Duration::ZERO, Duration::ZERO),
+        let mut panic: bool = false;
+        let mut soft: u64 = u64::MAX;
+        let mut hard: u64 = u64::MAX;
+
+        if let Some(clock) = time_control.clock {
+            let (remaining, increment, opponent) = if white {
+                (clock.wtime, clock.winc.unwrap_or(0), clock.btime)
+            } else {
+                (clock.btime, clock.binc.unwrap_or(0), clock.wtime)
+            };
+
+            // With almost no time left the usual limits (and the minimum time)
+            // could exceed the clock, so only a small slice of it is spent.
+            panic = remaining < PANIC_TIME;
+            (soft, hard) = if panic {
+                let time: u64 = remaining / PANIC_DIVISOR;
+                (time, time)
+            } else {
+                calculate_time(remaining, increment, opponent, clock.movestogo)
+            };
+        }
+
+        // A fixed move time caps both limits, also on top of the clock
+        if let Some(time) = time_control.movetime {
+            let time: u64 = time - MOVE_OVERHEAD.min(time);
+            soft = soft.min(time);
+            hard = hard.min(time);
+        }
+
+        let (soft_limit, hard_limit) = if time_control.timed() {
+            (Duration::from_millis(soft), Duration::from_millis(hard))
+        } else {
+            (Duration::ZERO, Duration::ZERO)
         };
 
         Self {
@@ -203,6 +269,8 @@ impl TimeManager {
             stop,
             nodes,
             buffer: 0,
+            panic,
+            max_nodes: u64::MAX,
         }
     }
 
@@ -226,18 +294,18 @@ impl TimeManager {
         self.nodes.load(Ordering::SeqCst)
     }
 
+    /// Stops the search once `limit` nodes are reached, even in infinite mode.
+    pub fn set_max_nodes(&mut self, limit: u64) {
+        self.max_nodes = limit;
+    }
+
     pub fn stop_soft(&mut self) -> bool {
         if self.stop.load(Ordering::SeqCst) {
             return true;
         }
 
-        let stop: bool = match self.time_control {
-            TimeControl::MoveTime(_) | TimeControl::DynamicTime { .. } => {
-                self.elapsed() >= self.soft_limit
-            }
-            TimeControl::Nodes(control_nodes) => self.nodes() >= control_nodes,
-            _ => false,
-        };
+        let stop: bool = (self.time_control.timed() && self.elapsed() >= self.soft_limit)
+            || self.nodes() >= self.node_limit();
 
         if stop {
             self.stop.store(true, Ordering::SeqCst);
@@ -252,20 +320,20 @@ impl TimeManager {
         }
         let searched: u64 = nodes - self.buffer;
 
-        if searched > 1024 {
+        // Nodes are published to the shared counter in chunks. Node limits shrink
+        // the chunk as the limit gets closer so it is never overshot, and in
+        // panic mode the clock is checked on every node.
+        let chunk: u64 = if self.panic { 0 } else { NODE_CHUNK }
+            .min(self.node_limit().saturating_sub(self.nodes()) / NODE_CHUNK_DIVISOR);
+
+        if searched > chunk {
             self.nodes.fetch_add(searched, Ordering::SeqCst);
             self.buffer = nodes;
         }
 
-        let stop: bool = match self.time_control {
-            TimeControl::Depth(_) | TimeControl::Infinite => self.stop.load(Ordering::SeqCst),
-            TimeControl::MoveTime(time) => {
-                let past_time: bool = self.elapsed().as_millis() as u64 >= time;
-                past_time
-            }
-            TimeControl::DynamicTime { .. } => self.elapsed() >= self.hard_limit,
-            TimeControl::Nodes(control_nodes) => self.nodes() >= control_nodes,
-        };
+        let stop: bool = (self.time_control.timed() && self.elapsed() >= self.hard_limit)
+            || self.nodes() >= self.node_limit()
+            || self.stop.load(Ordering::SeqCst);
 
         if stop {
             self.stop.store(true, Ordering::SeqCst);
@@ -275,20 +343,56 @@ impl TimeManager {
     }
 
     pub fn not_search(&self) -> bool {
-        match self.time_control {
-            TimeControl::MoveTime(_) | TimeControl::DynamicTime { .. } => {
-                self.hard_limit == Duration::ZERO
-            }
-            _ => false,
+        self.time_control.timed() && self.hard_limit == Duration::ZERO
+    }
+
+    /// Nodes allowed by `go nodes` and the MaxNodes cap, whichever is lower.
+    #[inline(always)]
+    fn node_limit(&self) -> u64 {
+        self.time_control
+            .nodes
+            .unwrap_or(u64::MAX)
+            .min(self.max_nodes)
+    }
+
+    /// Scales the soft limit by the game phase (0 endgame, 128 middlegame),
+    /// giving more time to middlegames and positions in check.
+    pub fn adjust_for_position(&mut self, phase: i32, in_check: bool) {
+        let phase: u64 = phase.clamp(0, 128) as u64;
+        let mut scale: u64 =
+            (ENDGAME_TIME_SCALE * (128 - phase) + MIDDLEGAME_TIME_SCALE * phase) / 128;
+        if in_check {
+            scale = scale * CHECK_TIME_SCALE / 100;
+        }
+        self.scale_soft(scale);
+    }
+
+    /// Scales the soft limit by `percent`, never beyond the hard limit.
+    pub fn scale_soft(&mut self, percent: u64) {
+        if self.time_control.clock.is_some() && !self.panic {
+            self.soft_limit = (self.soft_limit * percent as u32 / 100).min(self.hard_limit);
         }
     }
 
+    pub fn panic(&self) -> bool {
+        self.panic
+    }
+
+    pub fn infinite(&self) -> bool {
+        self.time_control.infinite
+    }
+
     pub fn reset_buffer(&mut self) {
         self.buffer = 0;
     }
 }
 
-pub fn calculate_time(remaining: u64, increment: u64, movestogo: Option<u64>) -> (u64, u64) {
+pub fn calculate_time(
+    remaining: u64,
+    increment: u64,
+    opponent: u64,
+    movestogo: Option<u64>,
+) -> (u64, u64) {
     let max_time: u64 = remaining.saturating_sub(MOVE_OVERHEAD);
 
     let limit_time: u64 = if let Some(movestogo) = movestogo {
@@ -298,18 +402,155 @@ pub fn calculate_time(remaining: u64, increment: u64, movestogo: Option<u64>) ->
     };
 
     let hard_time: u64 = limit_time.max(MINIMUM_TIME);
-    let soft_time: u64 = (hard_time.min(max_time) * OPTIMAL_TIME_BASE / 100).max(MINIMUM_TIME);
+    let mut soft_time: u64 = hard_time.min(max_time) * OPTIMAL_TIME_BASE / 100;
+
+    // Far behind on the clock, iterations stop earlier to catch up
+    if remaining * 100 < opponent * BEHIND_CLOCK_RATIO {
+        soft_time = soft_time * BEHIND_TIME_SCALE / 100;
+    }
 
-    (soft_time, hard_time)
+    // With a healthy clock the increment is spent rather than hoarded
+    if increment > 0 && remaining >= increment * HEALTHY_INCREMENTS {
+        soft_time = soft_time.max(increment.min(max_time));
+    }
+
+    let soft_time: u64 = soft_time.max(MINIMUM_TIME);
+    (soft_time, hard_time.max(soft_time))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::timer::calculate_time;
+    use crate::{
+        config::BEHIND_TIME_SCALE,
+        timer::{TimeControl, TimeManager, calculate_time},
+    };
+    use std::{
+        str::FromStr,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, AtomicU64, Ordering},
+        },
+        time::Duration,
+    };
 
     #[test]
     fn test() {
-        let (soft, hard) = calculate_time(20, 0, None);
+        let (soft, hard) = calculate_time(20, 0, 20, None);
         println!("Soft: {}, Hard: {}", soft, hard);
     }
+
+    #[test]
+    fn test_increment_usage() {
+        // A large increment on a healthy clock is spent, not hoarded
+        let (soft, hard) = calculate_time(60_000, 5_000, 60_000, None);
+        assert!(soft >= 5_000);
+        assert!(soft <= hard);
+
+        // Without enough time in the bank the increment floor is not applied
+        let (low, _) = calculate_time(20_000, 5_000, 20_000, None);
+        assert!(low < 5_000);
+
+        // Far behind on the clock the soft limit shrinks
+        let (even, _) = calculate_time(30_000, 0, 30_000, None);
+        let (behind, _) = calculate_time(30_000, 0, 90_000, None);
+        assert!(behind < even);
+        assert_eq!(behind, even * BEHIND_TIME_SCALE / 100);
+    }
+
+    #[test]
+    fn test_panic_mode() {
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let nodes: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let time_control: TimeControl = TimeControl::from_str("wtime 80 btime 5000").unwrap();
+
+        let white: TimeManager = TimeManager::new(stop.clone(), nodes.clone(), time_control, true);
+        assert!(white.panic());
+        assert!(white.hard_limit < Duration::from_millis(80));
+
+        let black: TimeManager = TimeManager::new(stop, nodes, time_control, false);
+        assert!(!black.panic());
+    }
+
+    #[test]
+    fn test_phase_scaling() {
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let nodes: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let time_control: TimeControl = TimeControl::from_str("wtime 60000 btime 60000").unwrap();
+        let base: TimeManager = TimeManager::new(stop, nodes, time_control, true);
+
+        let mut endgame: TimeManager = base.clone();
+        endgame.adjust_for_position(0, false);
+        let mut middlegame: TimeManager = base.clone();
+        middlegame.adjust_for_position(128, false);
+        let mut check: TimeManager = base.clone();
+        check.adjust_for_position(128, true);
+
+        assert!(endgame.soft_limit < base.soft_limit);
+        assert!(middlegame.soft_limit > base.soft_limit);
+        assert!(check.soft_limit >= middlegame.soft_limit);
+        assert!(check.soft_limit <= check.hard_limit);
+    }
+
+    #[test]
+    fn test_exact_nodes() {
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let nodes: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let mut time_manager: TimeManager =
+            TimeManager::new(stop, nodes, TimeControl::fixed_nodes(5_000), true);
+
+        let mut searched: u64 = 0;
+        while !time_manager.stop_hard(searched) {
+            searched += 1;
+        }
+        assert_eq!(searched, 5_000);
+        assert_eq!(time_manager.nodes(), 5_000);
+    }
+
+    #[test]
+    fn test_max_nodes() {
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let nodes: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let mut time_manager: TimeManager =
+            TimeManager::new(stop.clone(), nodes, TimeControl::INFINITE, true);
+        time_manager.set_max_nodes(3_000);
+
+        let mut searched: u64 = 0;
+        while !time_manager.stop_hard(searched) {
+            searched += 1;
+        }
+        assert_eq!(searched, 3_000);
+        assert!(stop.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_combined_limits() {
+        let combined: TimeControl = TimeControl::from_str("depth 20 movetime 5000").unwrap();
+        assert_eq!(combined.depth(), Some(20));
+        assert_eq!(combined.movetime, Some(5_000));
+
+        let clock: TimeControl =
+            TimeControl::from_str("nodes 1000000 wtime 60000 btime 60000 winc 0 binc 0").unwrap();
+        assert_eq!(clock.nodes, Some(1_000_000));
+        assert!(clock.clock.is_some());
+
+        // The tighter of movetime and the clock wins
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let nodes: Arc<AtomicU64> = Arc::new(AtomicU64::new(0));
+        let capped: TimeControl =
+            TimeControl::from_str("wtime 600000 btime 600000 movetime 200").unwrap();
+        let time_manager: TimeManager = TimeManager::new(stop, nodes, capped, true);
+        assert!(time_manager.hard_limit <= Duration::from_millis(200));
+
+        // Mate limits the depth too, and the lower of the two is kept
+        let mate: TimeControl = TimeControl::from_str("mate 3 depth 10").unwrap();
+        assert_eq!(mate.depth(), Some(5));
+
+        assert!(TimeControl::from_str("").is_err());
+        assert!(TimeControl::from_str("mate 0").is_err());
+        assert!(TimeControl::from_str("wtime 1000").is_err());
+        assert_eq!(
+            TimeControl::from_str("infinite depth 5").unwrap(),
+            TimeControl::INFINITE
+        );
+    }
 }
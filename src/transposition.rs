@@ -23,6 +23,8 @@
 
 use std::{
     alloc::{Layout, alloc_zeroed},
+    fmt,
+    io::{self, Read, Write},
     mem::MaybeUninit,
     ptr,
     sync::atomic::{AtomicU8, AtomicU16, AtomicU64, Ordering},
@@ -31,9 +33,14 @@ use std::{
 
 use laura_core::{AllMoves, Board, Move, gen_moves};
 
-use crate::config::{
-    AGE_MASK, AGE_OFFSET, BOUND_OFFSET, BOUNDTYPE_MASK, DATA_MASK, ENTRIES_PER_CELL, KEY_MASK,
-    KEY_WRAPPER_MASK, MEGABYTE, PV_NODE_MASK, TTMATE,
+use crate::{
+    config::{
+        AGE_MASK, AGE_OFFSET, BOUND_OFFSET, BOUNDTYPE_MASK, CLEAR_CHUNK_SIZE, DATA_MASK,
+        ENTRIES_PER_CELL, HASHFULL_SAMPLE, KEY_MASK, KEY_WRAPPER_MASK, MEGABYTE, PV_NODE_MASK,
+        TTMATE,
+    },
+    position::{is_pseudo_sane, quick_legality},
+    storage::{SharedMemoryError, SharedStorage, TableStorage},
 };
 
 fn normalize_score(score: i32, ply: i32) -> i16 {
@@ -108,14 +115,20 @@ impl EntryHit {
     #[inline(always)]
     /// Directly return the best legal move, if one exists, from an EntryHit.
     pub fn legal_move(&self, board: &Board) -> Option<Move> {
-        if !self.mv.is_null() && gen_moves::<AllMoves>(board).contains(&self.mv) {
-            Some(self.mv)
-        } else {
-            None
+        if self.mv.is_null() || !is_pseudo_sane(self.mv) {
+            return None;
         }
+
+        // Piece moves are validated directly, the rest through the generator
+        let legal: bool = quick_legality(board, self.mv)
+            .unwrap_or_else(|| gen_moves::<AllMoves>(board).contains(&self.mv));
+        legal.then_some(self.mv)
     }
 }
 
+/// Bytes of an entry written to disk, without the padding
+const ENTRY_BYTES: usize = 10;
+
 /// 10 Bytes Entry
 #[repr(C, align(8))]
 #[derive(Debug, Clone, Copy)]
@@ -217,70 +230,189 @@ impl Cell {
     }
 }
 
+/// The memory for a table of the requested size could not be allocated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationError {
+    pub megabytes: usize,
+}
+
+impl fmt::Display for AllocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to allocate {} MB for the transposition table",
+            self.megabytes
+        )
+    }
+}
+
+impl std::error::Error for AllocationError {}
+
 #[derive(Debug, Default)]
 pub struct TranspositionTable {
-    entries: Vec<Cell>,
+    entries: Box<dyn TableStorage>,
+    shared: Option<String>,
     age: AtomicU8,
+    #[cfg(feature = "tt-verify")]
+    verification: Verification,
+}
+
+/// Full keys stored alongside the entries, with the probe statistics.
+#[cfg(feature = "tt-verify")]
+#[derive(Debug, Default)]
+struct Verification {
+    keys: Vec<AtomicU64>,
+    hits: AtomicU64,
+    collisions: AtomicU64,
+}
+
+#[cfg(feature = "tt-verify")]
+impl Verification {
+    fn reset(&mut self, slots: usize) {
+        self.keys = (0..slots).map(|_| AtomicU64::new(0)).collect();
+        self.hits.store(0, Ordering::Relaxed);
+        self.collisions.store(0, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn store(&self, slot: usize, key: u64) {
+        self.keys[slot].store(key, Ordering::Relaxed);
+    }
+
+    /// Whether a 16-bit key match is a real hit. Slots without a known
+    /// full key, such as those loaded from a file, are trusted.
+    #[inline(always)]
+    fn check(&self, slot: usize, key: u64) -> bool {
+        let stored: u64 = self.keys[slot].load(Ordering::Relaxed);
+        if stored == 0 || stored == key {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            true
+        } else {
+            self.collisions.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    }
 }
 
 impl TranspositionTable {
+    /// Resizes the table, keeping the previous size if the allocation fails.
     pub fn resize(&mut self, megabytes: usize) {
-        let len: usize = megabytes * MEGABYTE / size_of::<Cell>();
-        let layout: Layout = Layout::array::<Cell>(len).expect("Invalid layout");
+        if let Err(error) = self.try_resize(megabytes) {
+            eprintln!(
+                "info string [error] {}, keeping {} MB.",
+                error,
+                self.megabytes()
+            );
+        }
+    }
 
-        // Allocate zeroed memory manually
-        let raw_ptr: *mut Cell = unsafe { alloc_zeroed(layout) as *mut Cell };
+    /// Allocates a new zeroed table, or maps the shared segment when one is
+    /// set. On failure the current table is left untouched.
+    pub fn try_resize(&mut self, megabytes: usize) -> Result<(), AllocationError> {
+        let error: AllocationError = AllocationError { megabytes };
+        if let Some(name) = &self.shared {
+            let storage: SharedStorage = SharedStorage::open(name, megabytes).map_err(|_| error)?;
+            self.set_storage(Box::new(storage));
+            return Ok(());
+        }
 
-        if raw_ptr.is_null() {
-            panic!("Failed to allocate memory");
+        // SAFETY: a cell of zeroed atomics is an empty cell
+        let final_vec: Vec<Cell> = unsafe { allocate_zeroed::<Cell>(megabytes)? };
+
+        self.set_storage(Box::new(final_vec));
+        Ok(())
+    }
+
+    /// Backs the table with the named shared-memory segment, or with private
+    /// memory again for `None`, keeping the current size.
+    pub fn set_shared(&mut self, name: Option<&str>) -> Result<(), SharedMemoryError> {
+        let megabytes: usize = self.megabytes().max(1);
+        match name {
+            Some(name) => {
+                let storage: SharedStorage = SharedStorage::open(name, megabytes)?;
+                self.shared = Some(name.to_string());
+                self.set_storage(Box::new(storage));
+            }
+            None if self.shared.is_some() => {
+                self.shared = None;
+                self.resize(megabytes);
+            }
+            None => {}
         }
+        Ok(())
+    }
 
-        // SAFETY: We allocated len * size_of::<Cell>() bytes, zeroed
-        let final_vec: Vec<Cell> = unsafe { Vec::from_raw_parts(raw_ptr, len, len) };
+    /// Name of the shared-memory segment backing the table, if any.
+    pub fn shared(&self) -> Option<&str> {
+        self.shared.as_deref()
+    }
 
-        self.entries = final_vec;
+    fn set_storage(&mut self, storage: Box<dyn TableStorage>) {
+        self.entries = storage;
+        #[cfg(feature = "tt-verify")]
+        self.verification
+            .reset(self.cells().len() * ENTRIES_PER_CELL);
     }
 
-    pub fn clear(&mut self, threads: usize) {
-        let len: usize = self.entries.len();
-        let ptr: *mut MaybeUninit<u8> = self.entries.as_mut_ptr() as *mut MaybeUninit<u8>;
+    #[inline(always)]
+    fn cells(&self) -> &[Cell] {
+        self.entries.cells()
+    }
 
-        self.parallel_clear(ptr, threads, len);
-        self.age.store(0, Ordering::Relaxed);
+    /// Verified hits and detected key collisions since the table was last cleared.
+    #[cfg(feature = "tt-verify")]
+    pub fn collision_stats(&self) -> (u64, u64) {
+        (
+            self.verification.hits.load(Ordering::Relaxed),
+            self.verification.collisions.load(Ordering::Relaxed),
+        )
     }
 
-    fn parallel_clear(&self, ptr: *mut MaybeUninit<u8>, threads: usize, len: usize) {
-        unsafe {
-            let base_ptr: *mut u8 = ptr as *mut u8;
-            let total_bytes: usize = len * size_of::<Cell>();
-            let chunk_size: usize = total_bytes.div_ceil(threads);
-
-            thread::scope(|s| {
-                for chunk_offset in (0..total_bytes).step_by(chunk_size) {
-                    let start_addr: usize = base_ptr.add(chunk_offset) as usize;
-                    let size: usize = chunk_size.min(total_bytes - chunk_offset);
-
-                    s.spawn(move || {
-                        let start: *mut u8 = start_addr as *mut u8;
-                        ptr::write_bytes(start, 0, size);
-                    });
-                }
-            })
+    /// Current size of the table in megabytes.
+    pub fn megabytes(&self) -> usize {
+        size_of_val(self.cells()) / MEGABYTE
+    }
+
+    /// Zeroes the table. Large tables are cleared in parallel, using as many
+    /// threads as there are cores and chunks of at least `CLEAR_CHUNK_SIZE`.
+    pub fn clear(&mut self) {
+        let total_bytes: usize = size_of_val(self.cells());
+        let ptr: *mut MaybeUninit<u8> =
+            self.entries.cells_mut().as_mut_ptr() as *mut MaybeUninit<u8>;
+
+        let cores: usize = thread::available_parallelism().map_or(1, |n| n.get());
+        let threads: usize = cores.min(total_bytes.div_ceil(CLEAR_CHUNK_SIZE)).max(1);
+
+        if threads == 1 {
+            // SAFETY: ptr points to total_bytes bytes owned by the table
+            unsafe { ptr::write_bytes(ptr as *mut u8, 0, total_bytes) };
+        } else {
+            parallel_clear(ptr, threads, total_bytes);
         }
+        self.age.store(0, Ordering::Relaxed);
+        #[cfg(feature = "tt-verify")]
+        self.verification
+            .reset(self.cells().len() * ENTRIES_PER_CELL);
     }
 
     #[inline(always)]
     fn index(&self, key: u64) -> usize {
         let key: u128 = key as u128;
-        let len: u128 = self.entries.len() as u128;
+        let len: u128 = self.cells().len() as u128;
 
         ((key * len) >> 64) as usize
     }
 
+    /// Returns the table occupancy in per mille, sampled from the first cells.
     pub fn hash_full(&self) -> usize {
+        let sample: usize = self.cells().len().min(HASHFULL_SAMPLE);
+        if sample == 0 {
+            return 0;
+        }
+
         let mut counter: usize = 0;
         let age: u8 = self.age.load(Ordering::Relaxed);
-        for cell in self.entries.iter().take(2000) {
+        for cell in self.cells().iter().take(sample) {
             for index in 0..ENTRIES_PER_CELL {
                 let entry: Entry = cell.load(index);
                 counter +=
@@ -288,7 +420,7 @@ impl TranspositionTable {
             }
         }
 
-        counter / (2 * ENTRIES_PER_CELL)
+        counter * 1000 / (sample * ENTRIES_PER_CELL)
     }
 
     pub fn age(&self) {
@@ -302,7 +434,7 @@ impl TranspositionTable {
             use core::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
 
             let index: usize = self.index(key);
-            let ptr: *const i8 = &self.entries[index] as *const Cell as *const i8;
+            let ptr: *const i8 = &self.cells()[index] as *const Cell as *const i8;
 
             _mm_prefetch::<_MM_HINT_T0>(ptr);
         }
@@ -314,10 +446,12 @@ impl TranspositionTable {
 
 impl TranspositionTable {
     pub fn probe(&self, key: u64, ply: usize) -> Option<EntryHit> {
-        let index: usize = self.index(key);
+        let cell_index: usize = self.index(key);
+        #[cfg(feature = "tt-verify")]
+        let full_key: u64 = key;
         let key: u16 = wrap_key(key);
 
-        let cell: &Cell = unsafe { self.entries.get_unchecked(index) };
+        let cell: &Cell = unsafe { self.cells().get_unchecked(cell_index) };
 
         for index in 0..ENTRIES_PER_CELL {
             let entry: Entry = cell.load(index);
@@ -326,6 +460,14 @@ impl TranspositionTable {
                 continue;
             }
 
+            #[cfg(feature = "tt-verify")]
+            if !self
+                .verification
+                .check(cell_index * ENTRIES_PER_CELL + index, full_key)
+            {
+                continue;
+            }
+
             return Some(EntryHit {
                 mv: entry.mv,
                 score: unnormalize_score(entry.score as i32, ply as i32),
@@ -339,6 +481,51 @@ impl TranspositionTable {
         None
     }
 
+    /// Writes the table contents, prefixed by its length and age.
+    pub fn save<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.cells().len() as u64).to_le_bytes())?;
+        writer.write_all(&[self.age.load(Ordering::Relaxed)])?;
+        for cell in self.cells().iter() {
+            for index in 0..ENTRIES_PER_CELL {
+                writer.write_all(&cell.load(index).to_ne_bytes()[..ENTRY_BYTES])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores a table written by [`TranspositionTable::save`], resizing it to the saved length.
+    /// Fails without reading any cell when that size cannot be allocated.
+    pub fn load<R: Read>(&mut self, reader: &mut R) -> io::Result<()> {
+        let mut len: [u8; 8] = [0; 8];
+        let mut age: [u8; 1] = [0; 1];
+        reader.read_exact(&mut len)?;
+        reader.read_exact(&mut age)?;
+
+        let len: usize = u64::from_le_bytes(len) as usize;
+        let bytes: usize = len
+            .checked_mul(size_of::<Cell>())
+            .filter(|bytes| *bytes > 0 && bytes % MEGABYTE == 0)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid table size"))?;
+        if len != self.cells().len() {
+            self.try_resize(bytes / MEGABYTE)
+                .map_err(|error| io::Error::new(io::ErrorKind::OutOfMemory, error))?;
+        }
+
+        for cell in self.cells().iter() {
+            for index in 0..ENTRIES_PER_CELL {
+                let mut raw: [u8; 16] = [0; 16];
+                reader.read_exact(&mut raw[..ENTRY_BYTES])?;
+                cell.store(index, Entry::from_ne_bytes(raw));
+            }
+        }
+        self.age.store(age[0] & AGE_MASK, Ordering::Relaxed);
+        #[cfg(feature = "tt-verify")]
+        self.verification
+            .reset(self.cells().len() * ENTRIES_PER_CELL);
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn insert(
         &self,
@@ -352,10 +539,12 @@ impl TranspositionTable {
         ply: usize,
     ) {
         let index: usize = self.index(key);
+        #[cfg(feature = "tt-verify")]
+        let full_key: u64 = key;
         let key: u16 = wrap_key(key);
         let age: u8 = self.age.load(Ordering::Relaxed);
 
-        let cell: &Cell = unsafe { self.entries.get_unchecked(index) };
+        let cell: &Cell = unsafe { self.cells().get_unchecked(index) };
 
         let mut entry: Entry = cell.load(0);
         let mut store_index: usize = 0;
@@ -390,10 +579,36 @@ impl TranspositionTable {
             };
 
             cell.store(store_index, new);
+            #[cfg(feature = "tt-verify")]
+            self.verification
+                .store(index * ENTRIES_PER_CELL + store_index, full_key);
         }
     }
 }
 
+/// Allocates as many zeroed `T` as fit in `megabytes`.
+///
+/// # Safety
+/// The all-zero bit pattern must be a valid `T`.
+pub(crate) unsafe fn allocate_zeroed<T>(megabytes: usize) -> Result<Vec<T>, AllocationError> {
+    let error: AllocationError = AllocationError { megabytes };
+    let len: usize = megabytes.checked_mul(MEGABYTE).ok_or(error)? / size_of::<T>();
+    let layout: Layout = Layout::array::<T>(len).map_err(|_| error)?;
+    if layout.size() == 0 {
+        return Err(error);
+    }
+
+    // Allocate zeroed memory manually
+    let raw_ptr: *mut T = unsafe { alloc_zeroed(layout) as *mut T };
+
+    if raw_ptr.is_null() {
+        return Err(error);
+    }
+
+    // SAFETY: We allocated len * size_of::<T>() bytes, zeroed
+    Ok(unsafe { Vec::from_raw_parts(raw_ptr, len, len) })
+}
+
 #[inline(always)]
 fn wrap_key(key: u64) -> u16 {
     (key & KEY_WRAPPER_MASK) as u16
@@ -423,21 +638,21 @@ fn parallel_clear(ptr: *mut MaybeUninit<u8>, threads: usize, len: usize) {
 mod test {
     use std::{
         alloc::{Layout, alloc_zeroed},
-        sync::atomic::AtomicU8,
+        io,
         time::Instant,
     };
 
     use laura_core::{Move, MoveType, Square};
 
-    use crate::transposition::{BoundType, Cell, Entry, PackedData};
+    use crate::transposition::{BoundType, Cell, Entry, EntryHit, PackedData};
 
-    use super::{MEGABYTE, TranspositionTable, parallel_clear};
+    use super::{AllocationError, ENTRIES_PER_CELL, MEGABYTE, TranspositionTable, parallel_clear};
 
     #[test]
     fn test_table() {
         let mut ttable: TranspositionTable = TranspositionTable::default();
         ttable.resize(1);
-        assert_eq!(ttable.entries.len(), 32_768);
+        assert_eq!(ttable.cells().len(), 32_768);
     }
 
     #[test]
@@ -489,14 +704,129 @@ mod test {
 
     #[test]
     fn test_resize() {
-        let mut tt: TranspositionTable = TranspositionTable {
-            entries: Vec::new(),
-            age: AtomicU8::new(0),
-        };
+        let mut tt: TranspositionTable = TranspositionTable::default();
 
         let start: Instant = Instant::now();
         tt.resize(16);
         println!("Full initialization in {} µs", start.elapsed().as_micros());
+        assert_eq!(tt.megabytes(), 16);
+    }
+
+    #[cfg(feature = "tt-verify")]
+    #[test]
+    fn test_collision_stats() {
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+
+        // Same cell and same 16-bit key, different position
+        let key: u64 = 0x1234_5678_9ABC_DEF0;
+        let other: u64 = key ^ (1 << 20);
+        let mv: Move = Move::new(Square::E2, Square::E3, MoveType::Quiet);
+        ttable.insert(key, mv, 10, 10, 4, BoundType::Exact, true, 0);
+
+        assert!(ttable.probe(key, 0).is_some());
+        assert!(ttable.probe(other, 0).is_none());
+        assert_eq!(ttable.collision_stats(), (1, 1));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_shared_table() {
+        use crate::storage::SharedStorage;
+
+        let name: String = format!("tt-{}", std::process::id());
+        let mut first: TranspositionTable = TranspositionTable::default();
+        let mut second: TranspositionTable = TranspositionTable::default();
+        first.resize(1);
+        second.resize(1);
+        first.set_shared(Some(&name)).unwrap();
+        second.set_shared(Some(&name)).unwrap();
+        assert_eq!(first.shared(), Some(name.as_str()));
+
+        // An entry stored by one table is found by the other
+        let mv: Move = Move::new(Square::E2, Square::E4, MoveType::Quiet);
+        first.insert(0xDEAD_BEEF, mv, 25, 10, 6, BoundType::Exact, true, 0);
+        let hit: EntryHit = second.probe(0xDEAD_BEEF, 0).unwrap();
+        assert_eq!((hit.mv, hit.score, hit.depth), (mv, 25, 6));
+
+        // Resizing keeps the table shared, going private drops the entries
+        second.resize(1);
+        assert!(second.probe(0xDEAD_BEEF, 0).is_some());
+        second.set_shared(None).unwrap();
+        assert_eq!(second.shared(), None);
+        assert!(second.probe(0xDEAD_BEEF, 0).is_none());
+        SharedStorage::unlink(&name);
+    }
+
+    #[test]
+    fn test_failed_resize() {
+        let mut tt: TranspositionTable = TranspositionTable::default();
+        tt.resize(2);
+
+        // Impossible sizes are reported and the previous table is kept
+        for megabytes in [usize::MAX, isize::MAX as usize / MEGABYTE] {
+            assert_eq!(tt.try_resize(megabytes), Err(AllocationError { megabytes }));
+            assert_eq!(tt.megabytes(), 2);
+        }
+    }
+
+    #[test]
+    fn test_hash_full() {
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        assert_eq!(ttable.hash_full(), 0);
+
+        ttable.resize(1);
+        assert_eq!(ttable.hash_full(), 0);
+
+        let entry: Entry = Entry {
+            key: 0xABCD,
+            mv: Move::new(Square::A2, Square::A5, MoveType::Quiet),
+            score: 20,
+            evaluation: -12,
+            depth: 5,
+            data: PackedData::new(0, BoundType::Exact, false),
+        };
+
+        for cell in ttable.cells().iter().take(500) {
+            for index in 0..ENTRIES_PER_CELL {
+                cell.store(index, entry);
+            }
+        }
+        assert_eq!(ttable.hash_full(), 500);
+
+        for cell in ttable.cells().iter() {
+            for index in 0..ENTRIES_PER_CELL {
+                cell.store(index, entry);
+            }
+        }
+        assert_eq!(ttable.hash_full(), 1000);
+
+        ttable.age();
+        assert_eq!(ttable.hash_full(), 0);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.clear();
+        ttable.resize(1);
+
+        let entry: Entry = Entry {
+            key: 0xABCD,
+            mv: Move::new(Square::A2, Square::A5, MoveType::Quiet),
+            score: 20,
+            evaluation: -12,
+            depth: 5,
+            data: PackedData::new(0, BoundType::Exact, false),
+        };
+        for cell in ttable.cells().iter() {
+            cell.store(0, entry);
+        }
+
+        ttable.clear();
+        for cell in ttable.cells().iter() {
+            assert_eq!(cell.load(0).key, 0);
+        }
     }
 
     #[test]
@@ -541,4 +871,42 @@ mod test {
             assert_eq!(*byte, 0, "Error on index {index}");
         }
     }
+
+    #[test]
+    fn test_save_load() {
+        let mut ttable: TranspositionTable = TranspositionTable::default();
+        ttable.resize(1);
+        ttable.age();
+        let mv: Move = Move::new(Square::A2, Square::A5, MoveType::Quiet);
+        ttable.insert(0xDEAD_BEEF, mv, 42, -7, 9, BoundType::Exact, true, 0);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        ttable.save(&mut buffer).unwrap();
+
+        let mut restored: TranspositionTable = TranspositionTable::default();
+        restored.resize(2);
+        restored.load(&mut buffer.as_slice()).unwrap();
+        assert_eq!(restored.cells().len(), ttable.cells().len());
+
+        let hit: EntryHit = restored.probe(0xDEAD_BEEF, 0).unwrap();
+        assert_eq!(hit.mv, mv);
+        assert_eq!(hit.score, 42);
+        assert_eq!(hit.evaluation, -7);
+        assert_eq!(hit.depth, 9);
+        assert_eq!(hit.bound, BoundType::Exact);
+        assert_eq!(restored.hash_full(), ttable.hash_full());
+
+        // Truncated input is rejected
+        assert!(restored.load(&mut &buffer[..4]).is_err());
+
+        // A size that cannot be allocated fails before any cell is read
+        let megabytes: usize = isize::MAX as usize / MEGABYTE;
+        let len: u64 = (megabytes * (MEGABYTE / size_of::<Cell>())) as u64;
+        let mut huge: Vec<u8> = len.to_le_bytes().to_vec();
+        huge.extend_from_slice(&buffer[8..]);
+        let error: io::Error = restored.load(&mut huge.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::OutOfMemory);
+        assert_eq!(restored.megabytes(), 1);
+        assert_eq!(restored.probe(0xDEAD_BEEF, 0).unwrap().mv, mv);
+    }
 }
@@ -22,48 +22,84 @@
 //! UCI protocol implementation
 
 use crate::{
-    config::DEFAULT_SIZE,
-    position::Position,
+    cluster::{self, Cluster, WorkerResult},
+    config::{CLUSTER_HOST, DEFAULT_SIZE, MAX_PLY, MIN_HASH_PER_THREAD},
+    cpu::{self, AttackBench},
+    display::BoardView,
+    error::EngineError,
+    geometry::{color_from_char, file_from_char, square_from_str},
+    movelist::MoveListExt,
+    options::{
+        EVAL_NOISE_MAX, EngineOptions, GIT_HASH, HASH_MAX, HASH_MIN, OPTIONS, THREADS_MAX,
+        THREADS_MIN, build_info,
+    },
+    output::{OutputFormat, bestmove_json},
+    perft::PerftTable,
+    position::{IllegalPosition, Position, validate},
+    profile::SearchProfile,
+    settings, signal, snapshot,
     thread::ThreadPool,
     timer::{TimeControl, TimeParserError},
     transposition::TranspositionTable,
 };
-use laura_core::{Board, Move};
+use laura_core::{Board, Move, MoveList, legal_moves};
 use std::{
-    io::{BufRead, Stdin, stdin},
+    io::{self, BufRead, Stdin, Write, stdin},
+    ops::Range,
+    path::Path,
     str::FromStr,
     sync::{
         Arc,
-        atomic::{AtomicBool, Ordering},
-        mpsc::{self, Receiver},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
     },
-    thread,
+    thread::{self, JoinHandle},
+    time::Duration,
 };
 
 const AUTHOR: &str = "HansTibberio";
 const NAME: &str = "Laura";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const THREADS_MIN: usize = 1;
-const THREADS_MAX: usize = 512;
-const HASH_MIN: usize = 1;
-const HASH_MAX: usize = 1048576;
-
 #[derive(Debug)]
 pub enum UCICommand {
     Uci,
     IsReady,
     UciNewGame,
-    Position(Board, Vec<Board>),
-    Go(TimeControl),
+    Position(Board, Vec<Board>, String),
+    Go(TimeControl, RootFilter),
     Stop,
     Quit,
-    SetOption { name: String, value: String },
+    SetOption {
+        name: String,
+        value: String,
+    },
+    SaveConfig,
+    Save {
+        path: String,
+        tt: bool,
+    },
+    Load(String),
+    ClusterListen {
+        host: String,
+        port: u16,
+    },
+    ClusterConnect(String),
     DividePerft(u8),
-    Perft(u8),
-    Print,
+    Perft {
+        depth: u8,
+        hash: usize,
+    },
+    BenchAttacks,
+    Print {
+        unicode: bool,
+        flipped: bool,
+        colored: bool,
+    },
     Eval,
+    Flip,
     License,
+    Version,
     Help,
 }
 
@@ -73,10 +109,12 @@ pub enum UCIError {
     NoOptionValue,
     InvalidOptionValue,
     InvalidFenPosition,
+    InvalidCastling(String),
     InvalidPositionFormat(String),
     InvalidSetOption,
     InvalidGo(TimeParserError),
     IlegalUciMove(String),
+    IllegalPosition(IllegalPosition),
 }
 
 impl std::fmt::Display for UCIError {
@@ -86,10 +124,21 @@ impl std::fmt::Display for UCIError {
             UCIError::NoOptionValue => write!(f, "[error] no option value provided."),
             UCIError::InvalidOptionValue => write!(f, "[error] invalid option value."),
             UCIError::InvalidFenPosition => write!(f, "[error] invalid fen position format."),
+            UCIError::InvalidCastling(s) => write!(f, "[error] invalid castling rights '{s}'."),
             UCIError::InvalidPositionFormat(s) => write!(f, "[error] {s}"),
             UCIError::InvalidSetOption => write!(f, "[error] invalid setoption."),
-            UCIError::InvalidGo(err) => write!(f, "[error] '{err:?}'"),
+            UCIError::InvalidGo(err) => write!(f, "[error] {err}."),
             UCIError::IlegalUciMove(s) => write!(f, "[error] ilegal uci move '{s}'."),
+            UCIError::IllegalPosition(e) => write!(f, "[error] illegal position: {e}."),
+        }
+    }
+}
+
+impl std::error::Error for UCIError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UCIError::InvalidGo(err) => Some(err),
+            _ => None,
         }
     }
 }
@@ -124,9 +173,9 @@ impl FromStr for UCICommand {
 
                         if matches!(tokens.next(), Some("moves")) {
                             for uci_move in tokens {
-                                if let Some(mv) = board.find_move(uci_move) {
+                                if let Some(next) = play_uci_move(&board, uci_move) {
                                     history.push(board);
-                                    board = board.make_move(mv);
+                                    board = next;
                                 } else {
                                     return Err(UCIError::IlegalUciMove(uci_move.to_string()));
                                 }
@@ -142,16 +191,27 @@ impl FromStr for UCICommand {
                             }
                             fen.push_str(token);
                         }
+                        let side: Option<char> = fen.split_whitespace().nth(1).and_then(|side| {
+                            let mut chars = side.chars();
+                            chars.next().filter(|_| chars.next().is_none())
+                        });
+                        if side.and_then(color_from_char).is_none() {
+                            return Err(UCIError::InvalidPositionFormat(
+                                "expected 'w' or 'b' as the side to move".to_string(),
+                            ));
+                        }
+                        let fen: String = normalize_castling(&fen)?;
                         let mut board: Board = Board::from_str(&fen)
                             .ok()
                             .ok_or(UCIError::InvalidFenPosition)?;
+                        validate(&board).map_err(UCIError::IllegalPosition)?;
                         let mut history: Vec<Board> = Vec::with_capacity(16);
 
                         if matches!(tokens.next(), Some("moves")) {
                             for uci_move in tokens {
-                                if let Some(mv) = board.find_move(uci_move) {
+                                if let Some(next) = play_uci_move(&board, uci_move) {
                                     history.push(board);
-                                    board = board.make_move(mv);
+                                    board = next;
                                 } else {
                                     return Err(UCIError::IlegalUciMove(uci_move.to_string()));
                                 }
@@ -166,19 +226,36 @@ impl FromStr for UCICommand {
                     }
                 };
 
-                Ok(Self::Position(board, history))
+                Ok(Self::Position(board, history, s.to_string()))
             }
             Some("go") => {
                 let mut commands: String = String::with_capacity(64);
+                let mut filter: RootFilter = RootFilter::default();
+                let mut list: Option<&mut Vec<String>> = None;
                 for token in tokens {
-                    if !commands.is_empty() {
-                        commands.push(' ');
+                    match token {
+                        "searchmoves" => list = Some(&mut filter.searchmoves),
+                        "excludemoves" => list = Some(&mut filter.excludemoves),
+                        _ => match list.as_mut() {
+                            Some(moves) if is_uci_move(token) => moves.push(token.to_string()),
+                            _ => {
+                                list = None;
+                                if !commands.is_empty() {
+                                    commands.push(' ');
+                                }
+                                commands.push_str(token);
+                            }
+                        },
                     }
-                    commands.push_str(token);
                 }
 
-                let time_control: TimeControl = TimeControl::from_str(&commands)?;
-                Ok(Self::Go(time_control))
+                // A bare `go searchmoves ...` searches until stopped
+                let time_control: TimeControl = if commands.is_empty() && !filter.is_empty() {
+                    TimeControl::INFINITE
+                } else {
+                    TimeControl::from_str(&commands)?
+                };
+                Ok(Self::Go(time_control, filter))
             }
             Some("stop") => Ok(Self::Stop),
             Some("quit") => Ok(Self::Quit),
@@ -194,23 +271,289 @@ impl FromStr for UCICommand {
 
                 Ok(Self::SetOption { name, value })
             }
+            Some("saveconfig") => Ok(Self::SaveConfig),
+            Some("save") => {
+                let path: String = tokens.next().ok_or(UCIError::NoOptionValue)?.to_string();
+                let tt: bool = match tokens.next() {
+                    Some("tt") => true,
+                    None => false,
+                    _ => return Err(UCIError::InvalidOptionValue),
+                };
+                Ok(Self::Save { path, tt })
+            }
+            Some("load") => Ok(Self::Load(
+                tokens.next().ok_or(UCIError::NoOptionValue)?.to_string(),
+            )),
+            Some("cluster") => match tokens.next() {
+                Some("listen") => match tokens.next().ok_or(UCIError::NoOptionValue)?.parse() {
+                    Ok(port) => Ok(Self::ClusterListen {
+                        host: tokens.next().unwrap_or(CLUSTER_HOST).to_string(),
+                        port,
+                    }),
+                    Err(_) => Err(UCIError::InvalidOptionValue),
+                },
+                Some("connect") => Ok(Self::ClusterConnect(
+                    tokens.next().ok_or(UCIError::NoOptionValue)?.to_string(),
+                )),
+                _ => Err(UCIError::InvalidOptionValue),
+            },
             Some("dperft") => match tokens.next().ok_or(UCIError::NoOptionValue)?.parse::<u8>() {
                 Ok(depth) if depth > 0 => Ok(Self::DividePerft(depth)),
                 _ => Err(UCIError::InvalidOptionValue),
             },
-            Some("perft") => match tokens.next().ok_or(UCIError::NoOptionValue)?.parse::<u8>() {
-                Ok(depth) if depth > 0 => Ok(Self::Perft(depth)),
+            Some("bench") => match tokens.next() {
+                Some("attacks") => Ok(Self::BenchAttacks),
                 _ => Err(UCIError::InvalidOptionValue),
             },
-            Some("print") => Ok(Self::Print),
+            Some("perft") => {
+                let depth: u8 = match tokens.next().ok_or(UCIError::NoOptionValue)?.parse::<u8>() {
+                    Ok(depth) if depth > 0 => depth,
+                    _ => return Err(UCIError::InvalidOptionValue),
+                };
+                let hash: usize = match tokens.next() {
+                    None => 0,
+                    Some("hash") => match tokens.next().map(str::parse::<usize>) {
+                        Some(Ok(megabytes)) if megabytes > 0 => megabytes,
+                        _ => return Err(UCIError::InvalidOptionValue),
+                    },
+                    Some(_) => return Err(UCIError::InvalidOptionValue),
+                };
+                Ok(Self::Perft { depth, hash })
+            }
+            Some("print") => {
+                let (mut unicode, mut flipped, mut colored) = (false, false, false);
+                for token in tokens {
+                    match token {
+                        "unicode" => unicode = true,
+                        "flip" => flipped = true,
+                        "color" => colored = true,
+                        _ => return Err(UCIError::InvalidOptionValue),
+                    }
+                }
+                Ok(Self::Print {
+                    unicode,
+                    flipped,
+                    colored,
+                })
+            }
             Some("eval") => Ok(Self::Eval),
+            Some("flip") => Ok(Self::Flip),
             Some("license") => Ok(Self::License),
+            Some("version") => Ok(Self::Version),
             Some("help") => Ok(Self::Help),
             _ => Err(UCIError::UnknownCommand(s.to_string())),
         }
     }
 }
 
+/// Rewrites X-FEN/Shredder castling fields (e.g. `HAha`) into `KQkq`.
+///
+/// File letters are accepted as long as they name a rook on its standard
+/// corner with the king on the e-file, which is all the board representation
+/// can castle with. Any other letter is reported as an inconsistent right.
+fn normalize_castling(fen: &str) -> Result<String, UCIError> {
+    let mut fields: Vec<&str> = fen.split_whitespace().collect();
+    let castling: &str = match fields.get(2) {
+        Some(&castling)
+            if castling
+                .chars()
+                .any(|c| c.is_ascii_alphabetic() && !"KQkq".contains(c)) =>
+        {
+            castling
+        }
+        _ => return Ok(fen.to_string()),
+    };
+
+    let ranks: Vec<&str> = fields[0].split('/').collect();
+    if ranks.len() != 8 {
+        return Err(UCIError::InvalidFenPosition);
+    }
+
+    // Expands a rank of the placement field into one char per file
+    let expand = |rank: &str| -> Vec<char> {
+        let mut squares: Vec<char> = Vec::with_capacity(8);
+        for c in rank.chars() {
+            match c.to_digit(10) {
+                Some(empty) => squares.extend(std::iter::repeat_n('.', empty as usize)),
+                None => squares.push(c),
+            }
+        }
+        squares
+    };
+    let back_ranks: [Vec<char>; 2] = [expand(ranks[7]), expand(ranks[0])];
+
+    let mut rights: [bool; 4] = [false; 4];
+    for c in castling.chars() {
+        let (color, right): (usize, usize) = match c {
+            'K' => (0, 0),
+            'Q' => (0, 1),
+            'k' => (1, 0),
+            'q' => (1, 1),
+            'A'..='H' | 'a'..='h' => {
+                let color: usize = c.is_ascii_lowercase() as usize;
+                let file: usize = file_from_char(c).unwrap();
+                let (king, rook): (char, char) = if color == 0 { ('K', 'R') } else { ('k', 'r') };
+                let rank: &[char] = &back_ranks[color];
+
+                if rank.get(4) != Some(&king) || rank.get(file) != Some(&rook) {
+                    return Err(UCIError::InvalidCastling(castling.to_string()));
+                }
+                match file {
+                    7 => (color, 0),
+                    0 => (color, 1),
+                    _ => return Err(UCIError::InvalidCastling(castling.to_string())),
+                }
+            }
+            _ => return Err(UCIError::InvalidCastling(castling.to_string())),
+        };
+        rights[color * 2 + right] = true;
+    }
+
+    let normalized: String = ['K', 'Q', 'k', 'q']
+        .iter()
+        .zip(rights)
+        .filter_map(|(c, set)| set.then_some(*c))
+        .collect();
+    fields[2] = if normalized.is_empty() {
+        "-"
+    } else {
+        &normalized
+    };
+
+    Ok(fields.join(" "))
+}
+
+/// Root moves restricted by `go searchmoves` and the `go excludemoves` extension.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RootFilter {
+    pub searchmoves: Vec<String>,
+    pub excludemoves: Vec<String>,
+}
+
+impl RootFilter {
+    pub fn is_empty(&self) -> bool {
+        self.searchmoves.is_empty() && self.excludemoves.is_empty()
+    }
+
+    /// Legal root moves that must not be searched. Moves outside `searchmoves`
+    /// are excluded along with the ones in `excludemoves`.
+    pub fn excluded(&self, board: &Board) -> Result<Vec<Move>, UCIError> {
+        let find = |moves: &[String]| -> Result<Vec<Move>, UCIError> {
+            moves
+                .iter()
+                .map(|mv| {
+                    board
+                        .find_move(mv)
+                        .ok_or_else(|| UCIError::IlegalUciMove(mv.clone()))
+                })
+                .collect()
+        };
+        let search: Vec<Move> = find(&self.searchmoves)?;
+        let exclude: Vec<Move> = find(&self.excludemoves)?;
+
+        let mut legal: MoveList = legal_moves!(board);
+        legal.retain(|mv| (!search.is_empty() && !search.contains(mv)) || exclude.contains(mv));
+        Ok(legal.as_slice().to_vec())
+    }
+}
+
+/// Plays a move given in long algebraic notation. The null move `0000` passes
+/// the turn, clearing en passant, as long as the side to move is not in check.
+fn play_uci_move(board: &Board, uci_move: &str) -> Option<Board> {
+    if uci_move == "0000" {
+        return (board.checkers.count_bits() == 0).then(|| board.null_move());
+    }
+    board.find_move(uci_move).map(|mv| board.make_move(mv))
+}
+
+/// Appends a null move to a `position` command, switching the side to move.
+fn flipped_command(command: &str) -> String {
+    if command.split_whitespace().any(|token| token == "moves") {
+        format!("{command} 0000")
+    } else {
+        format!("{command} moves 0000")
+    }
+}
+
+/// Whether a token has the shape of a move in long algebraic notation.
+fn is_uci_move(token: &str) -> bool {
+    let square = |range: Range<usize>| token.get(range).and_then(square_from_str).is_some();
+    matches!(token.len(), 4 | 5)
+        && square(0..2)
+        && square(2..4)
+        && token
+            .get(4..)
+            .is_some_and(|piece| ["", "n", "b", "r", "q"].contains(&piece))
+}
+
+/// Busy state shared by the listener and the command loop.
+///
+/// Counts the searches that are queued or running, so the listener knows
+/// when a `go` has to stop the current search before it is forwarded, and
+/// when `isready` has to be answered directly. Every other command is queued
+/// and only applied once the loop is idle.
+#[derive(Debug, Default, Clone)]
+pub struct SearchState {
+    pending: Arc<AtomicUsize>,
+}
+
+impl SearchState {
+    pub fn busy(&self) -> bool {
+        self.pending.load(Ordering::SeqCst) > 0
+    }
+
+    pub fn begin(&self) {
+        self.pending.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn finish(&self) {
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub fn wait_idle(&self) {
+        while self.busy() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+/// Forwards a command to the loop, returning false once the loop is gone.
+fn dispatch(
+    command: Result<UCICommand, UCIError>,
+    sender: &Sender<Result<UCICommand, UCIError>>,
+    stop: &AtomicBool,
+    state: &SearchState,
+) -> bool {
+    match command {
+        Ok(UCICommand::Stop) => {
+            stop.store(true, Ordering::SeqCst);
+            true
+        }
+        Ok(UCICommand::IsReady) if state.busy() => {
+            // GUIs use `isready` as a keep-alive while the engine is thinking
+            println!("readyok");
+            true
+        }
+        Ok(UCICommand::Go(..)) => {
+            // A new search stops the current one cleanly first, then arms the
+            // stop flag before it is queued so that a following `stop` always
+            // reaches this search.
+            if state.busy() {
+                stop.store(true, Ordering::SeqCst);
+                state.wait_idle();
+            }
+            stop.store(false, Ordering::SeqCst);
+            state.begin();
+            if sender.send(command).is_err() {
+                state.finish();
+                return false;
+            }
+            true
+        }
+        _ => sender.send(command).is_ok(),
+    }
+}
+
 pub fn uci_start() {
     println!("{NAME} {VERSION} by {AUTHOR}");
 }
@@ -219,44 +562,92 @@ pub fn uci_listener() {
     let (sender, receiver) = mpsc::channel();
     let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
     let stop_clone: Arc<AtomicBool> = Arc::clone(&stop);
+    let state: SearchState = SearchState::default();
+    let state_clone: SearchState = state.clone();
+    let handle: JoinHandle<()> = thread::spawn(move || {
+        uci_loop(receiver, stop_clone, state_clone);
+    });
+
+    // Termination signals stop the search and quit through the command loop,
+    // so the best move is still reported and no thread is killed mid-write.
+    signal::install();
+    let signal_sender: Sender<Result<UCICommand, UCIError>> = sender.clone();
+    let signal_stop: Arc<AtomicBool> = Arc::clone(&stop);
     thread::spawn(move || {
-        uci_loop(receiver, stop_clone);
+        while !signal::received() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        signal_stop.store(true, Ordering::SeqCst);
+        let _ = signal_sender.send(Ok(UCICommand::Quit));
     });
 
-    let stdin: Stdin = stdin();
-    for line in stdin.lock().lines() {
-        match line {
-            Ok(cmd) => {
-                let command: Result<UCICommand, UCIError> = UCICommand::from_str(&cmd);
-                match command {
-                    Ok(UCICommand::Stop) => {
-                        stop.store(true, Ordering::SeqCst);
+    // Input is read on its own thread so that the command loop is the only
+    // place where the engine shuts down, once any running search has finished.
+    let input_stop: Arc<AtomicBool> = Arc::clone(&stop);
+    thread::spawn(move || {
+        let stdin: Stdin = stdin();
+        for line in stdin.lock().lines() {
+            match line {
+                Ok(cmd) => {
+                    let command: Result<UCICommand, UCIError> = UCICommand::from_str(&cmd);
+                    let quit: bool = matches!(command, Ok(UCICommand::Quit));
+                    if quit {
+                        input_stop.store(true, Ordering::SeqCst);
                     }
-                    Ok(UCICommand::Quit) => {
-                        stop.store(true, Ordering::SeqCst);
-                        std::process::exit(0);
+                    if !dispatch(command, &sender, &input_stop, &state) {
+                        eprintln!("info string [error] failed to send command.");
+                        return;
                     }
-                    _ => {
-                        if sender.send(command).is_err() {
-                            eprintln!("info string [error] failed to send command.");
-                            break;
-                        }
+                    if quit {
+                        return;
                     }
                 }
-            }
-            Err(e) => {
-                eprintln!("info string [error] reading stdin: {}.", e);
+                Err(e) => {
+                    eprintln!("info string [error] reading stdin: {}.", e);
+                }
             }
         }
-    }
+
+        // EOF on stdin: stop any running search and quit
+        input_stop.store(true, Ordering::SeqCst);
+        let _ = sender.send(Ok(UCICommand::Quit));
+    });
+
+    let _ = handle.join();
 }
 
-pub fn uci_loop(receiver: Receiver<Result<UCICommand, UCIError>>, stop: Arc<AtomicBool>) {
+pub fn uci_loop(
+    receiver: Receiver<Result<UCICommand, UCIError>>,
+    stop: Arc<AtomicBool>,
+    state: SearchState,
+) {
     let mut position: Position = Position::default();
-    let mut threadpool: ThreadPool = ThreadPool::new(stop);
+    let mut threadpool: ThreadPool = ThreadPool::new(Arc::clone(&stop));
+    let mut options: Arc<EngineOptions> = Arc::default();
+    let mut cluster: Option<Cluster> = None;
     let mut ttable: TranspositionTable = TranspositionTable::default();
     ttable.resize(DEFAULT_SIZE);
 
+    let mut position_command: String = String::from("position startpos");
+    // Hash and threads configuration already warned about
+    let mut checked: Option<(usize, usize)> = None;
+
+    // Persistent defaults are applied before the UCI handshake
+    let mut saved: Vec<(String, String)> = Vec::new();
+    if let Some(path) = settings::config_path() {
+        match settings::load(&path) {
+            Ok(entries) => {
+                for (name, value) in entries {
+                    match set_option(&name, &value, &mut ttable, &mut threadpool, &mut options) {
+                        Ok(()) => remember(&mut saved, name, value),
+                        Err(e) => eprintln!("info string {}", e),
+                    }
+                }
+            }
+            Err(e) => eprintln!("info string [error] reading {}: {}.", path.display(), e),
+        }
+    }
+
     while let Ok(command) = receiver.recv() {
         match command {
             Ok(UCICommand::Uci) => {
@@ -264,8 +655,13 @@ pub fn uci_loop(receiver: Receiver<Result<UCICommand, UCIError>>, stop: Arc<Atom
                 println!("id author {}", AUTHOR);
 
                 // UCI options
-                println!("option name Hash type spin default 16 min 1 max 1048576");
-                println!("option name Threads type spin default 1 min 1 max 512");
+                for option in OPTIONS {
+                    println!("{option}");
+                }
+                println!("info string build {}", build_info());
+                if let Some(advice) = cpu::backend_advice() {
+                    eprintln!("info string [warning] {}.", advice);
+                }
 
                 println!("uciok");
             }
@@ -275,55 +671,173 @@ pub fn uci_loop(receiver: Receiver<Result<UCICommand, UCIError>>, stop: Arc<Atom
             Ok(UCICommand::UciNewGame) => {
                 position.set_board(Board::default());
                 position.set_game(Vec::new());
-                ttable.clear(threadpool.threads);
+                position_command = String::from("position startpos");
+                ttable.clear();
+                threadpool.clear();
             }
-            Ok(UCICommand::Position(board, history)) => {
+            Ok(UCICommand::Position(board, history, command)) => {
                 position.set_board(board);
                 position.set_game(history);
+                position_command = command;
             }
-            Ok(UCICommand::Go(time_control)) => {
-                ttable.age();
-                let best: Option<Move> =
-                    threadpool.start_search(&mut position, &ttable, time_control);
-                if let Some(mv) = best {
-                    println!("bestmove {}", mv);
+            Ok(UCICommand::Save { path, tt }) => {
+                let table: Option<&TranspositionTable> = tt.then_some(&ttable);
+                match snapshot::save(Path::new(&path), &position_command, table) {
+                    Ok(()) => println!("info string snapshot saved to {}", path),
+                    Err(e) => eprintln!("info string [error] saving snapshot: {}.", e),
                 }
             }
-            Ok(UCICommand::Stop) | Ok(UCICommand::Quit) => {
-                eprintln!("info string [warning] unexpected stop/quit.");
-                continue;
-            }
-            Ok(UCICommand::SetOption { name, value }) => match name.to_lowercase().as_str() {
-                "hash" => match value.parse::<usize>() {
-                    Ok(mb) if (HASH_MIN..=HASH_MAX).contains(&mb) => {
-                        ttable.resize(mb);
-                        println!("info string Hash size set to {} MB", mb);
+            Ok(UCICommand::Load(path)) => match snapshot::load(Path::new(&path), &mut ttable) {
+                Ok(command) => match UCICommand::from_str(&command) {
+                    Ok(UCICommand::Position(board, history, command)) => {
+                        position.set_board(board);
+                        position.set_game(history);
+                        position_command = command;
+                        println!("info string snapshot loaded from {}", path);
                     }
-                    _ => eprintln!(
-                        "info string [error] Invalid value for Hash: '{}'. Must be between {} and {}.",
-                        value, HASH_MIN, HASH_MAX
-                    ),
+                    _ => eprintln!("info string [error] invalid snapshot '{}'.", path),
                 },
-                "threads" => match value.parse::<usize>() {
-                    Ok(n) if (THREADS_MIN..=THREADS_MAX).contains(&n) => {
-                        threadpool.resize(n);
-                        println!("info string Threads set to {}", n);
+                Err(e) => eprintln!("info string [error] loading snapshot: {}.", e),
+            },
+            Ok(UCICommand::Go(time_control, filter)) => {
+                let excluded: Vec<Move> = match filter.excluded(&position.board()) {
+                    Ok(excluded)
+                        if excluded.is_empty() || excluded.len() < position.count_legal_moves() =>
+                    {
+                        excluded
                     }
-                    _ => eprintln!(
-                        "info string [error] Invalid value for Threads: '{}'. Must be between {} and {}.",
-                        value, THREADS_MIN, THREADS_MAX
-                    ),
+                    Ok(_) => {
+                        eprintln!("info string [warning] every root move is excluded, ignoring.");
+                        Vec::new()
+                    }
+                    Err(e) => {
+                        eprintln!("info string {e}");
+                        Vec::new()
+                    }
+                };
+                threadpool.set_excluded(excluded);
+                if checked != Some((ttable.megabytes(), threadpool.threads)) {
+                    checked = Some((ttable.megabytes(), threadpool.threads));
+                    check_configuration(&ttable, &threadpool);
+                }
+                // Repeated analysis of the same root keeps the table entries current
+                if !threadpool.repeats_root(&position) {
+                    ttable.age();
+                }
+                if let Some(cluster) = &cluster {
+                    cluster.start(&position_command);
+                }
+                let mut best: Option<Move> =
+                    threadpool.start_search(&mut position, &ttable, time_control, &options);
+                if let Some(cluster) = &cluster {
+                    let results: Vec<WorkerResult> = cluster.collect();
+                    let (depth, _, _) = threadpool.principal_variation();
+                    best = cluster::merge(&results, &position, &ttable, depth).or(best);
+                }
+                if let Some(mv) = best {
+                    let output: OutputFormat = options.output;
+                    if output.uci() {
+                        println!("bestmove {}", mv);
+                    }
+                    if output.json() {
+                        println!("{}", bestmove_json(mv));
+                    }
+                }
+                #[cfg(feature = "tt-verify")]
+                {
+                    let (hits, collisions) = ttable.collision_stats();
+                    println!("info string tthits {} collisions {}", hits, collisions);
+                }
+                state.finish();
+            }
+            Ok(UCICommand::Stop) => {
+                eprintln!("info string [warning] unexpected stop.");
+                continue;
+            }
+            Ok(UCICommand::Quit) => break,
+            Ok(UCICommand::SetOption { name, value }) => {
+                match set_option(&name, &value, &mut ttable, &mut threadpool, &mut options) {
+                    Ok(()) => remember(&mut saved, name, value),
+                    Err(e) => eprintln!("info string {}", e),
+                }
+            }
+            Ok(UCICommand::SaveConfig) => match settings::config_path() {
+                Some(path) => match settings::save(&path, &saved) {
+                    Ok(()) => println!("info string configuration saved to {}", path.display()),
+                    Err(e) => eprintln!("info string [error] saving configuration: {}.", e),
                 },
-                _ => eprintln!("info string [error] unrecognized option '{}'", name),
+                None => eprintln!("info string [error] configuration path not available."),
             },
+            Ok(UCICommand::ClusterListen { host, port }) => match Cluster::listen(&host, port) {
+                Ok(listening) => {
+                    println!("info string cluster listening on port {}", listening.port());
+                    cluster = Some(listening);
+                }
+                Err(e) => eprintln!("info string [error] cluster listen: {}.", e),
+            },
+            Ok(UCICommand::ClusterConnect(addr)) => {
+                if let Err(e) = cluster::serve(
+                    &addr,
+                    &stop,
+                    &mut position,
+                    &mut threadpool,
+                    &ttable,
+                    &options,
+                ) {
+                    eprintln!("info string [error] cluster worker: {}.", e);
+                }
+            }
             Ok(UCICommand::DividePerft(depth)) => {
-                position.divided_perft(depth);
+                threadpool.perft::<true>(&position, depth, None);
             }
-            Ok(UCICommand::Perft(depth)) => {
-                position.perft(depth);
+            Ok(UCICommand::Perft { depth, hash: 0 }) => {
+                threadpool.perft::<false>(&position, depth, None);
             }
-            Ok(UCICommand::Print) => {
-                println!("{}", position.board());
+            Ok(UCICommand::Perft { depth, hash }) => match PerftTable::new(hash) {
+                Ok(table) => {
+                    threadpool.perft::<false>(&position, depth, Some(&table));
+                }
+                Err(error) => eprintln!("info string [error] {}.", error),
+            },
+            Ok(UCICommand::BenchAttacks) => {
+                let bench: AttackBench = cpu::bench_attacks();
+                let compiled: &str = if cfg!(feature = "bmi2") {
+                    "pext"
+                } else {
+                    "magic"
+                };
+                println!(
+                    "info string bench attacks {} backend {:.1} Mlookups/s",
+                    compiled,
+                    bench.rate(bench.backend)
+                );
+                match bench.pext {
+                    Some(pext) => println!(
+                        "info string bench attacks raw pext index {:.1} Mlookups/s",
+                        bench.rate(pext)
+                    ),
+                    None => println!("info string bench attacks pext unavailable"),
+                }
+                println!(
+                    "info string bench attacks preferred backend {}",
+                    if cpu::fast_pext() { "pext" } else { "magic" }
+                );
+            }
+            Ok(UCICommand::Print {
+                unicode,
+                flipped,
+                colored,
+            }) => {
+                if unicode || flipped || colored {
+                    let board: Board = position.board();
+                    let view: BoardView<'_> = BoardView::new(&board)
+                        .unicode(unicode)
+                        .flipped(flipped)
+                        .colored(colored);
+                    println!("{view}");
+                } else {
+                    println!("{}", position.board());
+                }
             }
             Ok(UCICommand::Eval) => {
                 if position.in_check() {
@@ -332,10 +846,27 @@ pub fn uci_loop(receiver: Receiver<Result<UCICommand, UCIError>>, stop: Arc<Atom
                     println!("{}", position.evaluate());
                 }
             }
+            Ok(UCICommand::Flip) => {
+                let command: String = flipped_command(&position_command);
+                match UCICommand::from_str(&command) {
+                    Ok(UCICommand::Position(board, history, command)) => {
+                        position.set_board(board);
+                        position.set_game(history);
+                        position_command = command;
+                    }
+                    Err(e) => eprintln!("info string {e}"),
+                    _ => unreachable!(),
+                }
+            }
             Ok(UCICommand::License) => {
                 println!("Laura is licensed under the GNU GPL v3.0.");
                 println!("See https://www.gnu.org/licenses/gpl-3.0.html for details.");
             }
+            Ok(UCICommand::Version) => {
+                println!("{} {} ({})", NAME, VERSION, GIT_HASH);
+                println!("build {}", build_info());
+                println!("Laura is licensed under the GNU GPL v3.0.");
+            }
             Ok(UCICommand::Help) => {
                 println!("Laura: A multi-threaded UCI chess engine written in Rust.");
                 println!("For more information, visit: https://github.com/HansTibberio/Laura");
@@ -344,4 +875,545 @@ pub fn uci_loop(receiver: Receiver<Result<UCICommand, UCIError>>, stop: Arc<Atom
             Err(e) => eprintln!("info string {e}"),
         }
     }
+
+    // Orderly shutdown: the workers are joined before the table they search is freed
+    drop(threadpool);
+    drop(ttable);
+    let _ = io::stdout().flush();
+}
+
+/// Applies an option, returning whether the value was accepted.
+fn set_option(
+    name: &str,
+    value: &str,
+    ttable: &mut TranspositionTable,
+    threadpool: &mut ThreadPool,
+    options: &mut Arc<EngineOptions>,
+) -> Result<(), EngineError> {
+    match name.to_lowercase().as_str() {
+        "hash" => match value.parse::<usize>() {
+            Ok(mb) if (HASH_MIN..=HASH_MAX).contains(&mb) => match ttable.try_resize(mb) {
+                Ok(()) => {
+                    println!("info string Hash size set to {} MB", mb);
+                    Ok(())
+                }
+                Err(error) => Err(EngineError::Allocation {
+                    error,
+                    kept: ttable.megabytes(),
+                }),
+            },
+            _ => Err(EngineError::invalid_option(
+                "Hash",
+                value,
+                format!("between {} and {}", HASH_MIN, HASH_MAX),
+            )),
+        },
+        "sharedhash" => {
+            let name: Option<&str> = match value {
+                "<empty>" => None,
+                name => Some(name),
+            };
+            match ttable.set_shared(name) {
+                Ok(()) => {
+                    println!("info string SharedHash set to {}", value);
+                    Ok(())
+                }
+                Err(e) => Err(e.into()),
+            }
+        }
+        "threads" => match value.parse::<usize>() {
+            Ok(n) if (THREADS_MIN..=THREADS_MAX).contains(&n) => {
+                threadpool.resize(n);
+                println!("info string Threads set to {}", n);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "Threads",
+                value,
+                format!("between {} and {}", THREADS_MIN, THREADS_MAX),
+            )),
+        },
+        "maxdepth" => match value.parse::<usize>() {
+            Ok(depth) if (1..=MAX_PLY).contains(&depth) => {
+                Arc::make_mut(options).max_depth = depth;
+                println!("info string MaxDepth set to {}", depth);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "MaxDepth",
+                value,
+                format!("between 1 and {}", MAX_PLY),
+            )),
+        },
+        "maxnodes" => match value.parse::<u64>() {
+            Ok(limit) => {
+                Arc::make_mut(options).max_nodes = (limit > 0).then_some(limit);
+                println!("info string MaxNodes set to {}", limit);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "MaxNodes",
+                value,
+                "a non-negative integer",
+            )),
+        },
+        "threadaffinity" => match value.parse::<bool>() {
+            Ok(enabled) => {
+                threadpool.set_affinity(enabled);
+                println!("info string ThreadAffinity set to {}", enabled);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "ThreadAffinity",
+                value,
+                "true or false",
+            )),
+        },
+        "sharedhistory" => match value.parse::<bool>() {
+            Ok(shared) => {
+                threadpool.set_shared_history(shared);
+                println!("info string SharedHistory set to {}", shared);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "SharedHistory",
+                value,
+                "true or false",
+            )),
+        },
+        "diagnostics" => match value.parse::<bool>() {
+            Ok(diagnostics) => {
+                Arc::make_mut(options).diagnostics = diagnostics;
+                println!("info string Diagnostics set to {}", diagnostics);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "Diagnostics",
+                value,
+                "true or false",
+            )),
+        },
+        "uci_showrefutations" => match value.parse::<bool>() {
+            Ok(enabled) => {
+                Arc::make_mut(options).show_refutations = enabled;
+                println!("info string UCI_ShowRefutations set to {}", enabled);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "UCI_ShowRefutations",
+                value,
+                "true or false",
+            )),
+        },
+        "tiebreak" => match value.parse::<bool>() {
+            Ok(enabled) => {
+                Arc::make_mut(options).tie_break = enabled;
+                println!("info string TieBreak set to {}", enabled);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "TieBreak",
+                value,
+                "true or false",
+            )),
+        },
+        "tiebreakseed" => match value.parse::<u64>() {
+            Ok(seed) => {
+                Arc::make_mut(options).tie_seed = seed;
+                println!("info string TieBreakSeed set to {}", seed);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "TieBreakSeed",
+                value,
+                "a non-negative integer",
+            )),
+        },
+        "evalnoise" => match value.parse::<i32>() {
+            Ok(noise) if (0..=EVAL_NOISE_MAX).contains(&noise) => {
+                Arc::make_mut(options).eval_noise = noise;
+                println!("info string EvalNoise set to {}", noise);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "EvalNoise",
+                value,
+                format!("between 0 and {}", EVAL_NOISE_MAX),
+            )),
+        },
+        "evalnoiseplies" => match value.parse::<usize>() {
+            Ok(plies) if (1..=MAX_PLY).contains(&plies) => {
+                Arc::make_mut(options).noise_plies = plies;
+                println!("info string EvalNoisePlies set to {}", plies);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "EvalNoisePlies",
+                value,
+                format!("between 1 and {}", MAX_PLY),
+            )),
+        },
+        "tacticalmode" => match value.parse::<bool>() {
+            Ok(enabled) => {
+                Arc::make_mut(options).tactical = enabled;
+                println!("info string TacticalMode set to {}", enabled);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "TacticalMode",
+                value,
+                "true or false",
+            )),
+        },
+        "uci_showcurrline" => match value.parse::<bool>() {
+            Ok(enabled) => {
+                Arc::make_mut(options).show_currline = enabled;
+                println!("info string UCI_ShowCurrLine set to {}", enabled);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "UCI_ShowCurrLine",
+                value,
+                "true or false",
+            )),
+        },
+        "proofnumbersearch" => match value.parse::<bool>() {
+            Ok(enabled) => {
+                Arc::make_mut(options).proof_number_search = enabled;
+                println!("info string ProofNumberSearch set to {}", enabled);
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "ProofNumberSearch",
+                value,
+                "true or false",
+            )),
+        },
+        "profile" => match SearchProfile::from_str(value) {
+            Ok(profile) => {
+                Arc::make_mut(options).profile = profile;
+                println!("info string Profile set to {}", value.to_lowercase());
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "Profile",
+                value,
+                "bullet, blitz or analysis",
+            )),
+        },
+        "outputformat" => match OutputFormat::from_str(value) {
+            Ok(output) => {
+                Arc::make_mut(options).output = output;
+                println!("info string OutputFormat set to {}", value.to_lowercase());
+                Ok(())
+            }
+            _ => Err(EngineError::invalid_option(
+                "OutputFormat",
+                value,
+                "uci, json or both",
+            )),
+        },
+        _ => Err(EngineError::UnknownOption(name.to_string())),
+    }
+}
+
+/// Warns about settings that will hurt the search without being invalid.
+fn check_configuration(ttable: &TranspositionTable, threadpool: &ThreadPool) {
+    let threads: usize = threadpool.threads;
+    let cores: usize = thread::available_parallelism().map_or(1, |n| n.get());
+    if threads > cores {
+        eprintln!(
+            "info string [warning] {} threads on {} available cores, searches will be slower.",
+            threads, cores
+        );
+    }
+
+    let recommended: usize = threads * MIN_HASH_PER_THREAD;
+    if ttable.megabytes() < recommended {
+        eprintln!(
+            "info string [warning] Hash of {} MB is small for {} threads, at least {} MB is recommended.",
+            ttable.megabytes(),
+            threads,
+            recommended
+        );
+    }
+}
+
+/// Records an accepted option so that `saveconfig` can persist it.
+fn remember(saved: &mut Vec<(String, String)>, name: String, value: String) {
+    match saved
+        .iter_mut()
+        .find(|(option, _)| option.eq_ignore_ascii_case(&name))
+    {
+        Some(entry) => entry.1 = value,
+        None => saved.push((name, value)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        RootFilter, SearchState, UCICommand, UCIError, dispatch, flipped_command,
+        normalize_castling, uci_loop,
+    };
+    use crate::{
+        evaluation::{BLACK, WHITE},
+        position::IllegalPosition,
+    };
+    use laura_core::{Board, Color, Move};
+    use std::{
+        str::FromStr,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+            mpsc::{self, Receiver, Sender},
+        },
+        thread::{self, JoinHandle},
+    };
+
+    /// Command channel between the stdin reader and the command loop.
+    type Channel = (
+        Sender<Result<UCICommand, UCIError>>,
+        Receiver<Result<UCICommand, UCIError>>,
+    );
+
+    #[test]
+    fn test_go_stop_position_sequence() {
+        let (sender, receiver): Channel = mpsc::channel();
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let state: SearchState = SearchState::default();
+
+        let loop_stop: Arc<AtomicBool> = Arc::clone(&stop);
+        let loop_state: SearchState = state.clone();
+        let handle: JoinHandle<()> = thread::spawn(move || {
+            uci_loop(receiver, loop_stop, loop_state);
+        });
+
+        // Rapid GUI traffic, including a `go` and a `position` sent while searching
+        let commands: [&str; 10] = [
+            "position startpos",
+            "go infinite",
+            "isready",
+            "position startpos moves e2e4",
+            "go infinite",
+            "stop",
+            "go infinite",
+            "stop",
+            "position startpos moves e2e4 e7e5",
+            "go depth 3",
+        ];
+        for cmd in commands {
+            assert!(dispatch(UCICommand::from_str(cmd), &sender, &stop, &state));
+        }
+
+        drop(sender);
+        handle.join().unwrap();
+        assert!(!state.busy());
+    }
+
+    #[test]
+    fn test_root_filter() {
+        let command: UCICommand =
+            UCICommand::from_str("go depth 5 excludemoves e2e4 d2d4 searchmoves e2e4 g1f3 c2c4")
+                .unwrap();
+        let UCICommand::Go(_, filter) = command else {
+            panic!("expected go");
+        };
+        assert_eq!(filter.excludemoves, ["e2e4", "d2d4"]);
+        assert_eq!(filter.searchmoves, ["e2e4", "g1f3", "c2c4"]);
+
+        // Only g1f3 and c2c4 are left to search
+        let board: Board = Board::default();
+        let excluded: Vec<Move> = filter.excluded(&board).unwrap();
+        assert_eq!(excluded.len(), 18);
+        assert!(!excluded.contains(&board.find_move("g1f3").unwrap()));
+        assert!(excluded.contains(&board.find_move("e2e4").unwrap()));
+
+        // Keywords may come after the move list, and a bare filter searches until stopped
+        assert!(matches!(
+            UCICommand::from_str("go excludemoves e2e4 movetime 100"),
+            Ok(UCICommand::Go(..))
+        ));
+        assert!(matches!(
+            UCICommand::from_str("go searchmoves e2e4"),
+            Ok(UCICommand::Go(_, RootFilter { .. }))
+        ));
+        let illegal: RootFilter = RootFilter {
+            searchmoves: vec!["e2e5".to_string()],
+            excludemoves: Vec::new(),
+        };
+        assert!(illegal.excluded(&board).is_err());
+    }
+
+    #[test]
+    fn test_flip() {
+        let command: String = flipped_command("position startpos");
+        assert_eq!(command, "position startpos moves 0000");
+        assert!(matches!(
+            UCICommand::from_str(&command),
+            Ok(UCICommand::Position(board, _, _)) if board.side() == Color::Black
+        ));
+
+        // The en passant square of the last double push is cleared
+        let command: String = flipped_command("position startpos moves e2e4");
+        let Ok(UCICommand::Position(board, history, _)) = UCICommand::from_str(&command) else {
+            panic!("flip after e2e4 failed");
+        };
+        assert_eq!(history.len(), 2);
+        assert_eq!(board.side(), Color::White);
+        assert!(board.find_move("d2d4").is_some());
+        assert!(board.enpassant_square.is_none());
+
+        // A side in check cannot pass
+        let command: String = flipped_command("position fen 4k3/8/8/8/8/8/8/4K2r w - - 0 1");
+        assert!(matches!(
+            UCICommand::from_str(&command),
+            Err(UCIError::IlegalUciMove(mv)) if mv == "0000"
+        ));
+    }
+
+    #[test]
+    fn test_perft_hash() {
+        assert!(matches!(
+            UCICommand::from_str("perft 5"),
+            Ok(UCICommand::Perft { depth: 5, hash: 0 })
+        ));
+        assert!(matches!(
+            UCICommand::from_str("perft 7 hash 64"),
+            Ok(UCICommand::Perft { depth: 7, hash: 64 })
+        ));
+        assert!(UCICommand::from_str("perft 7 hash 0").is_err());
+        assert!(UCICommand::from_str("perft 7 hash").is_err());
+        assert!(UCICommand::from_str("perft 7 size 64").is_err());
+    }
+
+    #[test]
+    fn test_cluster_listen() {
+        // Workers are not authenticated, so only localhost is bound by default
+        assert!(matches!(
+            UCICommand::from_str("cluster listen 9000"),
+            Ok(UCICommand::ClusterListen { host, port: 9000 }) if host == "127.0.0.1"
+        ));
+        assert!(matches!(
+            UCICommand::from_str("cluster listen 9000 0.0.0.0"),
+            Ok(UCICommand::ClusterListen { host, port: 9000 }) if host == "0.0.0.0"
+        ));
+        assert!(UCICommand::from_str("cluster listen").is_err());
+    }
+
+    #[test]
+    fn test_illegal_fen() {
+        let cases: [(&str, IllegalPosition); 6] = [
+            (
+                "8/8/8/8/8/8/8/4K3 w - - 0 1",
+                IllegalPosition::KingCount(BLACK),
+            ),
+            (
+                "8/8/8/8/8/8/3k4/4K3 w - - 0 1",
+                IllegalPosition::AdjacentKings,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4R1K1 w - - 0 1",
+                IllegalPosition::OpponentInCheck,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+                IllegalPosition::PawnOnBackRank,
+            ),
+            (
+                "4k3/8/8/8/8/PPPPPPPP/P7/4K3 w - - 0 1",
+                IllegalPosition::TooManyPawns(WHITE),
+            ),
+            (
+                "4k3/8/8/8/8/8/PPPPPPPP/QQ2K3 w - - 0 1",
+                IllegalPosition::TooManyPieces(WHITE),
+            ),
+        ];
+        for (fen, expected) in cases {
+            assert!(
+                matches!(
+                    UCICommand::from_str(&format!("position fen {fen}")),
+                    Err(UCIError::IllegalPosition(error)) if error == expected
+                ),
+                "{fen}"
+            );
+        }
+
+        // Checking the side to move is fine, and so is a legal promotion
+        assert!(UCICommand::from_str("position fen 4k3/8/8/8/8/8/8/4R1K1 b - - 0 1").is_ok());
+        assert!(
+            UCICommand::from_str("position fen 4k3/8/8/8/8/8/PPPPPPP1/QQ2K3 w - - 0 1").is_ok()
+        );
+    }
+
+    #[test]
+    fn test_fen_side_to_move() {
+        assert!(matches!(
+            UCICommand::from_str("position fen 4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+            Err(UCIError::InvalidPositionFormat(_))
+        ));
+        assert!(UCICommand::from_str("position fen 4k3/8/8/8/8/8/8/4K3 b - - 0 1").is_ok());
+    }
+
+    #[test]
+    fn test_xfen_castling() {
+        let start: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1";
+        assert_eq!(
+            normalize_castling(start).unwrap(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        assert_eq!(
+            normalize_castling("r3k2r/8/8/8/8/8/8/R3K2R b Ha - 0 1").unwrap(),
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1"
+        );
+
+        // Plain fields are left untouched
+        let plain: &str = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(normalize_castling(plain).unwrap(), plain);
+
+        // Letters that do not match a rook in the corner are rejected
+        for fen in [
+            "r3k2r/8/8/8/8/8/8/R3K1R1 w G - 0 1",
+            "r3k2r/8/8/8/8/8/8/4K2R w A - 0 1",
+            "r2k3r/8/8/8/8/8/8/R3K2R w a - 0 1",
+        ] {
+            assert!(matches!(
+                normalize_castling(fen),
+                Err(UCIError::InvalidCastling(_))
+            ));
+        }
+        assert!(UCICommand::from_str(&format!("position fen {start} moves e2e4")).is_ok());
+    }
+
+    #[test]
+    fn test_quit_during_search() {
+        let (sender, receiver): Channel = mpsc::channel();
+        let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let state: SearchState = SearchState::default();
+
+        let loop_stop: Arc<AtomicBool> = Arc::clone(&stop);
+        let loop_state: SearchState = state.clone();
+        let handle: JoinHandle<()> = thread::spawn(move || {
+            uci_loop(receiver, loop_stop, loop_state);
+        });
+
+        // `quit` ends the loop once the search has stopped, even with the channel still open
+        assert!(dispatch(
+            UCICommand::from_str("go infinite"),
+            &sender,
+            &stop,
+            &state
+        ));
+        stop.store(true, Ordering::SeqCst);
+        assert!(dispatch(
+            UCICommand::from_str("quit"),
+            &sender,
+            &stop,
+            &state
+        ));
+
+        handle.join().unwrap();
+        assert!(!state.busy());
+        drop(sender);
+    }
 }
@@ -0,0 +1,218 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/workers.rs
+
+//! Persistent worker threads.
+//!
+//! The OS threads are spawned once and parked on a condition variable between
+//! searches, so a `go` only has to hand them a job instead of spawning threads.
+
+use crate::affinity;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+};
+
+/// A job borrowing data that outlives [`WorkerPool::execute`].
+pub type Job<'a> = Box<dyn FnOnce() + Send + 'a>;
+
+#[derive(Default)]
+struct Slot {
+    job: Option<Job<'static>>,
+    busy: bool,
+    quit: bool,
+}
+
+struct Worker {
+    shared: Arc<(Mutex<Slot>, Condvar)>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    fn new(id: usize, cpu: Option<usize>) -> Self {
+        let shared: Arc<(Mutex<Slot>, Condvar)> = Arc::new((Mutex::default(), Condvar::new()));
+        let worker: Arc<(Mutex<Slot>, Condvar)> = Arc::clone(&shared);
+        let handle: JoinHandle<()> = thread::Builder::new()
+            .name(format!("laura-worker-{id}"))
+            .spawn(move || {
+                if let Some(cpu) = cpu
+                    && !affinity::pin(cpu)
+                {
+                    eprintln!("info string [warning] could not pin worker {id} to cpu {cpu}.");
+                }
+                worker_loop(&worker)
+            })
+            .expect("Failed to spawn worker thread");
+
+        Self {
+            shared,
+            handle: Some(handle),
+        }
+    }
+
+    fn dispatch(&self, job: Job<'static>) {
+        let (lock, condvar) = &*self.shared;
+        let mut slot: MutexGuard<'_, Slot> = lock.lock().unwrap();
+        slot.job = Some(job);
+        slot.busy = true;
+        condvar.notify_all();
+    }
+
+    fn wait(&self) {
+        let (lock, condvar) = &*self.shared;
+        let mut slot: MutexGuard<'_, Slot> = lock.lock().unwrap();
+        while slot.busy {
+            slot = condvar.wait(slot).unwrap();
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        {
+            let (lock, condvar) = &*self.shared;
+            let mut slot: MutexGuard<'_, Slot> = lock.lock().unwrap();
+            slot.quit = true;
+            condvar.notify_all();
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn worker_loop(shared: &(Mutex<Slot>, Condvar)) {
+    let (lock, condvar) = shared;
+    loop {
+        let job: Job<'static> = {
+            let mut slot: MutexGuard<'_, Slot> = lock.lock().unwrap();
+            loop {
+                if slot.quit {
+                    return;
+                }
+                if let Some(job) = slot.job.take() {
+                    break job;
+                }
+                slot = condvar.wait(slot).unwrap();
+            }
+        };
+
+        // The job has to be reported as finished even if it panics,
+        // since `execute` relies on it to keep the borrows alive.
+        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+
+        let mut slot: MutexGuard<'_, Slot> = lock.lock().unwrap();
+        slot.busy = false;
+        condvar.notify_all();
+    }
+}
+
+#[derive(Default)]
+pub struct WorkerPool {
+    workers: Vec<Worker>,
+}
+
+impl std::fmt::Debug for WorkerPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WorkerPool({})", self.workers.len())
+    }
+}
+
+impl WorkerPool {
+    pub fn new(threads: usize) -> Self {
+        Self {
+            workers: (0..threads.max(1))
+                .map(|id| Worker::new(id, None))
+                .collect(),
+        }
+    }
+
+    /// Spawns workers pinned round-robin to the cpus of every NUMA node.
+    pub fn pinned(threads: usize) -> Self {
+        let cpus: Vec<usize> = affinity::cpu_order();
+        Self {
+            workers: (0..threads.max(1))
+                .map(|id| Worker::new(id, Some(cpus[id % cpus.len()])))
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    /// Runs one job per worker and blocks until all of them have finished.
+    pub fn execute<'a>(&self, jobs: Vec<Job<'a>>) {
+        assert!(jobs.len() <= self.workers.len(), "More jobs than workers");
+
+        for (worker, job) in self.workers.iter().zip(jobs) {
+            // SAFETY: the job only borrows data living for 'a, and this function
+            // does not return before every dispatched job has finished running.
+            let job: Job<'static> = unsafe { std::mem::transmute::<Job<'a>, Job<'static>>(job) };
+            worker.dispatch(job);
+        }
+
+        for worker in self.workers.iter() {
+            worker.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Job, WorkerPool};
+    use std::thread::{self, ThreadId};
+
+    #[test]
+    fn test_workers_are_reused() {
+        let pool: WorkerPool = WorkerPool::new(4);
+        let mut first: Vec<Option<ThreadId>> = vec![None; 4];
+        let mut second: Vec<Option<ThreadId>> = vec![None; 4];
+
+        for ids in [&mut first, &mut second] {
+            let jobs: Vec<Job<'_>> = ids
+                .iter_mut()
+                .map(|id| -> Job<'_> { Box::new(move || *id = Some(thread::current().id())) })
+                .collect();
+            pool.execute(jobs);
+        }
+
+        assert!(first.iter().all(Option::is_some));
+        assert_eq!(first, second);
+        assert!(!first.contains(&Some(thread::current().id())));
+    }
+
+    #[test]
+    fn test_pinned_workers() {
+        let pool: WorkerPool = WorkerPool::pinned(2);
+        let mut done: [bool; 2] = [false; 2];
+        let jobs: Vec<Job<'_>> = done
+            .iter_mut()
+            .map(|done| -> Job<'_> { Box::new(move || *done = true) })
+            .collect();
+        pool.execute(jobs);
+        assert_eq!(done, [true; 2]);
+    }
+}
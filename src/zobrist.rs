@@ -0,0 +1,141 @@
+/*
+    Laura: A multi-threaded UCI chess engine written in Rust.
+
+    Copyright (C) 2024-2026 HansTibberio <hanstiberio@proton.me>
+
+    Laura is free software: you can redistribute it and/or modify
+    it under the terms of the GNU General Public License as published by
+    the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    Laura is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+    GNU General Public License for more details.
+
+    You should have received a copy of the GNU General Public License
+    along with Laura. If not, see <https://www.gnu.org/licenses/>.
+*/
+
+// src/zobrist.rs
+
+//! Zobrist key helpers built on laura_core's public hashing.
+
+use laura_core::{Board, Color, File, Move, MoveType, Piece, PieceType, Zobrist};
+
+/// Key of `board` derived from scratch, to check the incremental updates.
+pub fn recompute_key(board: &Board) -> u64 {
+    let mut key: Zobrist = Zobrist::null();
+    for square in board.combined_bitboard() {
+        if let Some(piece) = board.piece_on(square) {
+            key.hash_piece(piece, square);
+        }
+    }
+
+    if board.side == Color::White {
+        key.hash_side();
+    }
+    key.hash_castle(board.castling);
+    if let Some(square) = board.enpassant_square {
+        key.hash_enpassant(square);
+    }
+    key.0
+}
+
+/// Key of the position after `mv`, without building the child board.
+///
+/// Mirrors the incremental update of `Board::make_move` through the public
+/// [`Zobrist`] hashing methods, so the child entry can be prefetched before
+/// the move is made.
+pub fn key_after(board: &Board, mv: Move) -> u64 {
+    let mut key: Zobrist = board.zobrist;
+    let (src, dest) = (mv.get_src(), mv.get_dest());
+    let Some(piece) = board.piece_on(src) else {
+        return key.0;
+    };
+
+    key.hash_piece(piece, src);
+    match mv.get_type() {
+        MoveType::EnPassant => {
+            let pawn: Piece = Piece::new(PieceType::Pawn, !board.side);
+            key.hash_piece(pawn, dest.forward(!board.side));
+        }
+        MoveType::KingCastle | MoveType::QueenCastle => {
+            let rook: Piece = Piece::new(PieceType::Rook, board.side);
+            let (rook_src, rook_dest) = if dest.file() == File::G {
+                (dest.right(), dest.left())
+            } else {
+                (dest.left().left(), dest.right())
+            };
+            key.hash_piece(rook, rook_src);
+            key.hash_piece(rook, rook_dest);
+        }
+        _ if mv.is_capture() => {
+            if let Some(victim) = board.piece_on(dest) {
+                key.hash_piece(victim, dest);
+            }
+        }
+        _ => {}
+    }
+
+    if mv.is_promotion() {
+        key.hash_piece(mv.get_prom(board.side), dest);
+    } else {
+        key.hash_piece(piece, dest);
+    }
+
+    if let Some(square) = board.enpassant_square {
+        key.hash_enpassant(square);
+    }
+    if mv.get_type() == MoveType::DoublePawn {
+        key.hash_enpassant(src.forward(board.side));
+    }
+
+    key.swap_castle_hash(board.castling, board.castling.update(src, dest));
+    key.hash_side();
+    key.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::{key_after, recompute_key};
+    use crate::movelist::for_each_legal_move;
+    use laura_core::Board;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_recompute_key() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ] {
+            let board: Board = Board::from_str(fen).unwrap();
+            assert_eq!(recompute_key(&board), board.zobrist.0, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_key_after() {
+        // Castling, en passant, promotions with and without capture
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ] {
+            let board: Board = Board::from_str(fen).unwrap();
+            for_each_legal_move(&board, |mv| {
+                let child: Board = board.make_move(mv);
+                assert_eq!(key_after(&board, mv), child.zobrist.0, "{fen} {mv}");
+                assert_eq!(recompute_key(&child), child.zobrist.0, "{fen} {mv}");
+                for_each_legal_move(&child, |reply| {
+                    assert_eq!(
+                        key_after(&child, reply),
+                        child.make_move(reply).zobrist.0,
+                        "{fen} {mv} {reply}"
+                    );
+                });
+            });
+        }
+    }
+}